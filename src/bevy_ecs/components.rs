@@ -0,0 +1,73 @@
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use macroquad::prelude::*;
+
+use crate::functional::balloon::BALLOON_COLLIDER_SIZE;
+use crate::functional::projectile::PROJECTILE_SIZE;
+use crate::functional::tower::TOWER_SIZE;
+
+#[derive(Component, Copy, Clone)]
+pub struct Position(pub Vec2);
+
+// Where a moving entity was on the previous tick, for interpolated drawing.
+#[derive(Component, Copy, Clone)]
+pub struct PreviousPosition(pub Vec2);
+
+// Queries iterate in storage order, which changes as entities are despawned,
+// so every entity records when it was spawned to recover a stable order.
+#[derive(Component, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SpawnOrder(pub u64);
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum BalloonState {
+    Alive,
+    Popped,
+    Escaped,
+}
+
+#[derive(Component, Copy, Clone)]
+pub struct Balloon {
+    pub speed: f32,
+    pub state: BalloonState,
+}
+
+#[derive(Component, Copy, Clone)]
+pub struct Tower {
+    pub angle: f32,
+    pub shot_cooldown: f32,
+    pub pop_count: u32,
+    pub level: u32,
+    pub spent: u32,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ProjectileState {
+    Alive,
+    Dead,
+    Hit,
+}
+
+#[derive(Component, Copy, Clone)]
+pub struct Projectile {
+    pub direction: Vec2,
+    pub state: ProjectileState,
+    // The tower that fired it, credited with the pops.
+    pub tower: Entity,
+}
+
+pub fn is_point_on_tower(position: Vec2, point: Vec2) -> bool {
+    position.distance(point) <= TOWER_SIZE
+}
+
+// Footprints are circles, so they overlap when closer than two radii.
+pub fn do_towers_overlap(position: Vec2, other: Vec2) -> bool {
+    position.distance(other) < TOWER_SIZE * 2.
+}
+
+pub fn check_collision(projectile_position: Vec2, balloon_position: Vec2) -> bool {
+    let distance_x = (projectile_position.x - balloon_position.x).abs();
+    let distance_y = (projectile_position.y - balloon_position.y).abs();
+
+    distance_x <= BALLOON_COLLIDER_SIZE + PROJECTILE_SIZE
+        && distance_y <= BALLOON_COLLIDER_SIZE + PROJECTILE_SIZE
+}
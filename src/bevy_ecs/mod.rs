@@ -0,0 +1,6 @@
+pub mod components;
+pub mod render;
+pub mod resources;
+pub mod scene;
+pub mod schedules;
+pub mod systems;
@@ -0,0 +1,442 @@
+// Drawing only reads the world, so it walks the entities directly instead of
+// running a schedule, which would need mutable access.
+
+use bevy::ecs::world::World;
+use macroquad::prelude::*;
+
+use crate::atlas::draw_sprite;
+use crate::atlas::is_action_playing;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::functional::balloon::BALLOON_COLLIDER_SIZE;
+use crate::functional::balloon::BALLOON_SIZE;
+use crate::functional::difficulty::difficulty_name;
+use crate::functional::difficulty::final_round;
+use crate::functional::difficulty::scale_price;
+use crate::functional::difficulty::DIFFICULTIES;
+use crate::functional::game_mode::allows_continues;
+use crate::functional::game_mode::game_mode_name;
+use crate::functional::game_mode::has_infinite_resources;
+use crate::functional::game_mode::GAME_MODES;
+use crate::functional::map::MAPS;
+use crate::functional::projectile::PROJECTILE_SIZE;
+use crate::functional::scene::TOWER_COST;
+use crate::functional::screen::Screen;
+use crate::functional::sprites::Sprites;
+use crate::functional::tower::TOWER_SIZE;
+use crate::guides::draw_placement_guides;
+use crate::guides::draw_range;
+use crate::guides::TowerGuides;
+use crate::placement::draw_grid;
+use crate::shop::draw_shop;
+use crate::shop::is_on_sidebar;
+use crate::view::set_interface_camera;
+use crate::view::set_view_camera;
+use crate::view::View;
+use crate::view::INTERFACE_HEIGHT;
+use crate::view::INTERFACE_WIDTH;
+use crate::world::distance_to_world_edge;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
+use super::components::is_point_on_tower;
+use super::components::Balloon;
+use super::components::Position;
+use super::components::PreviousPosition;
+use super::components::Projectile;
+use super::components::SpawnOrder;
+use super::components::Tower;
+
+use super::resources::CommandLog;
+use super::resources::Economy;
+use super::resources::Game;
+use super::resources::Placement;
+use super::resources::Rounds;
+use super::resources::Tick;
+
+use super::systems::is_free_for_tower;
+
+fn draw_message(text: &str) {
+    clear_background(WHITE);
+
+    let font_size = 30.;
+    let text_size = measure_text(text, None, font_size as _, 1.0);
+
+    draw_text(
+        text,
+        INTERFACE_WIDTH / 2. - text_size.width / 2.,
+        WORLD_HEIGHT / 2. + text_size.height / 2.,
+        font_size,
+        DARKGRAY,
+    );
+}
+
+fn draw_menu(title: &str, options: Vec<&str>) {
+    clear_background(WHITE);
+
+    let font_size = 30.;
+    let title_size = measure_text(title, None, font_size as _, 1.0);
+
+    draw_text(
+        title,
+        INTERFACE_WIDTH / 2. - title_size.width / 2.,
+        WORLD_HEIGHT / 4.,
+        font_size,
+        DARKGRAY,
+    );
+
+    options.iter().enumerate().for_each(|(index, option)| {
+        let line = format!("[{}] {}", index + 1, option);
+        let line_size = measure_text(line.as_str(), None, font_size as _, 1.0);
+
+        draw_text(
+            line.as_str(),
+            INTERFACE_WIDTH / 2. - line_size.width / 2.,
+            WORLD_HEIGHT / 4. + font_size * 1.5 * (index + 1) as f32,
+            font_size,
+            DARKGRAY,
+        );
+    });
+}
+
+fn draw_background(world: &World, sprites: &Sprites) {
+    clear_background(LIGHTGRAY);
+
+    let sprite = &sprites.maps[world.resource::<Game>().map];
+
+    let scale_factor = WORLD_WIDTH / sprite.width();
+    let adjusted_width = sprite.width() * scale_factor;
+    let adjusted_height = sprite.height() * scale_factor;
+
+    let x = (WORLD_WIDTH - adjusted_width) / 2.0;
+    let y = (WORLD_HEIGHT - adjusted_height) / 2.0;
+
+    draw_texture_ex(
+        sprite,
+        x,
+        y,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(Vec2::new(adjusted_width, adjusted_height)),
+            ..Default::default()
+        },
+    );
+}
+
+fn draw_statistics(world: &World) {
+    let game = world.resource::<Game>();
+    let economy = world.resource::<Economy>();
+    let rounds = world.resource::<Rounds>();
+
+    let (coins, lives) = if has_infinite_resources(game.game_mode) {
+        (String::from("INF"), String::from("INF"))
+    } else {
+        (economy.coins.to_string(), economy.lives.to_string())
+    };
+
+    draw_text(format!("COINS: {}", coins).as_str(), 10., 32., 32., WHITE);
+
+    draw_text(format!("LIVES: {}", lives).as_str(), 10., 64., 32., WHITE);
+
+    draw_text(
+        format!("ROUND: {}/{}", rounds.round, final_round(game.difficulty)).as_str(),
+        10.,
+        96.,
+        32.,
+        WHITE,
+    );
+
+    draw_text(
+        format!("SPEED: {}x", game.game_speed).as_str(),
+        10.,
+        128.,
+        32.,
+        WHITE,
+    );
+
+    draw_text(
+        format!(
+            "{} - {} - {}",
+            MAPS[game.map].name,
+            difficulty_name(game.difficulty),
+            game_mode_name(game.game_mode)
+        )
+        .as_str(),
+        10.,
+        160.,
+        32.,
+        WHITE,
+    );
+}
+
+fn draw_sidebar(world: &World) {
+    let game = world.resource::<Game>();
+    let economy = world.resource::<Economy>();
+    let tick = world.resource::<Tick>();
+
+    let coins = (!has_infinite_resources(game.game_mode)).then_some(economy.coins);
+
+    draw_shop(
+        &[scale_price(game.difficulty, TOWER_COST)],
+        coins,
+        tick.keys.interface_pointer_position,
+    );
+}
+
+// Towers play their attack after every shot, counted from their cooldown.
+fn draw_tower(sheet: &SpriteSheet, position: Vec2, tower: &Tower, is_disabled: bool) {
+    let color = if is_disabled {
+        GRAY
+    } else {
+        match tower.level {
+            1 => BLUE,
+            2 => GREEN,
+            3 => YELLOW,
+            4 => ORANGE,
+            _ => RED,
+        }
+    };
+
+    let time_since_shot = 2. / tower.level as f32 - tower.shot_cooldown;
+
+    let action = if is_action_playing(
+        sheet,
+        SpriteKind::Tower,
+        SpriteAction::Attack,
+        time_since_shot,
+    ) {
+        SpriteAction::Attack
+    } else {
+        SpriteAction::Idle
+    };
+
+    draw_sprite(
+        sheet,
+        SpriteKind::Tower,
+        action,
+        time_since_shot,
+        position,
+        TOWER_SIZE * 4.,
+        tower.angle,
+        color,
+    );
+}
+
+// Darts fly until they leave the world, so a tower reaches as far as the edge
+// of the world in the direction it aims.
+fn tower_guides(position: Vec2, angle: f32) -> TowerGuides {
+    TowerGuides {
+        position,
+        angle,
+        footprint: TOWER_SIZE,
+        range: distance_to_world_edge(position, Vec2::from_angle(angle)),
+        lane_width: PROJECTILE_SIZE * 2.,
+    }
+}
+
+// Shows the range of the earliest placed tower under the pointer.
+fn draw_hovered_tower_range(world: &World) {
+    let pointer_position = world.resource::<Tick>().keys.pointer_position;
+
+    let hovered_tower = world
+        .iter_entities()
+        .filter(|entity| entity.contains::<Tower>())
+        .filter_map(|entity| {
+            Some((
+                entity.get::<Position>()?.0,
+                entity.get::<Tower>()?.angle,
+                *entity.get::<SpawnOrder>()?,
+            ))
+        })
+        .filter(|(position, _, _)| is_point_on_tower(*position, pointer_position))
+        .min_by_key(|(_, _, spawn_order)| *spawn_order);
+
+    if let Some((position, angle, _)) = hovered_tower {
+        draw_range(position, tower_guides(position, angle).range);
+    }
+}
+
+fn draw_world(world: &World, sprites: &Sprites, alpha: f32, view: &View) {
+    set_view_camera(view);
+    draw_background(world, sprites);
+
+    world.iter_entities().for_each(|entity| {
+        let Some(position) = entity.get::<Position>() else {
+            return;
+        };
+
+        let interpolated_position = entity
+            .get::<PreviousPosition>()
+            .map_or(position.0, |previous_position| {
+                previous_position.0.lerp(position.0, alpha)
+            });
+
+        if entity.contains::<Balloon>() {
+            draw_sprite(
+                &sprites.sheet,
+                SpriteKind::Balloon,
+                SpriteAction::Idle,
+                get_time() as f32,
+                interpolated_position,
+                BALLOON_SIZE,
+                0.,
+                WHITE,
+            );
+
+            draw_circle_lines(
+                interpolated_position.x,
+                interpolated_position.y,
+                BALLOON_COLLIDER_SIZE,
+                1.,
+                RED,
+            );
+        }
+
+        if let Some(tower) = entity.get::<Tower>() {
+            draw_tower(&sprites.sheet, position.0, tower, false);
+        }
+
+        if let Some(projectile) = entity.get::<Projectile>() {
+            draw_sprite(
+                &sprites.sheet,
+                SpriteKind::Projectile,
+                SpriteAction::Idle,
+                0.,
+                interpolated_position,
+                PROJECTILE_SIZE * 2.,
+                projectile.direction.y.atan2(projectile.direction.x),
+                WHITE,
+            );
+        }
+    });
+
+    let game = world.resource::<Game>();
+    let economy = world.resource::<Economy>();
+    let placement = world.resource::<Placement>();
+
+    if let (true, Some(preview_tower)) = (placement.is_placing_tower, placement.preview_tower) {
+        let tower_cost = scale_price(game.difficulty, TOWER_COST);
+        let can_afford = has_infinite_resources(game.game_mode) || economy.coins >= tower_cost;
+
+        if let Some(grid_size) = world.resource::<Tick>().keys.grid_size {
+            draw_grid(grid_size);
+        }
+
+        let towers = world
+            .iter_entities()
+            .filter(|entity| entity.contains::<Tower>())
+            .filter_map(|entity| Some(entity.get::<Position>()?.0));
+
+        let is_valid = can_afford
+            && is_free_for_tower(preview_tower.position, towers)
+            && !is_on_sidebar(world.resource::<Tick>().keys.interface_pointer_position);
+
+        let tower = Tower {
+            angle: preview_tower.angle,
+            shot_cooldown: 0.,
+            pop_count: 0,
+            level: 1,
+            spent: 0,
+        };
+
+        draw_tower(&sprites.sheet, preview_tower.position, &tower, !is_valid);
+        draw_placement_guides(
+            &tower_guides(preview_tower.position, preview_tower.angle),
+            is_valid,
+        );
+    } else {
+        draw_hovered_tower_range(world);
+    }
+
+    set_interface_camera();
+}
+
+fn draw_undo_hint(world: &World) {
+    if world.resource::<CommandLog>().0.is_empty() {
+        return;
+    }
+
+    draw_text(
+        "Press [z] to undo the last placement or upgrade",
+        10.,
+        WORLD_HEIGHT - 52.,
+        24.,
+        WHITE,
+    );
+}
+
+pub fn draw_scene(world: &World, sprites: &Sprites, alpha: f32, view: &View) {
+    let game = world.resource::<Game>();
+
+    match game.screen {
+        Screen::MainMenu => draw_message("Balloons. Press [enter] to start."),
+        Screen::MapSelect => draw_menu("Select a map:", MAPS.iter().map(|map| map.name).collect()),
+        Screen::DifficultySelect => draw_menu(
+            "Select a difficulty:",
+            DIFFICULTIES
+                .iter()
+                .map(|difficulty| difficulty_name(*difficulty))
+                .collect(),
+        ),
+        Screen::GameModeSelect => draw_menu(
+            "Select a game mode:",
+            GAME_MODES
+                .iter()
+                .map(|game_mode| game_mode_name(*game_mode))
+                .collect(),
+        ),
+        Screen::BetweenRounds => {
+            draw_world(world, sprites, 1., view);
+            draw_statistics(world);
+            draw_sidebar(world);
+            draw_undo_hint(world);
+
+            draw_text(
+                "Press [space] to start the next round",
+                10.,
+                WORLD_HEIGHT - 20.,
+                32.,
+                WHITE,
+            );
+        }
+        Screen::Playing => {
+            draw_world(world, sprites, alpha, view);
+            draw_statistics(world);
+            draw_sidebar(world);
+        }
+        Screen::Paused => {
+            draw_world(world, sprites, 1., view);
+            draw_statistics(world);
+            draw_sidebar(world);
+
+            draw_rectangle(
+                0.,
+                0.,
+                INTERFACE_WIDTH,
+                INTERFACE_HEIGHT,
+                Color::new(0., 0., 0., 0.5),
+            );
+
+            let text = "Paused. Press [p] to resume or [n] to step a frame.";
+            let font_size = 30.;
+            let text_size = measure_text(text, None, font_size as _, 1.0);
+
+            draw_text(
+                text,
+                INTERFACE_WIDTH / 2. - text_size.width / 2.,
+                WORLD_HEIGHT / 2. + text_size.height / 2.,
+                font_size,
+                WHITE,
+            );
+        }
+        Screen::Victory => draw_message("Victory! Press [enter] to return to the main menu."),
+        Screen::Defeat => {
+            if allows_continues(game.game_mode) {
+                draw_message("Game Over. Press [enter] to play again or [c] to continue.");
+            } else {
+                draw_message("Game Over. Press [enter] to play again.");
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::ecs::system::Resource;
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+use crate::input::Keys;
+use crate::input::NO_KEYS;
+
+use crate::functional::difficulty::starting_cash;
+use crate::functional::difficulty::starting_lives;
+use crate::functional::difficulty::Difficulty;
+use crate::functional::game_mode::GameMode;
+use crate::functional::random::Rng;
+use crate::functional::screen::Screen;
+
+use super::components::SpawnOrder;
+
+// The input of the tick being simulated.
+#[derive(Resource, Clone)]
+pub struct Tick {
+    pub delta_time: f32,
+    pub keys: Keys,
+}
+
+#[derive(Resource, Clone)]
+pub struct Game {
+    pub screen: Screen,
+    pub game_speed: u32,
+    pub map: usize,
+    pub difficulty: Difficulty,
+    pub game_mode: GameMode,
+}
+
+#[derive(Resource, Clone)]
+pub struct Economy {
+    pub coins: u32,
+    pub income_remainder: f32,
+    pub lives: i32,
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct Rounds {
+    pub round: u32,
+    pub balloons_to_spawn: u32,
+    pub spawn_timer: f32,
+}
+
+#[derive(Copy, Clone)]
+pub struct PreviewTower {
+    pub position: Vec2,
+    pub angle: f32,
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct Placement {
+    pub is_placing_tower: bool,
+    pub preview_tower: Option<PreviewTower>,
+    // Where the pointer was pressed while placing, for aiming by dragging.
+    pub drag_start: Option<Vec2>,
+    // Where the arrow keys moved the preview to, until the pointer moves.
+    pub nudged_position: Option<Vec2>,
+}
+
+// Towers are referred to by spawn order, which survives copying the world,
+// unlike their entities.
+#[derive(Copy, Clone)]
+pub enum TowerCommand {
+    PlaceTower { tower: SpawnOrder, cost: u32 },
+    UpgradeTower { tower: SpawnOrder, cost: u32 },
+}
+
+// What happened during the last step, for subscribers outside the
+// simulation.
+#[derive(Resource, Clone, Default)]
+pub struct StepEvents(pub Vec<GameEvent>);
+
+// The placements and upgrades made since the last round, newest last, which
+// can be undone until the next one starts.
+#[derive(Resource, Clone, Default)]
+pub struct CommandLog(pub Vec<TowerCommand>);
+
+#[derive(Resource, Clone)]
+pub struct RandomNumbers(pub Rng);
+
+#[derive(Resource, Clone, Default)]
+pub struct NextSpawnOrder(u64);
+
+impl NextSpawnOrder {
+    pub fn allocate(&mut self) -> SpawnOrder {
+        self.0 += 1;
+
+        SpawnOrder(self.0)
+    }
+}
+
+pub const INITIAL_TICK: Tick = Tick {
+    delta_time: 0.,
+    keys: NO_KEYS,
+};
+
+pub const INITIAL_GAME: Game = Game {
+    screen: Screen::MainMenu,
+    game_speed: 1,
+    map: 0,
+    difficulty: Difficulty::Medium,
+    game_mode: GameMode::Standard,
+};
+
+pub const INITIAL_ECONOMY: Economy = Economy {
+    coins: starting_cash(Difficulty::Medium),
+    income_remainder: 0.,
+    lives: starting_lives(Difficulty::Medium),
+};
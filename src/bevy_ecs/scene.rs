@@ -0,0 +1,487 @@
+// this is the entity component system version of the Scene, built on the
+// bevy ECS without the rest of the engine so it runs headlessly
+
+use std::collections::HashMap;
+use std::io::Error;
+
+use bevy::ecs::world::World;
+
+use crate::events::GameEvent;
+use crate::input::Keys;
+use crate::simulation::GameSimulation;
+use crate::snapshot::GameSnapshot;
+use crate::snapshot::PlacementSnapshot;
+use crate::snapshot::PreviewTowerSnapshot;
+use crate::snapshot::TowerSnapshot;
+use crate::view::View;
+
+use crate::functional::difficulty::difficulty_from_id;
+use crate::functional::difficulty::difficulty_id;
+use crate::functional::game_mode::game_mode_from_id;
+use crate::functional::game_mode::game_mode_id;
+use crate::functional::map::MAPS;
+use crate::functional::random::new_rng;
+use crate::functional::random::rng_state;
+use crate::functional::save::invalid_data;
+use crate::functional::save::read_save_file;
+use crate::functional::save::write_save_file;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+use crate::functional::scene::MAX_GAME_SPEED;
+use crate::functional::screen::screen_snapshot;
+use crate::functional::screen::Screen;
+use crate::functional::sprites::Sprites;
+use crate::functional::statistics::decode_statistics;
+use crate::functional::statistics::encode_statistics;
+use crate::functional::statistics::INITIAL_STATISTICS;
+
+use super::components::Balloon;
+use super::components::BalloonState;
+use super::components::Position;
+use super::components::PreviousPosition;
+use super::components::Projectile;
+use super::components::ProjectileState;
+use super::components::SpawnOrder;
+use super::components::Tower;
+
+use super::render::draw_scene;
+
+use super::resources::CommandLog;
+use super::resources::Economy;
+use super::resources::Game;
+use super::resources::NextSpawnOrder;
+use super::resources::Placement;
+use super::resources::RandomNumbers;
+use super::resources::Rounds;
+use super::resources::StepEvents;
+use super::resources::Tick;
+use super::resources::INITIAL_ECONOMY;
+use super::resources::INITIAL_GAME;
+use super::resources::INITIAL_TICK;
+
+use super::schedules::add_schedules;
+use super::schedules::update_screen;
+
+pub struct Scene {
+    world: World,
+}
+
+fn new_world() -> World {
+    let mut world = World::new();
+
+    add_schedules(&mut world);
+
+    world
+}
+
+fn sorted_by_spawn_order<T>(mut items: Vec<(SpawnOrder, T)>) -> Vec<T> {
+    items.sort_by_key(|(spawn_order, _)| *spawn_order);
+
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+impl Scene {
+    pub fn new(seed: u64) -> Self {
+        let mut world = new_world();
+
+        world.insert_resource(INITIAL_TICK);
+        world.insert_resource(INITIAL_GAME);
+        world.insert_resource(INITIAL_ECONOMY);
+        world.insert_resource(Rounds::default());
+        world.insert_resource(Placement::default());
+        world.insert_resource(CommandLog::default());
+        world.insert_resource(RandomNumbers(new_rng(seed)));
+        world.insert_resource(NextSpawnOrder::default());
+        world.insert_resource(StepEvents::default());
+
+        Self { world }
+    }
+
+    pub fn update(&mut self, delta_time: f32, keys: Keys) {
+        self.world.insert_resource(Tick { delta_time, keys });
+        self.world.resource_mut::<StepEvents>().0.clear();
+
+        let screen = self.world.resource::<Game>().screen;
+
+        self.world.run_schedule(update_screen(screen));
+    }
+
+    fn can_save(&self) -> bool {
+        matches!(
+            self.world.resource::<Game>().screen,
+            Screen::BetweenRounds | Screen::Playing | Screen::Paused
+        )
+    }
+
+    // Written in the same layout as the functional version, so saves load in
+    // either. Entities are written in spawn order, each tower followed by the
+    // projectiles it fired. The statistics are only kept by the functional
+    // version, so empty ones are written here and loaded ones are dropped.
+    fn encode(&self, writer: &mut SaveWriter) {
+        let game = self.world.resource::<Game>();
+        let economy = self.world.resource::<Economy>();
+        let rounds = self.world.resource::<Rounds>();
+
+        let mut balloons = Vec::new();
+        let mut towers = Vec::new();
+        let mut projectiles = Vec::new();
+
+        self.world.iter_entities().for_each(|entity| {
+            let spawn_order = *entity.get::<SpawnOrder>().unwrap();
+            let position = entity.get::<Position>().unwrap().0;
+
+            if let Some(balloon) = entity.get::<Balloon>() {
+                let previous_position = entity.get::<PreviousPosition>().unwrap().0;
+
+                balloons.push((spawn_order, (position, previous_position, *balloon)));
+            }
+
+            if let Some(tower) = entity.get::<Tower>() {
+                towers.push((spawn_order, (entity.id(), position, *tower)));
+            }
+
+            if let Some(projectile) = entity.get::<Projectile>() {
+                let previous_position = entity.get::<PreviousPosition>().unwrap().0;
+
+                projectiles.push((spawn_order, (position, previous_position, *projectile)));
+            }
+        });
+
+        let balloons = sorted_by_spawn_order(balloons);
+        let towers = sorted_by_spawn_order(towers);
+        let projectiles = sorted_by_spawn_order(projectiles);
+
+        // A game saved mid-round is restored paused, so the player is not
+        // dropped straight into the action when loading it.
+        writer.write_bool(game.screen == Screen::BetweenRounds);
+        writer.write_u32(game.map as u32);
+        writer.write_u8(difficulty_id(game.difficulty));
+        writer.write_u8(game_mode_id(game.game_mode));
+        writer.write_u32(economy.coins);
+        writer.write_f32(economy.income_remainder);
+        writer.write_i32(economy.lives);
+        writer.write_u32(rounds.round);
+        writer.write_u32(rounds.balloons_to_spawn);
+        writer.write_f32(rounds.spawn_timer);
+        writer.write_u32(game.game_speed);
+        writer.write_u64(rng_state(self.world.resource::<RandomNumbers>().0));
+
+        writer.write_u32(balloons.len() as u32);
+        balloons
+            .iter()
+            .for_each(|(position, previous_position, balloon)| {
+                writer.write_vec2(*position);
+                writer.write_vec2(*previous_position);
+                // Balloons only move right.
+                writer.write_u8(0);
+                writer.write_f32(balloon.speed);
+                writer.write_u8(match balloon.state {
+                    BalloonState::Alive => 0,
+                    BalloonState::Popped => 1,
+                    BalloonState::Escaped => 2,
+                });
+            });
+
+        writer.write_u32(towers.len() as u32);
+        towers.iter().for_each(|(entity, position, tower)| {
+            let tower_projectiles = projectiles
+                .iter()
+                .filter(|(_, _, projectile)| projectile.tower == *entity)
+                .collect::<Vec<_>>();
+
+            writer.write_vec2(*position);
+            writer.write_f32(tower.angle);
+            writer.write_f32(tower.shot_cooldown);
+            writer.write_u32(tower.pop_count);
+            writer.write_u32(tower.level);
+            writer.write_u32(tower.spent);
+            writer.write_u32(tower_projectiles.len() as u32);
+
+            tower_projectiles
+                .iter()
+                .for_each(|(position, previous_position, projectile)| {
+                    writer.write_vec2(*position);
+                    writer.write_vec2(*previous_position);
+                    writer.write_vec2(projectile.direction);
+                    writer.write_u8(match projectile.state {
+                        ProjectileState::Alive => 0,
+                        ProjectileState::Dead => 1,
+                        ProjectileState::Hit => 2,
+                    });
+                });
+        });
+
+        encode_statistics(&INITIAL_STATISTICS, writer);
+    }
+
+    fn decode(reader: &mut SaveReader) -> Result<Self, Error> {
+        let mut scene = Self::new(0);
+        let world = &mut scene.world;
+
+        let screen = match reader.read_bool()? {
+            true => Screen::BetweenRounds,
+            false => Screen::Paused,
+        };
+
+        let map = reader.read_u32()? as usize;
+
+        if map >= MAPS.len() {
+            return Err(invalid_data("Invalid map"));
+        }
+
+        let difficulty = difficulty_from_id(reader.read_u8()?)
+            .ok_or_else(|| invalid_data("Invalid difficulty"))?;
+        let game_mode = game_mode_from_id(reader.read_u8()?)
+            .ok_or_else(|| invalid_data("Invalid game mode"))?;
+
+        world.insert_resource(Economy {
+            coins: reader.read_u32()?,
+            income_remainder: reader.read_f32()?,
+            lives: reader.read_i32()?,
+        });
+        world.insert_resource(Rounds {
+            round: reader.read_u32()?,
+            balloons_to_spawn: reader.read_u32()?,
+            spawn_timer: reader.read_f32()?,
+        });
+        world.insert_resource(Game {
+            screen,
+            game_speed: reader.read_u32()?.clamp(1, MAX_GAME_SPEED),
+            map,
+            difficulty,
+            game_mode,
+        });
+        world.insert_resource(RandomNumbers(new_rng(reader.read_u64()?)));
+
+        let balloons_count = reader.read_u32()?;
+
+        for _ in 0..balloons_count {
+            let position = reader.read_vec2()?;
+            let previous_position = reader.read_vec2()?;
+
+            if reader.read_u8()? != 0 {
+                return Err(invalid_data("Invalid balloon direction"));
+            }
+
+            let balloon = Balloon {
+                speed: reader.read_f32()?,
+                state: match reader.read_u8()? {
+                    0 => BalloonState::Alive,
+                    1 => BalloonState::Popped,
+                    2 => BalloonState::Escaped,
+                    _ => return Err(invalid_data("Invalid balloon state")),
+                },
+            };
+            let spawn_order = world.resource_mut::<NextSpawnOrder>().allocate();
+
+            world.spawn((
+                balloon,
+                Position(position),
+                PreviousPosition(previous_position),
+                spawn_order,
+            ));
+        }
+
+        let towers_count = reader.read_u32()?;
+
+        for _ in 0..towers_count {
+            let position = reader.read_vec2()?;
+            let tower = Tower {
+                angle: reader.read_f32()?,
+                shot_cooldown: reader.read_f32()?,
+                pop_count: reader.read_u32()?,
+                level: reader.read_u32()?,
+                spent: reader.read_u32()?,
+            };
+            let spawn_order = world.resource_mut::<NextSpawnOrder>().allocate();
+            let entity = world.spawn((tower, Position(position), spawn_order)).id();
+
+            let projectiles_count = reader.read_u32()?;
+
+            for _ in 0..projectiles_count {
+                let position = reader.read_vec2()?;
+                let previous_position = reader.read_vec2()?;
+                let projectile = Projectile {
+                    direction: reader.read_vec2()?,
+                    state: match reader.read_u8()? {
+                        0 => ProjectileState::Alive,
+                        1 => ProjectileState::Dead,
+                        2 => ProjectileState::Hit,
+                        _ => return Err(invalid_data("Invalid projectile state")),
+                    },
+                    tower: entity,
+                };
+                let spawn_order = world.resource_mut::<NextSpawnOrder>().allocate();
+
+                world.spawn((
+                    projectile,
+                    Position(position),
+                    PreviousPosition(previous_position),
+                    spawn_order,
+                ));
+            }
+        }
+
+        decode_statistics(reader)?;
+
+        Ok(scene)
+    }
+}
+
+// Worlds cannot be cloned, so a copy is rebuilt from the resources and the
+// components, pointing projectiles at the copies of their towers.
+impl Clone for Scene {
+    fn clone(&self) -> Self {
+        let mut world = new_world();
+
+        world.insert_resource(self.world.resource::<Tick>().clone());
+        world.insert_resource(self.world.resource::<Game>().clone());
+        world.insert_resource(self.world.resource::<Economy>().clone());
+        world.insert_resource(self.world.resource::<Rounds>().clone());
+        world.insert_resource(self.world.resource::<Placement>().clone());
+        world.insert_resource(self.world.resource::<CommandLog>().clone());
+        world.insert_resource(self.world.resource::<RandomNumbers>().clone());
+        world.insert_resource(self.world.resource::<NextSpawnOrder>().clone());
+        world.insert_resource(self.world.resource::<StepEvents>().clone());
+
+        let mut towers = HashMap::new();
+
+        self.world.iter_entities().for_each(|entity| {
+            let spawn_order = *entity.get::<SpawnOrder>().unwrap();
+            let position = *entity.get::<Position>().unwrap();
+
+            if let Some(tower) = entity.get::<Tower>() {
+                let copy = world.spawn((*tower, position, spawn_order)).id();
+
+                towers.insert(entity.id(), copy);
+            }
+
+            if let Some(balloon) = entity.get::<Balloon>() {
+                let previous_position = *entity.get::<PreviousPosition>().unwrap();
+
+                world.spawn((*balloon, position, previous_position, spawn_order));
+            }
+        });
+
+        self.world.iter_entities().for_each(|entity| {
+            // Projectiles are despawned with their tower, so one without a
+            // tower is left out rather than copied.
+            if let Some((projectile, tower)) = entity
+                .get::<Projectile>()
+                .and_then(|projectile| Some((projectile, *towers.get(&projectile.tower)?)))
+            {
+                let projectile = Projectile {
+                    tower,
+                    ..*projectile
+                };
+
+                world.spawn((
+                    projectile,
+                    *entity.get::<Position>().unwrap(),
+                    *entity.get::<PreviousPosition>().unwrap(),
+                    *entity.get::<SpawnOrder>().unwrap(),
+                ));
+            }
+        });
+
+        Self { world }
+    }
+}
+
+impl GameSimulation for Scene {
+    type Sprites = Sprites;
+
+    fn step(mut self, delta_time: f32, keys: Keys) -> Self {
+        self.update(delta_time, keys);
+        self
+    }
+
+    fn render(&self, sprites: &Sprites, alpha: f32, view: &View) {
+        draw_scene(&self.world, sprites, alpha, view);
+    }
+
+    fn snapshot(&self) -> GameSnapshot {
+        let game = self.world.resource::<Game>();
+        let economy = self.world.resource::<Economy>();
+        let rounds = self.world.resource::<Rounds>();
+        let placement = self.world.resource::<Placement>();
+
+        let mut balloons = Vec::new();
+        let mut towers = Vec::new();
+        let mut projectiles = Vec::new();
+
+        self.world.iter_entities().for_each(|entity| {
+            let spawn_order = *entity.get::<SpawnOrder>().unwrap();
+            let position = entity.get::<Position>().unwrap().0;
+
+            if entity.contains::<Balloon>() {
+                balloons.push((spawn_order, position));
+            }
+
+            if let Some(tower) = entity.get::<Tower>() {
+                towers.push((spawn_order, (entity.id(), position, *tower)));
+            }
+
+            if let Some(projectile) = entity.get::<Projectile>() {
+                projectiles.push((spawn_order, (projectile.tower, position)));
+            }
+        });
+
+        let projectiles = sorted_by_spawn_order(projectiles);
+
+        GameSnapshot {
+            screen: screen_snapshot(game.screen),
+            game_speed: game.game_speed,
+            map: game.map,
+            difficulty: difficulty_id(game.difficulty) as usize,
+            game_mode: game_mode_id(game.game_mode) as usize,
+            coins: economy.coins,
+            lives: economy.lives,
+            round: rounds.round,
+            balloons_to_spawn: rounds.balloons_to_spawn,
+            balloons: sorted_by_spawn_order(balloons),
+            towers: sorted_by_spawn_order(towers)
+                .into_iter()
+                .map(|(entity, position, tower)| TowerSnapshot {
+                    position,
+                    angle: tower.angle,
+                    level: tower.level,
+                    pop_count: tower.pop_count,
+                    spent: tower.spent,
+                    projectiles: projectiles
+                        .iter()
+                        .filter(|(owner, _)| *owner == entity)
+                        .map(|(_, position)| *position)
+                        .collect(),
+                })
+                .collect(),
+            placement: PlacementSnapshot {
+                is_placing_tower: placement.is_placing_tower,
+                preview_tower: placement.preview_tower.as_ref().map(|preview_tower| {
+                    PreviewTowerSnapshot {
+                        position: preview_tower.position,
+                        angle: preview_tower.angle,
+                    }
+                }),
+                drag_start: placement.drag_start,
+                nudged_position: placement.nudged_position,
+            },
+        }
+    }
+
+    fn events(&self) -> &[GameEvent] {
+        &self.world.resource::<StepEvents>().0
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        if !self.can_save() {
+            return Err(invalid_data("Games can only be saved while playing"));
+        }
+
+        write_save_file(path, |writer| self.encode(writer))
+    }
+
+    fn load(path: &str) -> Result<Self, Error> {
+        read_save_file(path, Self::decode)
+    }
+}
@@ -0,0 +1,157 @@
+// Each screen has its own schedule, picked by the scene before running it,
+// so a screen change made by one system only takes effect on the next step.
+
+use bevy::ecs::schedule::apply_deferred;
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::ecs::schedule::IntoSystemConfigs;
+use bevy::ecs::schedule::Schedule;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::ecs::world::World;
+
+use crate::functional::screen::Screen;
+
+use super::systems::clean_projectiles;
+use super::systems::clear_balloons;
+use super::systems::handle_balloon_spawning;
+use super::systems::handle_game_speed;
+use super::systems::handle_popping;
+use super::systems::handle_round_start;
+use super::systems::handle_rounds;
+use super::systems::handle_spawn_timer;
+use super::systems::handle_tower_placement;
+use super::systems::handle_tower_selling;
+use super::systems::handle_tower_upgrade;
+use super::systems::handle_undo;
+use super::systems::in_screen;
+use super::systems::simulate_ticks;
+use super::systems::step_frame;
+use super::systems::toggle_pause;
+use super::systems::update_balloons;
+use super::systems::update_defeat;
+use super::systems::update_difficulty_select;
+use super::systems::update_game_mode_select;
+use super::systems::update_main_menu;
+use super::systems::update_map_select;
+use super::systems::update_projectiles;
+use super::systems::update_towers;
+use super::systems::update_victory;
+
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UpdateScreen {
+    MainMenu,
+    MapSelect,
+    DifficultySelect,
+    GameModeSelect,
+    BetweenRounds,
+    Playing,
+    Paused,
+    Victory,
+    Defeat,
+}
+
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SimulateTick;
+
+pub fn update_screen(screen: Screen) -> UpdateScreen {
+    match screen {
+        Screen::MainMenu => UpdateScreen::MainMenu,
+        Screen::MapSelect => UpdateScreen::MapSelect,
+        Screen::DifficultySelect => UpdateScreen::DifficultySelect,
+        Screen::GameModeSelect => UpdateScreen::GameModeSelect,
+        Screen::BetweenRounds => UpdateScreen::BetweenRounds,
+        Screen::Playing => UpdateScreen::Playing,
+        Screen::Paused => UpdateScreen::Paused,
+        Screen::Victory => UpdateScreen::Victory,
+        Screen::Defeat => UpdateScreen::Defeat,
+    }
+}
+
+// Runs on the calling thread so that headless runs are deterministic and do
+// not need a task pool.
+fn new_schedule<M>(systems: impl IntoSystemConfigs<M>) -> Schedule {
+    let mut schedule = Schedule::new();
+
+    schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    schedule.add_systems(systems);
+
+    schedule
+}
+
+pub fn add_schedules(world: &mut World) {
+    world.add_schedule(new_schedule(update_main_menu), UpdateScreen::MainMenu);
+    world.add_schedule(new_schedule(update_map_select), UpdateScreen::MapSelect);
+    world.add_schedule(
+        new_schedule(update_difficulty_select),
+        UpdateScreen::DifficultySelect,
+    );
+    world.add_schedule(
+        new_schedule(update_game_mode_select),
+        UpdateScreen::GameModeSelect,
+    );
+    world.add_schedule(
+        new_schedule(
+            (
+                handle_game_speed,
+                handle_tower_placement,
+                apply_deferred,
+                handle_tower_upgrade,
+                handle_tower_selling,
+                handle_undo,
+                apply_deferred,
+                handle_round_start,
+            )
+                .chain(),
+        ),
+        UpdateScreen::BetweenRounds,
+    );
+    world.add_schedule(
+        new_schedule(
+            (
+                toggle_pause,
+                (handle_game_speed, simulate_ticks)
+                    .chain()
+                    .distributive_run_if(in_screen(Screen::Playing)),
+            )
+                .chain(),
+        ),
+        UpdateScreen::Playing,
+    );
+    world.add_schedule(
+        new_schedule(
+            (
+                toggle_pause,
+                (step_frame, handle_game_speed)
+                    .chain()
+                    .distributive_run_if(in_screen(Screen::Paused)),
+            )
+                .chain(),
+        ),
+        UpdateScreen::Paused,
+    );
+    world.add_schedule(new_schedule(update_victory), UpdateScreen::Victory);
+    world.add_schedule(new_schedule(update_defeat), UpdateScreen::Defeat);
+    world.add_schedule(
+        new_schedule(
+            (
+                handle_spawn_timer,
+                handle_balloon_spawning,
+                handle_tower_placement,
+                apply_deferred,
+                handle_tower_upgrade,
+                handle_tower_selling,
+                apply_deferred,
+                update_balloons,
+                update_towers,
+                apply_deferred,
+                update_projectiles,
+                handle_popping,
+                clean_projectiles,
+                clear_balloons,
+                apply_deferred,
+                handle_rounds,
+            )
+                .chain(),
+        ),
+        SimulateTick,
+    );
+}
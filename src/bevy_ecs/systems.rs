@@ -0,0 +1,823 @@
+use std::collections::HashMap;
+
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::world::World;
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+use crate::input::is_drag;
+use crate::input::NO_KEYS;
+use crate::placement::clamp_to_world;
+use crate::placement::nudge_offset;
+use crate::placement::rotation_offset;
+use crate::placement::snap_to_grid;
+use crate::shop::chosen_shop_item;
+use crate::shop::is_on_sidebar;
+use crate::world::is_in_world;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
+use crate::functional::difficulty::balloon_speed_multiplier;
+use crate::functional::difficulty::final_round;
+use crate::functional::difficulty::scale_price;
+use crate::functional::difficulty::starting_lives;
+use crate::functional::difficulty::Difficulty;
+use crate::functional::difficulty::DIFFICULTIES;
+
+use crate::functional::game_mode::allows_continues;
+use crate::functional::game_mode::allows_selling;
+use crate::functional::game_mode::allows_spawning_on_demand;
+use crate::functional::game_mode::has_infinite_resources;
+use crate::functional::game_mode::income_multiplier;
+use crate::functional::game_mode::pauses_between_rounds;
+use crate::functional::game_mode::starting_cash_for_mode;
+use crate::functional::game_mode::GameMode;
+use crate::functional::game_mode::GAME_MODES;
+
+use crate::functional::balloon::BALLOON_COLLIDER_SIZE;
+use crate::functional::balloon::BALLOON_SIZE;
+use crate::functional::balloon::BALLOON_SPEED;
+use crate::functional::map::MAPS;
+use crate::functional::projectile::PROJECTILE_SPEED;
+use crate::functional::random::random_range;
+use crate::functional::scene::MAX_GAME_SPEED;
+use crate::functional::scene::SPAWN_INTERVAL;
+use crate::functional::scene::SPAWN_JITTER;
+use crate::functional::scene::TOWER_COST;
+use crate::functional::scene::UPGRADE_COST;
+use crate::functional::screen::Screen;
+use crate::functional::tower::MAX_TOWER_LEVEL;
+
+use super::components::check_collision;
+use super::components::do_towers_overlap;
+use super::components::is_point_on_tower;
+use super::components::Balloon;
+use super::components::BalloonState;
+use super::components::Position;
+use super::components::PreviousPosition;
+use super::components::Projectile;
+use super::components::ProjectileState;
+use super::components::SpawnOrder;
+use super::components::Tower;
+
+use super::resources::CommandLog;
+use super::resources::Economy;
+use super::resources::Game;
+use super::resources::NextSpawnOrder;
+use super::resources::Placement;
+use super::resources::PreviewTower;
+use super::resources::RandomNumbers;
+use super::resources::Rounds;
+use super::resources::StepEvents;
+use super::resources::Tick;
+use super::resources::TowerCommand;
+use super::resources::INITIAL_ECONOMY;
+use super::resources::INITIAL_GAME;
+
+use super::schedules::SimulateTick;
+
+pub fn in_screen(screen: Screen) -> impl FnMut(Res<Game>) -> bool + Clone {
+    move |game: Res<Game>| game.screen == screen
+}
+
+fn round_balloon_count(round: u32) -> u32 {
+    5 + round * 2
+}
+
+// On the map and clear of every other tower's footprint.
+pub fn is_free_for_tower(position: Vec2, mut towers: impl Iterator<Item = Vec2>) -> bool {
+    is_in_world(position) && !towers.any(|tower| do_towers_overlap(tower, position))
+}
+
+fn can_afford(game: &Game, economy: &Economy, price: u32) -> bool {
+    has_infinite_resources(game.game_mode) || economy.coins >= price
+}
+
+fn spend(game: &Game, economy: &mut Economy, price: u32) {
+    if !has_infinite_resources(game.game_mode) {
+        economy.coins -= price;
+    }
+}
+
+fn earn(game: &Game, economy: &mut Economy, amount: u32) {
+    let income = economy.income_remainder + amount as f32 * income_multiplier(game.game_mode);
+
+    economy.coins += income.floor() as u32;
+    economy.income_remainder = income.fract();
+}
+
+fn start_round(
+    game: &mut Game,
+    rounds: &mut Rounds,
+    command_log: &mut CommandLog,
+    events: &mut StepEvents,
+) {
+    game.screen = Screen::Playing;
+    rounds.round += 1;
+    rounds.balloons_to_spawn = round_balloon_count(rounds.round);
+    rounds.spawn_timer = 0.;
+    command_log.0.clear();
+    events.0.push(GameEvent::RoundStarted {
+        round: rounds.round,
+    });
+}
+
+// Towers placed or upgraded during the round have already fired, so they can
+// no longer be undone.
+fn finish_round(
+    game: &mut Game,
+    rounds: &mut Rounds,
+    command_log: &mut CommandLog,
+    events: &mut StepEvents,
+) {
+    command_log.0.clear();
+
+    if rounds.round >= final_round(game.difficulty) {
+        game.screen = Screen::Victory;
+    } else if !pauses_between_rounds(game.game_mode) {
+        start_round(game, rounds, command_log, events);
+    } else {
+        game.screen = Screen::BetweenRounds;
+    }
+}
+
+fn spawn_balloon(
+    commands: &mut Commands,
+    next_spawn_order: &mut NextSpawnOrder,
+    difficulty: Difficulty,
+) {
+    let position = Vec2::new(BALLOON_SIZE, WORLD_HEIGHT / 2.);
+
+    commands.spawn((
+        Balloon {
+            speed: BALLOON_SPEED * balloon_speed_multiplier(difficulty),
+            state: BalloonState::Alive,
+        },
+        Position(position),
+        PreviousPosition(position),
+        next_spawn_order.allocate(),
+    ));
+}
+
+fn reset(world: &mut World, difficulty: Difficulty, game_mode: GameMode) {
+    let map = world.resource::<Game>().map;
+
+    world.clear_entities();
+    world.insert_resource(Game {
+        screen: Screen::BetweenRounds,
+        map,
+        difficulty,
+        game_mode,
+        ..INITIAL_GAME
+    });
+    world.insert_resource(Economy {
+        coins: starting_cash_for_mode(game_mode, difficulty),
+        lives: starting_lives(difficulty),
+        ..INITIAL_ECONOMY
+    });
+    world.insert_resource(Rounds::default());
+    world.insert_resource(Placement::default());
+    world.insert_resource(CommandLog::default());
+}
+
+fn return_to_main_menu(world: &mut World) {
+    world.clear_entities();
+    world.insert_resource(INITIAL_GAME);
+    world.insert_resource(INITIAL_ECONOMY);
+    world.insert_resource(Rounds::default());
+    world.insert_resource(Placement::default());
+    world.insert_resource(CommandLog::default());
+}
+
+pub fn update_main_menu(tick: Res<Tick>, mut game: ResMut<Game>) {
+    if tick.keys.confirm.pressed {
+        game.screen = Screen::MapSelect;
+    }
+}
+
+pub fn update_map_select(tick: Res<Tick>, mut game: ResMut<Game>) {
+    if let Some(map) = tick.keys.select_map.filter(|map| *map < MAPS.len()) {
+        game.screen = Screen::DifficultySelect;
+        game.map = map;
+    }
+}
+
+pub fn update_difficulty_select(tick: Res<Tick>, mut game: ResMut<Game>) {
+    if let Some(difficulty) = tick
+        .keys
+        .select_difficulty
+        .and_then(|index| DIFFICULTIES.get(index))
+    {
+        game.screen = Screen::GameModeSelect;
+        game.difficulty = *difficulty;
+    }
+}
+
+pub fn update_game_mode_select(world: &mut World) {
+    let selected_game_mode = world
+        .resource::<Tick>()
+        .keys
+        .select_game_mode
+        .and_then(|index| GAME_MODES.get(index));
+
+    if let Some(game_mode) = selected_game_mode {
+        let difficulty = world.resource::<Game>().difficulty;
+
+        reset(world, difficulty, *game_mode);
+    }
+}
+
+pub fn handle_round_start(
+    tick: Res<Tick>,
+    mut game: ResMut<Game>,
+    mut rounds: ResMut<Rounds>,
+    mut command_log: ResMut<CommandLog>,
+    mut events: ResMut<StepEvents>,
+) {
+    if tick.keys.start_round.pressed || !pauses_between_rounds(game.game_mode) {
+        start_round(&mut game, &mut rounds, &mut command_log, &mut events);
+    }
+}
+
+pub fn toggle_pause(tick: Res<Tick>, mut game: ResMut<Game>) {
+    if !tick.keys.pause.pressed {
+        return;
+    }
+
+    game.screen = match game.screen {
+        Screen::Playing => Screen::Paused,
+        _ => Screen::Playing,
+    };
+}
+
+pub fn simulate_ticks(world: &mut World) {
+    world.run_schedule(SimulateTick);
+
+    // Extra substeps reuse the same delta so that collisions behave exactly
+    // as they would at 1x, and input is only consumed by the first one.
+    for _ in 1..world.resource::<Game>().game_speed {
+        if world.resource::<Game>().screen != Screen::Playing {
+            break;
+        }
+
+        world.resource_mut::<Tick>().keys = NO_KEYS;
+        world.run_schedule(SimulateTick);
+    }
+}
+
+pub fn step_frame(world: &mut World) {
+    if !world.resource::<Tick>().keys.step_frame.pressed {
+        return;
+    }
+
+    world.resource_mut::<Tick>().keys = NO_KEYS;
+    world.resource_mut::<Game>().screen = Screen::Playing;
+    world.run_schedule(SimulateTick);
+
+    let mut game = world.resource_mut::<Game>();
+
+    if game.screen == Screen::Playing {
+        game.screen = Screen::Paused;
+    }
+}
+
+pub fn handle_game_speed(tick: Res<Tick>, mut game: ResMut<Game>) {
+    if tick.keys.change_game_speed.pressed {
+        game.game_speed = game.game_speed % MAX_GAME_SPEED + 1;
+    }
+}
+
+pub fn update_victory(world: &mut World) {
+    if world.resource::<Tick>().keys.confirm.pressed {
+        return_to_main_menu(world);
+    }
+}
+
+pub fn update_defeat(world: &mut World) {
+    let keys = world.resource::<Tick>().keys.clone();
+    let game = world.resource::<Game>().clone();
+
+    if keys.confirm.pressed {
+        reset(world, game.difficulty, game.game_mode);
+    } else if keys.continue_game.pressed && allows_continues(game.game_mode) {
+        let balloons: Vec<Entity> = world
+            .query_filtered::<Entity, With<Balloon>>()
+            .iter(world)
+            .collect();
+
+        balloons.into_iter().for_each(|balloon| {
+            world.despawn(balloon);
+        });
+
+        world.resource_mut::<Economy>().lives = starting_lives(game.difficulty);
+        world.resource_mut::<Rounds>().balloons_to_spawn = 0;
+        world.resource_mut::<Game>().screen = Screen::BetweenRounds;
+    }
+}
+
+pub fn handle_spawn_timer(
+    mut commands: Commands,
+    tick: Res<Tick>,
+    game: Res<Game>,
+    mut rounds: ResMut<Rounds>,
+    mut random_numbers: ResMut<RandomNumbers>,
+    mut next_spawn_order: ResMut<NextSpawnOrder>,
+) {
+    if rounds.balloons_to_spawn == 0 {
+        return;
+    }
+
+    rounds.spawn_timer += tick.delta_time;
+
+    if rounds.spawn_timer > SPAWN_INTERVAL {
+        let (rng, jitter) = random_range(random_numbers.0, 0., SPAWN_JITTER);
+
+        random_numbers.0 = rng;
+        rounds.spawn_timer = rounds.spawn_timer - SPAWN_INTERVAL - jitter;
+        rounds.balloons_to_spawn -= 1;
+
+        spawn_balloon(&mut commands, &mut next_spawn_order, game.difficulty);
+    }
+}
+
+pub fn handle_balloon_spawning(
+    mut commands: Commands,
+    tick: Res<Tick>,
+    game: Res<Game>,
+    mut next_spawn_order: ResMut<NextSpawnOrder>,
+) {
+    if tick.keys.spawn_balloon.pressed && allows_spawning_on_demand(game.game_mode) {
+        spawn_balloon(&mut commands, &mut next_spawn_order, game.difficulty);
+    }
+}
+
+// Clicking places the tower where it is previewed, while dragging from the
+// press keeps it where the press started and aims it at the pointer. Towers
+// are only placed on free spots of the map and never by releasing over the
+// sidebar, and pressing an affordable shop button starts placing one.
+// Checking for other towers takes this past clippy's argument limit.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_tower_placement(
+    mut commands: Commands,
+    tick: Res<Tick>,
+    game: Res<Game>,
+    mut economy: ResMut<Economy>,
+    mut placement: ResMut<Placement>,
+    mut next_spawn_order: ResMut<NextSpawnOrder>,
+    mut command_log: ResMut<CommandLog>,
+    mut events: ResMut<StepEvents>,
+    towers: Query<&Position, With<Tower>>,
+) {
+    let keys = &tick.keys;
+    let tower_cost = scale_price(game.difficulty, TOWER_COST);
+
+    let is_shop_choice =
+        chosen_shop_item(keys).is_some() && can_afford(&game, &economy, tower_cost);
+
+    if keys.tower_placement.pressed || is_shop_choice {
+        placement.is_placing_tower = true;
+        placement.preview_tower = Some(PreviewTower {
+            position: keys.pointer_position,
+            angle: 0.,
+        });
+        placement.drag_start = None;
+        placement.nudged_position = None;
+    } else if keys.cancel_tower_placement.pressed {
+        placement.is_placing_tower = false;
+        placement.preview_tower = None;
+        placement.drag_start = None;
+        placement.nudged_position = None;
+    }
+
+    // Moving the pointer takes over from the arrow keys again.
+    if keys.pointer_delta != Vec2::ZERO {
+        placement.nudged_position = None;
+    }
+
+    let pointer_position = clamp_to_world(snap_to_grid(keys.pointer_position, keys.grid_size));
+    let nudge = nudge_offset(keys);
+
+    if placement.is_placing_tower && nudge != Vec2::ZERO {
+        placement.nudged_position = Some(clamp_to_world(
+            placement.nudged_position.unwrap_or(pointer_position) + nudge,
+        ));
+    }
+
+    if placement.is_placing_tower
+        && placement.drag_start.is_none()
+        && keys.place_tower.pressed
+        && !is_on_sidebar(keys.interface_pointer_position)
+    {
+        placement.drag_start = Some(keys.pointer_position);
+    }
+
+    let drag_start = placement.drag_start;
+    let nudged_position = placement.nudged_position;
+
+    if placement.is_placing_tower {
+        if let Some(preview_tower) = placement.preview_tower.as_mut() {
+            preview_tower.angle += rotation_offset(keys);
+            preview_tower.position = nudged_position.unwrap_or(pointer_position);
+
+            if let Some(start) = drag_start {
+                let is_dragging = keys.place_tower.held || keys.place_tower.released;
+
+                if is_dragging && is_drag(start, keys.pointer_position) {
+                    let aim = keys.pointer_position - start;
+
+                    preview_tower.position = clamp_to_world(snap_to_grid(start, keys.grid_size));
+                    preview_tower.angle = aim.y.atan2(aim.x);
+                }
+            }
+        }
+    }
+
+    if keys.place_tower.released {
+        placement.drag_start = None;
+    }
+
+    let is_blocked = placement.preview_tower.is_some_and(|preview_tower| {
+        !is_free_for_tower(
+            preview_tower.position,
+            towers.iter().map(|position| position.0),
+        )
+    });
+
+    if !placement.is_placing_tower
+        || !keys.place_tower.released
+        || !can_afford(&game, &economy, tower_cost)
+        || is_blocked
+        || is_on_sidebar(keys.interface_pointer_position)
+    {
+        return;
+    }
+
+    if let Some(preview_tower) = placement.preview_tower {
+        let spawn_order = next_spawn_order.allocate();
+
+        command_log.0.push(TowerCommand::PlaceTower {
+            tower: spawn_order,
+            cost: tower_cost,
+        });
+        events.0.push(GameEvent::TowerPlaced {
+            position: preview_tower.position,
+            cost: tower_cost,
+        });
+        commands.spawn((
+            Tower {
+                angle: preview_tower.angle,
+                shot_cooldown: 0.,
+                pop_count: 0,
+                level: 1,
+                spent: tower_cost,
+            },
+            Position(preview_tower.position),
+            spawn_order,
+        ));
+    }
+
+    placement.is_placing_tower = false;
+    placement.preview_tower = None;
+    spend(&game, &mut economy, tower_cost);
+}
+
+pub fn handle_tower_upgrade(
+    tick: Res<Tick>,
+    game: Res<Game>,
+    placement: Res<Placement>,
+    mut economy: ResMut<Economy>,
+    mut command_log: ResMut<CommandLog>,
+    mut events: ResMut<StepEvents>,
+    mut towers: Query<(&Position, &SpawnOrder, &mut Tower)>,
+) {
+    if !tick.keys.upgrade_tower.pressed || placement.is_placing_tower {
+        return;
+    }
+
+    let upgrade_cost = scale_price(game.difficulty, UPGRADE_COST);
+    let pointer_position = tick.keys.pointer_position;
+
+    let selected_tower = towers
+        .iter_mut()
+        .filter(|(position, _, tower)| {
+            is_point_on_tower(position.0, pointer_position) && tower.level < MAX_TOWER_LEVEL
+        })
+        .min_by_key(|(_, spawn_order, _)| **spawn_order);
+
+    if let Some((position, spawn_order, mut tower)) = selected_tower {
+        if can_afford(&game, &economy, upgrade_cost) {
+            tower.level += 1;
+            tower.spent += upgrade_cost;
+            spend(&game, &mut economy, upgrade_cost);
+            command_log.0.push(TowerCommand::UpgradeTower {
+                tower: *spawn_order,
+                cost: upgrade_cost,
+            });
+            events.0.push(GameEvent::TowerUpgraded {
+                position: position.0,
+                cost: upgrade_cost,
+            });
+        }
+    }
+}
+
+// Clearing the undo log as well takes this past clippy's argument limit.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_tower_selling(
+    mut commands: Commands,
+    tick: Res<Tick>,
+    game: Res<Game>,
+    placement: Res<Placement>,
+    mut economy: ResMut<Economy>,
+    mut command_log: ResMut<CommandLog>,
+    mut events: ResMut<StepEvents>,
+    towers: Query<(Entity, &Position, &SpawnOrder, &Tower)>,
+    projectiles: Query<(Entity, &Projectile)>,
+) {
+    if !tick.keys.sell_tower.pressed
+        || placement.is_placing_tower
+        || !allows_selling(game.game_mode)
+    {
+        return;
+    }
+
+    let pointer_position = tick.keys.pointer_position;
+
+    let selected_tower = towers
+        .iter()
+        .filter(|(_, position, _, _)| is_point_on_tower(position.0, pointer_position))
+        .min_by_key(|(_, _, spawn_order, _)| **spawn_order);
+
+    if let Some((entity, position, _, tower)) = selected_tower {
+        let refund = tower.spent * 7 / 10;
+
+        economy.coins += refund;
+        events.0.push(GameEvent::TowerSold {
+            position: position.0,
+            refund,
+        });
+        // Selling cannot be undone, so neither can anything before it.
+        command_log.0.clear();
+
+        commands.entity(entity).despawn();
+
+        projectiles
+            .iter()
+            .filter(|(_, projectile)| projectile.tower == entity)
+            .for_each(|(projectile_entity, _)| commands.entity(projectile_entity).despawn());
+    }
+}
+
+// Reverses the latest placement or upgrade since the last round, with a full
+// refund. It only runs between rounds.
+pub fn handle_undo(
+    mut commands: Commands,
+    tick: Res<Tick>,
+    mut economy: ResMut<Economy>,
+    mut command_log: ResMut<CommandLog>,
+    mut events: ResMut<StepEvents>,
+    mut towers: Query<(Entity, &Position, &SpawnOrder, &mut Tower)>,
+    projectiles: Query<(Entity, &Projectile)>,
+) {
+    if !tick.keys.undo.pressed {
+        return;
+    }
+
+    match command_log.0.pop() {
+        Some(TowerCommand::PlaceTower { tower, cost }) => {
+            let placed_tower = towers
+                .iter()
+                .find(|(_, _, spawn_order, _)| **spawn_order == tower);
+
+            if let Some((entity, position, _, _)) = placed_tower {
+                economy.coins += cost;
+                events.0.push(GameEvent::TowerPlacementUndone {
+                    position: position.0,
+                    refund: cost,
+                });
+
+                commands.entity(entity).despawn();
+
+                projectiles
+                    .iter()
+                    .filter(|(_, projectile)| projectile.tower == entity)
+                    .for_each(|(projectile_entity, _)| {
+                        commands.entity(projectile_entity).despawn()
+                    });
+            }
+        }
+        Some(TowerCommand::UpgradeTower { tower, cost }) => {
+            let upgraded_tower = towers
+                .iter_mut()
+                .find(|(_, _, spawn_order, _)| **spawn_order == tower);
+
+            if let Some((_, position, _, mut upgraded_tower)) = upgraded_tower {
+                upgraded_tower.level = upgraded_tower.level.saturating_sub(1).max(1);
+                upgraded_tower.spent = upgraded_tower.spent.saturating_sub(cost);
+                economy.coins += cost;
+                events.0.push(GameEvent::TowerUpgradeUndone {
+                    position: position.0,
+                    refund: cost,
+                });
+            }
+        }
+        None => {}
+    }
+}
+
+pub fn update_balloons(
+    tick: Res<Tick>,
+    mut balloons: Query<(&mut Position, &mut PreviousPosition, &Balloon)>,
+) {
+    balloons
+        .iter_mut()
+        .for_each(|(mut position, mut previous_position, balloon)| {
+            previous_position.0 = position.0;
+            position.0.x += balloon.speed * tick.delta_time;
+        });
+}
+
+pub fn update_towers(
+    mut commands: Commands,
+    tick: Res<Tick>,
+    mut next_spawn_order: ResMut<NextSpawnOrder>,
+    mut towers: Query<(Entity, &Position, &mut Tower)>,
+) {
+    towers.iter_mut().for_each(|(entity, position, mut tower)| {
+        tower.shot_cooldown -= tick.delta_time;
+
+        if tower.shot_cooldown < 0. {
+            commands.spawn((
+                Projectile {
+                    direction: Vec2::new(tower.angle.cos(), tower.angle.sin()),
+                    state: ProjectileState::Alive,
+                    tower: entity,
+                },
+                *position,
+                PreviousPosition(position.0),
+                next_spawn_order.allocate(),
+            ));
+
+            tower.shot_cooldown += 2. / tower.level as f32;
+        }
+    });
+}
+
+pub fn update_projectiles(
+    tick: Res<Tick>,
+    mut projectiles: Query<(&mut Position, &mut PreviousPosition, &mut Projectile)>,
+) {
+    projectiles
+        .iter_mut()
+        .for_each(|(mut position, mut previous_position, mut projectile)| {
+            if position.0.x < 0.
+                || position.0.x > WORLD_WIDTH
+                || position.0.y < 0.
+                || position.0.y > WORLD_HEIGHT
+            {
+                projectile.state = ProjectileState::Dead;
+            }
+
+            previous_position.0 = position.0;
+            position.0 += projectile.direction * PROJECTILE_SPEED * tick.delta_time;
+        });
+}
+
+pub fn handle_popping(
+    game: Res<Game>,
+    mut economy: ResMut<Economy>,
+    mut events: ResMut<StepEvents>,
+    towers: Query<&SpawnOrder, With<Tower>>,
+    mut projectiles: Query<(&SpawnOrder, &Position, &mut Projectile)>,
+    mut balloons: Query<(&SpawnOrder, &Position, &mut Balloon)>,
+) {
+    let mut popped_balloons = 0;
+
+    // Darts are checked tower by tower, as the other implementations do, since
+    // the first dart to reach a balloon takes it.
+    let mut projectiles: Vec<_> = projectiles.iter_mut().collect();
+    projectiles.sort_by_key(|(spawn_order, _, projectile)| {
+        (towers.get(projectile.tower).ok().copied(), **spawn_order)
+    });
+
+    let mut balloons: Vec<_> = balloons.iter_mut().collect();
+    balloons.sort_by_key(|(spawn_order, _, _)| **spawn_order);
+
+    projectiles
+        .into_iter()
+        .for_each(|(_, projectile_position, mut projectile)| {
+            // A dart pops one balloon, and a balloon is popped once.
+            balloons
+                .iter_mut()
+                .for_each(|(_, balloon_position, balloon)| {
+                    if projectile.state == ProjectileState::Hit
+                        || balloon.state != BalloonState::Alive
+                        || !check_collision(projectile_position.0, balloon_position.0)
+                    {
+                        return;
+                    }
+
+                    balloon.state = BalloonState::Popped;
+                    projectile.state = ProjectileState::Hit;
+                    popped_balloons += 1;
+                    events.0.push(GameEvent::BalloonPopped {
+                        position: balloon_position.0,
+                    });
+                });
+        });
+
+    earn(&game, &mut economy, popped_balloons);
+}
+
+pub fn clean_projectiles(
+    mut commands: Commands,
+    projectiles: Query<(Entity, &Projectile)>,
+    mut towers: Query<&mut Tower>,
+) {
+    let mut projectiles_hit: HashMap<Entity, u32> = HashMap::new();
+
+    projectiles.iter().for_each(|(entity, projectile)| {
+        if projectile.state == ProjectileState::Hit {
+            *projectiles_hit.entry(projectile.tower).or_default() += 1;
+        }
+
+        if projectile.state != ProjectileState::Alive {
+            commands.entity(entity).despawn();
+        }
+    });
+
+    projectiles_hit.into_iter().for_each(|(entity, hits)| {
+        if let Ok(mut tower) = towers.get_mut(entity) {
+            tower.pop_count += hits;
+
+            if tower.pop_count.is_multiple_of(10) {
+                tower.level += 1;
+            }
+        }
+    });
+}
+
+pub fn clear_balloons(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut economy: ResMut<Economy>,
+    mut events: ResMut<StepEvents>,
+    mut balloons: Query<(Entity, &Position, &mut Balloon)>,
+) {
+    let mut escaped_balloons = 0;
+
+    balloons
+        .iter_mut()
+        .for_each(|(entity, position, mut balloon)| {
+            let is_out_of_world = position.0.x > WORLD_WIDTH + BALLOON_COLLIDER_SIZE
+                || position.0.x < -BALLOON_COLLIDER_SIZE;
+
+            if balloon.state == BalloonState::Alive && is_out_of_world {
+                balloon.state = BalloonState::Escaped;
+                escaped_balloons += 1;
+                events.0.push(GameEvent::BalloonEscaped {
+                    position: position.0,
+                });
+            }
+
+            if balloon.state != BalloonState::Alive {
+                commands.entity(entity).despawn();
+            }
+        });
+
+    if !has_infinite_resources(game.game_mode) {
+        economy.lives -= escaped_balloons;
+    }
+
+    if economy.lives <= 0 {
+        game.screen = Screen::Defeat;
+    }
+}
+
+pub fn handle_rounds(
+    mut game: ResMut<Game>,
+    mut rounds: ResMut<Rounds>,
+    mut command_log: ResMut<CommandLog>,
+    mut events: ResMut<StepEvents>,
+    balloons: Query<(), With<Balloon>>,
+) {
+    if game.screen != Screen::Playing || rounds.balloons_to_spawn > 0 {
+        return;
+    }
+
+    if balloons.is_empty() {
+        finish_round(&mut game, &mut rounds, &mut command_log, &mut events);
+        return;
+    }
+
+    let is_final_round = rounds.round >= final_round(game.difficulty);
+
+    if !pauses_between_rounds(game.game_mode) && !is_final_round {
+        start_round(&mut game, &mut rounds, &mut command_log, &mut events);
+    }
+}
@@ -0,0 +1,759 @@
+// Maps physical inputs to the actions the game understands, so the front end
+// reads actions rather than hardcoded keys. Bindings are kept in a plain text
+// file with one action per line, e.g. `place_tower = Mouse.Left, Key.Enter`,
+// and actions missing from it keep their default inputs.
+
+use std::fs;
+use std::io::Error;
+use std::io::ErrorKind;
+
+use macroquad::prelude::*;
+
+use crate::input::ButtonState;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    TowerPlacement,
+    PreviousShopItem,
+    NextShopItem,
+    CancelTowerPlacement,
+    PlaceTower,
+    RotateTowerClockwise,
+    RotateTowerCounterClockwise,
+    UpgradeTower,
+    SellTower,
+    StartRound,
+    SpawnBalloon,
+    Pause,
+    StepFrame,
+    ChangeGameSpeed,
+    Confirm,
+    ContinueGame,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+    ToggleGridSnapping,
+    Undo,
+    PanView,
+    SaveGame,
+    LoadGame,
+    ToggleRewind,
+    RewindBack,
+    RewindForward,
+    OpenBindings,
+    SelectOption1,
+    SelectOption2,
+    SelectOption3,
+    SelectOption4,
+    SelectOption5,
+    SelectOption6,
+}
+
+pub const ACTIONS: [Action; 35] = [
+    Action::TowerPlacement,
+    Action::PreviousShopItem,
+    Action::NextShopItem,
+    Action::CancelTowerPlacement,
+    Action::PlaceTower,
+    Action::RotateTowerClockwise,
+    Action::RotateTowerCounterClockwise,
+    Action::UpgradeTower,
+    Action::SellTower,
+    Action::StartRound,
+    Action::SpawnBalloon,
+    Action::Pause,
+    Action::StepFrame,
+    Action::ChangeGameSpeed,
+    Action::Confirm,
+    Action::ContinueGame,
+    Action::NudgeLeft,
+    Action::NudgeRight,
+    Action::NudgeUp,
+    Action::NudgeDown,
+    Action::ToggleGridSnapping,
+    Action::Undo,
+    Action::PanView,
+    Action::SaveGame,
+    Action::LoadGame,
+    Action::ToggleRewind,
+    Action::RewindBack,
+    Action::RewindForward,
+    Action::OpenBindings,
+    Action::SelectOption1,
+    Action::SelectOption2,
+    Action::SelectOption3,
+    Action::SelectOption4,
+    Action::SelectOption5,
+    Action::SelectOption6,
+];
+
+// Pick the option at their index on whichever menu is showing.
+pub const OPTION_ACTIONS: [Action; 6] = [
+    Action::SelectOption1,
+    Action::SelectOption2,
+    Action::SelectOption3,
+    Action::SelectOption4,
+    Action::SelectOption5,
+    Action::SelectOption6,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftShoulder,
+    RightShoulder,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+pub const GAMEPAD_BUTTONS: [GamepadButton; 14] = [
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::West,
+    GamepadButton::North,
+    GamepadButton::LeftShoulder,
+    GamepadButton::RightShoulder,
+    GamepadButton::LeftTrigger,
+    GamepadButton::RightTrigger,
+    GamepadButton::Select,
+    GamepadButton::Start,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+];
+
+// The buttons held on this frame and the previous one, so that presses can be
+// told apart from holds.
+#[derive(Clone, Default)]
+pub struct GamepadButtons {
+    pub down: Vec<GamepadButton>,
+    pub previous_down: Vec<GamepadButton>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Input {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+#[derive(Clone)]
+pub struct Bindings {
+    inputs: Vec<(Action, Vec<Input>)>,
+}
+
+const KEY_CODES: [KeyCode; 120] = [
+    KeyCode::Space,
+    KeyCode::Apostrophe,
+    KeyCode::Comma,
+    KeyCode::Minus,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Semicolon,
+    KeyCode::Equal,
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::LeftBracket,
+    KeyCode::Backslash,
+    KeyCode::RightBracket,
+    KeyCode::GraveAccent,
+    KeyCode::World1,
+    KeyCode::World2,
+    KeyCode::Escape,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Right,
+    KeyCode::Left,
+    KeyCode::Down,
+    KeyCode::Up,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::CapsLock,
+    KeyCode::ScrollLock,
+    KeyCode::NumLock,
+    KeyCode::PrintScreen,
+    KeyCode::Pause,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+    KeyCode::F25,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::KpDecimal,
+    KeyCode::KpDivide,
+    KeyCode::KpMultiply,
+    KeyCode::KpSubtract,
+    KeyCode::KpAdd,
+    KeyCode::KpEnter,
+    KeyCode::KpEqual,
+    KeyCode::LeftShift,
+    KeyCode::LeftControl,
+    KeyCode::LeftAlt,
+    KeyCode::LeftSuper,
+    KeyCode::RightShift,
+    KeyCode::RightControl,
+    KeyCode::RightAlt,
+    KeyCode::RightSuper,
+    KeyCode::Menu,
+];
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+pub fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::TowerPlacement => "tower_placement",
+        Action::PreviousShopItem => "previous_shop_item",
+        Action::NextShopItem => "next_shop_item",
+        Action::CancelTowerPlacement => "cancel_tower_placement",
+        Action::PlaceTower => "place_tower",
+        Action::RotateTowerClockwise => "rotate_tower_clockwise",
+        Action::RotateTowerCounterClockwise => "rotate_tower_counter_clockwise",
+        Action::UpgradeTower => "upgrade_tower",
+        Action::SellTower => "sell_tower",
+        Action::StartRound => "start_round",
+        Action::SpawnBalloon => "spawn_balloon",
+        Action::Pause => "pause",
+        Action::StepFrame => "step_frame",
+        Action::ChangeGameSpeed => "change_game_speed",
+        Action::Confirm => "confirm",
+        Action::ContinueGame => "continue_game",
+        Action::NudgeLeft => "nudge_left",
+        Action::NudgeRight => "nudge_right",
+        Action::NudgeUp => "nudge_up",
+        Action::NudgeDown => "nudge_down",
+        Action::ToggleGridSnapping => "toggle_grid_snapping",
+        Action::Undo => "undo",
+        Action::PanView => "pan_view",
+        Action::SaveGame => "save_game",
+        Action::LoadGame => "load_game",
+        Action::ToggleRewind => "toggle_rewind",
+        Action::RewindBack => "rewind_back",
+        Action::RewindForward => "rewind_forward",
+        Action::OpenBindings => "open_bindings",
+        Action::SelectOption1 => "select_option_1",
+        Action::SelectOption2 => "select_option_2",
+        Action::SelectOption3 => "select_option_3",
+        Action::SelectOption4 => "select_option_4",
+        Action::SelectOption5 => "select_option_5",
+        Action::SelectOption6 => "select_option_6",
+    }
+}
+
+fn default_inputs(action: Action) -> Vec<Input> {
+    match action {
+        Action::TowerPlacement => vec![Input::Key(KeyCode::T)],
+        Action::PreviousShopItem => vec![Input::Gamepad(GamepadButton::LeftShoulder)],
+        Action::NextShopItem => vec![Input::Gamepad(GamepadButton::RightShoulder)],
+        Action::CancelTowerPlacement => vec![
+            Input::Key(KeyCode::Escape),
+            Input::Gamepad(GamepadButton::East),
+        ],
+        Action::PlaceTower => vec![
+            Input::Mouse(MouseButton::Left),
+            Input::Gamepad(GamepadButton::South),
+        ],
+        Action::RotateTowerClockwise => vec![
+            Input::Key(KeyCode::R),
+            Input::Gamepad(GamepadButton::RightTrigger),
+        ],
+        Action::RotateTowerCounterClockwise => vec![
+            Input::Key(KeyCode::E),
+            Input::Gamepad(GamepadButton::LeftTrigger),
+        ],
+        Action::UpgradeTower => vec![Input::Key(KeyCode::U), Input::Gamepad(GamepadButton::North)],
+        Action::SellTower => vec![Input::Key(KeyCode::S), Input::Gamepad(GamepadButton::West)],
+        Action::StartRound => vec![
+            Input::Key(KeyCode::Space),
+            Input::Gamepad(GamepadButton::DPadUp),
+        ],
+        Action::SpawnBalloon => vec![Input::Key(KeyCode::B)],
+        Action::Pause => vec![
+            Input::Key(KeyCode::P),
+            Input::Gamepad(GamepadButton::Select),
+        ],
+        Action::StepFrame => vec![Input::Key(KeyCode::N)],
+        Action::ChangeGameSpeed => vec![
+            Input::Key(KeyCode::F),
+            Input::Gamepad(GamepadButton::DPadRight),
+        ],
+        Action::Confirm => vec![
+            Input::Key(KeyCode::Enter),
+            Input::Gamepad(GamepadButton::Start),
+        ],
+        Action::ContinueGame => vec![
+            Input::Key(KeyCode::C),
+            Input::Gamepad(GamepadButton::DPadLeft),
+        ],
+        Action::NudgeLeft => vec![Input::Key(KeyCode::Left)],
+        Action::NudgeRight => vec![Input::Key(KeyCode::Right)],
+        Action::NudgeUp => vec![Input::Key(KeyCode::Up)],
+        Action::NudgeDown => vec![Input::Key(KeyCode::Down)],
+        Action::ToggleGridSnapping => vec![Input::Key(KeyCode::G)],
+        Action::Undo => vec![Input::Key(KeyCode::Z)],
+        Action::PanView => vec![
+            Input::Mouse(MouseButton::Middle),
+            Input::Mouse(MouseButton::Right),
+        ],
+        Action::SaveGame => vec![Input::Key(KeyCode::F5)],
+        Action::LoadGame => vec![Input::Key(KeyCode::F9)],
+        Action::ToggleRewind => vec![Input::Key(KeyCode::Backspace)],
+        Action::RewindBack => vec![Input::Key(KeyCode::Comma)],
+        Action::RewindForward => vec![Input::Key(KeyCode::Period)],
+        Action::OpenBindings => vec![Input::Key(KeyCode::F1)],
+        Action::SelectOption1 => vec![Input::Key(KeyCode::Key1)],
+        Action::SelectOption2 => vec![Input::Key(KeyCode::Key2)],
+        Action::SelectOption3 => vec![Input::Key(KeyCode::Key3)],
+        Action::SelectOption4 => vec![Input::Key(KeyCode::Key4)],
+        Action::SelectOption5 => vec![Input::Key(KeyCode::Key5)],
+        Action::SelectOption6 => vec![Input::Key(KeyCode::Key6)],
+    }
+}
+
+pub fn default_bindings() -> Bindings {
+    Bindings {
+        inputs: ACTIONS
+            .iter()
+            .map(|action| (*action, default_inputs(*action)))
+            .collect(),
+    }
+}
+
+pub fn action_inputs(bindings: &Bindings, action: Action) -> &[Input] {
+    bindings
+        .inputs
+        .iter()
+        .find(|(bound_action, _)| *bound_action == action)
+        .map_or(&[], |(_, inputs)| inputs.as_slice())
+}
+
+pub fn bind(bindings: Bindings, action: Action, inputs: Vec<Input>) -> Bindings {
+    Bindings {
+        inputs: bindings
+            .inputs
+            .into_iter()
+            .map(|(bound_action, bound_inputs)| {
+                if bound_action == action {
+                    return (bound_action, inputs.clone());
+                }
+
+                (bound_action, bound_inputs)
+            })
+            .collect(),
+    }
+}
+
+// The keyboard and mouse are used together, so they count as one device.
+fn is_same_device(input: Input, other: Input) -> bool {
+    matches!(input, Input::Gamepad(_)) == matches!(other, Input::Gamepad(_))
+}
+
+// Binds the input in place of the action's inputs from the same device, so
+// rebinding a key or mouse button keeps the gamepad button and the other way
+// around.
+pub fn rebind(bindings: Bindings, action: Action, input: Input) -> Bindings {
+    let mut inputs = Vec::new();
+
+    action_inputs(&bindings, action)
+        .iter()
+        .map(|bound_input| {
+            if is_same_device(*bound_input, input) {
+                return input;
+            }
+
+            *bound_input
+        })
+        .chain([input])
+        .for_each(|next_input| {
+            if !inputs.contains(&next_input) {
+                inputs.push(next_input);
+            }
+        });
+
+    bind(bindings, action, inputs)
+}
+
+// The other actions sharing an input with the action, which fire along with it
+// whenever that input is used.
+pub fn conflicting_actions(bindings: &Bindings, action: Action) -> Vec<Action> {
+    let inputs = action_inputs(bindings, action);
+
+    bindings
+        .inputs
+        .iter()
+        .filter(|(other_action, other_inputs)| {
+            *other_action != action && other_inputs.iter().any(|input| inputs.contains(input))
+        })
+        .map(|(other_action, _)| *other_action)
+        .collect()
+}
+
+pub fn input_name(input: Input) -> String {
+    match input {
+        Input::Key(key) => format!("Key.{:?}", key),
+        Input::Mouse(button) => format!("Mouse.{:?}", button),
+        Input::Gamepad(button) => format!("Gamepad.{:?}", button),
+    }
+}
+
+pub fn parse_input(name: &str) -> Option<Input> {
+    let (device, button) = name.trim().split_once('.')?;
+
+    match device {
+        "Key" => KEY_CODES
+            .iter()
+            .find(|key| format!("{:?}", key) == button)
+            .map(|key| Input::Key(*key)),
+        "Mouse" => MOUSE_BUTTONS
+            .iter()
+            .find(|mouse_button| format!("{:?}", mouse_button) == button)
+            .map(|mouse_button| Input::Mouse(*mouse_button)),
+        "Gamepad" => GAMEPAD_BUTTONS
+            .iter()
+            .find(|gamepad_button| format!("{:?}", gamepad_button) == button)
+            .map(|gamepad_button| Input::Gamepad(*gamepad_button)),
+        _ => None,
+    }
+}
+
+fn invalid_bindings(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+pub fn encode_bindings(bindings: &Bindings) -> String {
+    bindings
+        .inputs
+        .iter()
+        .map(|(action, inputs)| {
+            let inputs: Vec<String> = inputs.iter().map(|input| input_name(*input)).collect();
+
+            format!("{} = {}\n", action_name(*action), inputs.join(", "))
+        })
+        .collect()
+}
+
+pub fn decode_bindings(text: &str) -> Result<Bindings, Error> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .try_fold(default_bindings(), |bindings, line| {
+            let (name, inputs) = line.split_once('=').ok_or_else(|| {
+                invalid_bindings(format!("Expected `action = inputs` in {}", line))
+            })?;
+
+            let action = ACTIONS
+                .iter()
+                .find(|action| action_name(**action) == name.trim())
+                .ok_or_else(|| invalid_bindings(format!("Unknown action {}", name.trim())))?;
+
+            let inputs = inputs
+                .split(',')
+                .filter(|input| !input.trim().is_empty())
+                .map(|input| {
+                    parse_input(input)
+                        .ok_or_else(|| invalid_bindings(format!("Unknown input {}", input.trim())))
+                })
+                .collect::<Result<Vec<Input>, Error>>()?;
+
+            Ok(bind(bindings, *action, inputs))
+        })
+}
+
+pub fn save_bindings(path: &str, bindings: &Bindings) -> Result<(), Error> {
+    fs::write(path, encode_bindings(bindings))
+}
+
+// A missing file is not an error, it only means nothing was rebound yet.
+pub fn load_bindings(path: &str) -> Result<Bindings, Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => decode_bindings(&text),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(default_bindings()),
+        Err(error) => Err(error),
+    }
+}
+
+fn is_input_down(input: Input, gamepad: &GamepadButtons) -> bool {
+    match input {
+        Input::Key(key) => is_key_down(key),
+        Input::Mouse(button) => is_mouse_button_down(button),
+        Input::Gamepad(button) => gamepad.down.contains(&button),
+    }
+}
+
+fn is_input_released(input: Input, gamepad: &GamepadButtons) -> bool {
+    match input {
+        Input::Key(key) => is_key_released(key),
+        Input::Mouse(button) => is_mouse_button_released(button),
+        Input::Gamepad(button) => {
+            !gamepad.down.contains(&button) && gamepad.previous_down.contains(&button)
+        }
+    }
+}
+
+fn is_input_pressed(input: Input, gamepad: &GamepadButtons) -> bool {
+    match input {
+        Input::Key(key) => is_key_pressed(key),
+        Input::Mouse(button) => is_mouse_button_pressed(button),
+        Input::Gamepad(button) => {
+            gamepad.down.contains(&button) && !gamepad.previous_down.contains(&button)
+        }
+    }
+}
+
+pub fn is_action_down(bindings: &Bindings, gamepad: &GamepadButtons, action: Action) -> bool {
+    action_inputs(bindings, action)
+        .iter()
+        .any(|input| is_input_down(*input, gamepad))
+}
+
+pub fn is_action_pressed(bindings: &Bindings, gamepad: &GamepadButtons, action: Action) -> bool {
+    action_inputs(bindings, action)
+        .iter()
+        .any(|input| is_input_pressed(*input, gamepad))
+}
+
+pub fn is_action_released(bindings: &Bindings, gamepad: &GamepadButtons, action: Action) -> bool {
+    action_inputs(bindings, action)
+        .iter()
+        .any(|input| is_input_released(*input, gamepad))
+}
+
+// The index of the option picked on this frame, if any.
+pub fn pressed_option(bindings: &Bindings, gamepad: &GamepadButtons) -> Option<usize> {
+    OPTION_ACTIONS
+        .iter()
+        .position(|action| is_action_pressed(bindings, gamepad, *action))
+}
+
+pub fn action_state(bindings: &Bindings, gamepad: &GamepadButtons, action: Action) -> ButtonState {
+    ButtonState {
+        pressed: is_action_pressed(bindings, gamepad, action),
+        held: is_action_down(bindings, gamepad, action),
+        released: is_action_released(bindings, gamepad, action),
+    }
+}
+
+// The first input pressed on this frame, used to capture a new binding.
+pub fn pressed_input(gamepad: &GamepadButtons) -> Option<Input> {
+    KEY_CODES
+        .iter()
+        .map(|key| Input::Key(*key))
+        .chain(MOUSE_BUTTONS.iter().map(|button| Input::Mouse(*button)))
+        .chain(GAMEPAD_BUTTONS.iter().map(|button| Input::Gamepad(*button)))
+        .find(|input| is_input_pressed(*input, gamepad))
+}
+
+pub struct RebindMenu {
+    selected: usize,
+    is_waiting_for_input: bool,
+}
+
+pub fn new_rebind_menu() -> RebindMenu {
+    RebindMenu {
+        selected: 0,
+        is_waiting_for_input: false,
+    }
+}
+
+// Up and down pick an action, enter waits for the next input to bind to it in
+// place of its input from the same device, delete restores its default and
+// escape closes the menu. These keys are fixed
+// so that the menu can always be used to repair broken bindings.
+pub fn update_rebind_menu(
+    menu: RebindMenu,
+    bindings: Bindings,
+    gamepad: &GamepadButtons,
+) -> (Option<RebindMenu>, Bindings) {
+    let action = ACTIONS[menu.selected];
+
+    if menu.is_waiting_for_input {
+        let next_menu = RebindMenu {
+            is_waiting_for_input: false,
+            ..menu
+        };
+
+        return match pressed_input(gamepad) {
+            Some(Input::Key(KeyCode::Escape)) => (Some(next_menu), bindings),
+            Some(input) => (Some(next_menu), rebind(bindings, action, input)),
+            None => (Some(menu), bindings),
+        };
+    }
+
+    if is_key_pressed(KeyCode::Escape) {
+        return (None, bindings);
+    }
+
+    if is_key_pressed(KeyCode::Up) {
+        return (
+            Some(RebindMenu {
+                selected: (menu.selected + ACTIONS.len() - 1) % ACTIONS.len(),
+                ..menu
+            }),
+            bindings,
+        );
+    }
+
+    if is_key_pressed(KeyCode::Down) {
+        return (
+            Some(RebindMenu {
+                selected: (menu.selected + 1) % ACTIONS.len(),
+                ..menu
+            }),
+            bindings,
+        );
+    }
+
+    if is_key_pressed(KeyCode::Enter) {
+        return (
+            Some(RebindMenu {
+                is_waiting_for_input: true,
+                ..menu
+            }),
+            bindings,
+        );
+    }
+
+    if is_key_pressed(KeyCode::Delete) {
+        return (Some(menu), bind(bindings, action, default_inputs(action)));
+    }
+
+    (Some(menu), bindings)
+}
+
+pub fn draw_rebind_menu(menu: &RebindMenu, bindings: &Bindings) {
+    clear_background(WHITE);
+
+    // Small enough for every action to fit in the default window.
+    let font_size = 15.;
+
+    draw_text(
+        "Bindings. [up]/[down] to select, [enter] to rebind, [delete] to reset, [escape] to close.",
+        10.,
+        font_size * 1.5,
+        font_size,
+        DARKGRAY,
+    );
+
+    ACTIONS.iter().enumerate().for_each(|(index, action)| {
+        let inputs = if menu.is_waiting_for_input && index == menu.selected {
+            String::from("press an input, [escape] to cancel")
+        } else {
+            action_inputs(bindings, *action)
+                .iter()
+                .map(|input| input_name(*input))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        // Conflicts are flagged rather than refused, so that two actions can
+        // swap inputs one rebind at a time.
+        let conflicts = conflicting_actions(bindings, *action);
+        let conflicts = if conflicts.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " (also {})",
+                conflicts
+                    .iter()
+                    .map(|conflict| action_name(*conflict))
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        };
+
+        let color = if index == menu.selected {
+            BLUE
+        } else if !conflicts.is_empty() {
+            RED
+        } else {
+            DARKGRAY
+        };
+
+        draw_text(
+            format!("{}: {}{}", action_name(*action), inputs, conflicts).as_str(),
+            10.,
+            font_size * (index + 3) as f32,
+            font_size,
+            color,
+        );
+    });
+}
@@ -0,0 +1,62 @@
+// Short animations played where something happened, such as the burst left
+// by a popped balloon. They are only for show, so rather than living in any
+// implementation's state the front end subscribes them to the events the
+// simulation reports.
+
+use macroquad::prelude::*;
+
+use crate::atlas::draw_sprite;
+use crate::atlas::is_action_playing;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::events::GameEvent;
+use crate::functional::balloon::BALLOON_SIZE;
+
+// As wide as balloons are drawn, so the burst covers the balloon it replaces.
+const POP_WIDTH: f32 = BALLOON_SIZE;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Effect {
+    pub position: Vec2,
+    // Seconds since it started.
+    pub age: f32,
+}
+
+pub fn spawn_effect(effects: &mut Vec<Effect>, event: &GameEvent) {
+    if let GameEvent::BalloonPopped { position } = event {
+        effects.push(Effect {
+            position: *position,
+            age: 0.,
+        });
+    }
+}
+
+// Effects are dropped once their animation has played.
+pub fn update_effects(effects: Vec<Effect>, delta_time: f32, sheet: &SpriteSheet) -> Vec<Effect> {
+    effects
+        .into_iter()
+        .map(|effect| Effect {
+            age: effect.age + delta_time,
+            ..effect
+        })
+        .filter(|effect| {
+            is_action_playing(sheet, SpriteKind::Balloon, SpriteAction::Pop, effect.age)
+        })
+        .collect()
+}
+
+pub fn draw_effects(effects: &[Effect], sheet: &SpriteSheet) {
+    effects.iter().for_each(|effect| {
+        draw_sprite(
+            sheet,
+            SpriteKind::Balloon,
+            SpriteAction::Pop,
+            effect.age,
+            effect.position,
+            POP_WIDTH,
+            0.,
+            WHITE,
+        );
+    });
+}
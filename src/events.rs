@@ -0,0 +1,36 @@
+// Things that happened during a step, reported by the gameplay stages instead
+// of applying every consequence inline. Stages later in the pipeline turn them
+// into coins, lives and statistics, and code outside the simulation can
+// subscribe to them for sounds or effects.
+
+use macroquad::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    BalloonPopped { position: Vec2 },
+    BalloonEscaped { position: Vec2 },
+    TowerPlaced { position: Vec2, cost: u32 },
+    TowerUpgraded { position: Vec2, cost: u32 },
+    TowerSold { position: Vec2, refund: u32 },
+    TowerPlacementUndone { position: Vec2, refund: u32 },
+    TowerUpgradeUndone { position: Vec2, refund: u32 },
+    RoundStarted { round: u32 },
+}
+
+pub trait Subscriber {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+impl<F: FnMut(&GameEvent)> Subscriber for F {
+    fn notify(&mut self, event: &GameEvent) {
+        self(event)
+    }
+}
+
+pub fn publish(events: &[GameEvent], subscribers: &mut [&mut dyn Subscriber]) {
+    events.iter().for_each(|event| {
+        subscribers
+            .iter_mut()
+            .for_each(|subscriber| subscriber.notify(event))
+    });
+}
@@ -1,5 +1,17 @@
+use std::io::Error;
+
 use macroquad::prelude::*;
 
+use crate::atlas::draw_sprite;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::functional::save::invalid_data;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
 #[derive(Copy, Clone)]
 enum Direction {
     Right,
@@ -25,21 +37,27 @@ impl PartialEq for BalloonState {
 }
 
 const BALLOON_SPRITE_SIZE: f32 = 48.;
-const BALLOON_SIZE: f32 = BALLOON_SPRITE_SIZE * 3.;
+pub const BALLOON_SIZE: f32 = BALLOON_SPRITE_SIZE * 3.;
 pub const BALLOON_COLLIDER_SIZE: f32 = BALLOON_SIZE / 2.;
-const BALLOON_SPEED: f32 = 150.;
+pub const BALLOON_SPEED: f32 = 150.;
 
 #[derive(Copy, Clone)]
 pub struct Balloon {
     pub position: Vec2,
+    previous_position: Vec2,
     direction: Direction,
+    speed: f32,
     pub state: BalloonState,
 }
 
-pub fn new_balloon() -> Balloon {
+pub fn new_balloon(speed_multiplier: f32) -> Balloon {
+    let position = Vec2::new(BALLOON_SIZE, WORLD_HEIGHT / 2.);
+
     Balloon {
-        position: Vec2::new(BALLOON_SIZE, screen_height() / 2.),
+        position,
+        previous_position: position,
         direction: Direction::Right,
+        speed: BALLOON_SPEED * speed_multiplier,
         state: BalloonState::Alive,
     }
 }
@@ -48,8 +66,8 @@ pub fn update_balloon(balloon: Balloon, delta_time: f32) -> Balloon {
     let current_position_x = balloon.position.x;
 
     let new_position_x = match balloon.direction {
-        Direction::Right => current_position_x + BALLOON_SPEED * delta_time,
-        Direction::Left => current_position_x - BALLOON_SPEED * delta_time,
+        Direction::Right => current_position_x + balloon.speed * delta_time,
+        Direction::Left => current_position_x - balloon.speed * delta_time,
     };
 
     Balloon {
@@ -57,6 +75,7 @@ pub fn update_balloon(balloon: Balloon, delta_time: f32) -> Balloon {
             x: new_position_x,
             y: balloon.position.y,
         },
+        previous_position: balloon.position,
         ..balloon
     }
 }
@@ -65,26 +84,57 @@ pub fn has_escaped(balloon: Balloon) -> bool {
     let position = balloon.position;
     let size = BALLOON_COLLIDER_SIZE;
 
-    position.x > screen_width() + size || position.x < -size
+    position.x > WORLD_WIDTH + size || position.x < -size
 }
 
-pub fn draw_balloon(balloon: Balloon, balloon_texture: Texture2D) {
-    draw_texture_ex(
-        &balloon_texture,
-        balloon.position.x - BALLOON_SIZE / 2.,
-        screen_height() / 2. - (BALLOON_SIZE / 2.),
+// Balloons bob in step with each other, on the clock rather than the game.
+pub fn draw_balloon(balloon: Balloon, sheet: &SpriteSheet, alpha: f32) {
+    let position = balloon.previous_position.lerp(balloon.position, alpha);
+
+    draw_sprite(
+        sheet,
+        SpriteKind::Balloon,
+        SpriteAction::Idle,
+        get_time() as f32,
+        position,
+        BALLOON_SIZE,
+        0.,
         WHITE,
-        DrawTextureParams {
-            dest_size: Some(Vec2::new(BALLOON_SIZE, BALLOON_SIZE)),
-            ..Default::default()
-        },
     );
 
-    draw_circle_lines(
-        balloon.position.x,
-        screen_height() / 2.,
-        BALLOON_COLLIDER_SIZE,
-        1.,
-        RED,
-    );
+    draw_circle_lines(position.x, position.y, BALLOON_COLLIDER_SIZE, 1., RED);
+}
+
+pub fn encode_balloon(balloon: &Balloon, writer: &mut SaveWriter) {
+    writer.write_vec2(balloon.position);
+    writer.write_vec2(balloon.previous_position);
+    writer.write_u8(match balloon.direction {
+        Direction::Right => 0,
+        Direction::Left => 1,
+    });
+    writer.write_f32(balloon.speed);
+    writer.write_u8(match balloon.state {
+        BalloonState::Alive => 0,
+        BalloonState::Popped => 1,
+        BalloonState::Escaped => 2,
+    });
+}
+
+pub fn decode_balloon(reader: &mut SaveReader) -> Result<Balloon, Error> {
+    Ok(Balloon {
+        position: reader.read_vec2()?,
+        previous_position: reader.read_vec2()?,
+        direction: match reader.read_u8()? {
+            0 => Direction::Right,
+            1 => Direction::Left,
+            _ => return Err(invalid_data("Invalid balloon direction")),
+        },
+        speed: reader.read_f32()?,
+        state: match reader.read_u8()? {
+            0 => BalloonState::Alive,
+            1 => BalloonState::Popped,
+            2 => BalloonState::Escaped,
+            _ => return Err(invalid_data("Invalid balloon state")),
+        },
+    })
 }
@@ -0,0 +1,20 @@
+// The tower actions the player took since the current round started, newest
+// last. Undoing pops the latest one and reverses it with a full refund.
+// Selling clears the log, as it cannot be undone and shifts the towers the
+// earlier entries refer to.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    // Towers are always placed at the end of the list, so undoing in order
+    // always removes the last one.
+    PlaceTower { tower: usize, cost: u32 },
+    UpgradeTower { tower: usize, cost: u32 },
+}
+
+pub fn record_command(commands: Vec<Command>, command: Command) -> Vec<Command> {
+    let mut commands = commands;
+
+    commands.push(command);
+
+    commands
+}
@@ -0,0 +1,91 @@
+#[derive(Copy, Clone, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Impoppable,
+}
+
+pub const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Medium,
+    Difficulty::Hard,
+    Difficulty::Impoppable,
+];
+
+pub const fn starting_lives(difficulty: Difficulty) -> i32 {
+    match difficulty {
+        Difficulty::Easy => 5,
+        Difficulty::Medium => 3,
+        Difficulty::Hard => 2,
+        Difficulty::Impoppable => 1,
+    }
+}
+
+pub const fn starting_cash(difficulty: Difficulty) -> u32 {
+    match difficulty {
+        Difficulty::Easy => 40,
+        Difficulty::Medium => 30,
+        Difficulty::Hard => 25,
+        Difficulty::Impoppable => 25,
+    }
+}
+
+pub fn final_round(difficulty: Difficulty) -> u32 {
+    match difficulty {
+        Difficulty::Easy => 20,
+        Difficulty::Medium => 30,
+        Difficulty::Hard => 40,
+        Difficulty::Impoppable => 50,
+    }
+}
+
+fn price_multiplier(difficulty: Difficulty) -> f32 {
+    match difficulty {
+        Difficulty::Easy => 0.85,
+        Difficulty::Medium => 1.,
+        Difficulty::Hard => 1.08,
+        Difficulty::Impoppable => 1.2,
+    }
+}
+
+pub fn balloon_speed_multiplier(difficulty: Difficulty) -> f32 {
+    match difficulty {
+        Difficulty::Easy => 0.9,
+        Difficulty::Medium => 1.,
+        Difficulty::Hard => 1.1,
+        Difficulty::Impoppable => 1.2,
+    }
+}
+
+pub fn scale_price(difficulty: Difficulty, base_price: u32) -> u32 {
+    (base_price as f32 * price_multiplier(difficulty)).round() as u32
+}
+
+pub fn difficulty_name(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Easy => "Easy",
+        Difficulty::Medium => "Medium",
+        Difficulty::Hard => "Hard",
+        Difficulty::Impoppable => "Impoppable",
+    }
+}
+
+pub fn difficulty_id(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Easy => 0,
+        Difficulty::Medium => 1,
+        Difficulty::Hard => 2,
+        Difficulty::Impoppable => 3,
+    }
+}
+
+pub fn difficulty_from_id(id: u8) -> Option<Difficulty> {
+    match id {
+        0 => Some(Difficulty::Easy),
+        1 => Some(Difficulty::Medium),
+        2 => Some(Difficulty::Hard),
+        3 => Some(Difficulty::Impoppable),
+        _ => None,
+    }
+}
@@ -0,0 +1,93 @@
+use crate::functional::difficulty::starting_cash;
+use crate::functional::difficulty::Difficulty;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum GameMode {
+    Standard,
+    Sandbox,
+    Deflation,
+    HalfCash,
+    Apopalypse,
+    Chimps,
+}
+
+pub const GAME_MODES: [GameMode; 6] = [
+    GameMode::Standard,
+    GameMode::Sandbox,
+    GameMode::Deflation,
+    GameMode::HalfCash,
+    GameMode::Apopalypse,
+    GameMode::Chimps,
+];
+
+const DEFLATION_CASH: u32 = 100;
+
+pub fn game_mode_name(game_mode: GameMode) -> &'static str {
+    match game_mode {
+        GameMode::Standard => "Standard",
+        GameMode::Sandbox => "Sandbox",
+        GameMode::Deflation => "Deflation",
+        GameMode::HalfCash => "Half Cash",
+        GameMode::Apopalypse => "Apopalypse",
+        GameMode::Chimps => "CHIMPS",
+    }
+}
+
+pub fn starting_cash_for_mode(game_mode: GameMode, difficulty: Difficulty) -> u32 {
+    match game_mode {
+        GameMode::Deflation => DEFLATION_CASH,
+        GameMode::HalfCash => starting_cash(difficulty) / 2,
+        _ => starting_cash(difficulty),
+    }
+}
+
+pub fn income_multiplier(game_mode: GameMode) -> f32 {
+    match game_mode {
+        GameMode::Deflation => 0.,
+        GameMode::HalfCash => 0.5,
+        _ => 1.,
+    }
+}
+
+pub fn has_infinite_resources(game_mode: GameMode) -> bool {
+    game_mode == GameMode::Sandbox
+}
+
+pub fn allows_spawning_on_demand(game_mode: GameMode) -> bool {
+    game_mode == GameMode::Sandbox
+}
+
+pub fn pauses_between_rounds(game_mode: GameMode) -> bool {
+    game_mode != GameMode::Apopalypse
+}
+
+pub fn allows_selling(game_mode: GameMode) -> bool {
+    game_mode != GameMode::Chimps
+}
+
+pub fn allows_continues(game_mode: GameMode) -> bool {
+    game_mode != GameMode::Chimps
+}
+
+pub fn game_mode_id(game_mode: GameMode) -> u8 {
+    match game_mode {
+        GameMode::Standard => 0,
+        GameMode::Sandbox => 1,
+        GameMode::Deflation => 2,
+        GameMode::HalfCash => 3,
+        GameMode::Apopalypse => 4,
+        GameMode::Chimps => 5,
+    }
+}
+
+pub fn game_mode_from_id(id: u8) -> Option<GameMode> {
+    match id {
+        0 => Some(GameMode::Standard),
+        1 => Some(GameMode::Sandbox),
+        2 => Some(GameMode::Deflation),
+        3 => Some(GameMode::HalfCash),
+        4 => Some(GameMode::Apopalypse),
+        5 => Some(GameMode::Chimps),
+        _ => None,
+    }
+}
@@ -0,0 +1,15 @@
+pub struct Map {
+    pub name: &'static str,
+    pub background_path: &'static str,
+}
+
+pub const MAPS: [Map; 2] = [
+    Map {
+        name: "Meadow",
+        background_path: "resources/sprites/background.png",
+    },
+    Map {
+        name: "Cobblestones",
+        background_path: "resources/sprites/background2.png",
+    },
+];
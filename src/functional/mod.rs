@@ -1,4 +1,16 @@
 pub mod balloon;
+pub mod command;
+pub mod difficulty;
+pub mod game_mode;
+pub mod map;
 pub mod projectile;
+pub mod random;
+pub mod replay;
+pub mod rewind;
+pub mod save;
 pub mod scene;
+pub mod schedule;
+pub mod screen;
+pub mod sprites;
+pub mod statistics;
 pub mod tower;
@@ -1,7 +1,18 @@
+use std::io::Error;
+
 use macroquad::prelude::*;
 
+use crate::atlas::draw_sprite;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
 use crate::functional::balloon::Balloon;
 use crate::functional::balloon::BALLOON_COLLIDER_SIZE;
+use crate::functional::save::invalid_data;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
 
 #[derive(Copy, Clone)]
 pub enum ProjectileState {
@@ -13,27 +24,30 @@ pub enum ProjectileState {
 #[derive(Copy, Clone)]
 pub struct Projectile {
     position: Vec2,
+    previous_position: Vec2,
     direction: Vec2,
     state: ProjectileState,
 }
 
-const PROJECTILE_SIZE: f32 = 15.;
+pub const PROJECTILE_SIZE: f32 = 15.;
+pub const PROJECTILE_SPEED: f32 = 500.;
 
 pub fn new_projectile(position: Vec2, direction: Vec2) -> Projectile {
     Projectile {
         position,
+        previous_position: position,
         direction,
         state: ProjectileState::Alive,
     }
 }
 
 pub fn update_projectile(projectile: Projectile, delta_time: f32) -> Projectile {
-    let new_position = projectile.position + projectile.direction * 500. * delta_time;
+    let new_position = projectile.position + projectile.direction * PROJECTILE_SPEED * delta_time;
 
     let new_state = if projectile.position.x < 0.
-        || projectile.position.x > screen_width()
+        || projectile.position.x > WORLD_WIDTH
         || projectile.position.y < 0.
-        || projectile.position.y > screen_height()
+        || projectile.position.y > WORLD_HEIGHT
     {
         ProjectileState::Dead
     } else {
@@ -42,11 +56,16 @@ pub fn update_projectile(projectile: Projectile, delta_time: f32) -> Projectile
 
     Projectile {
         position: new_position,
+        previous_position: projectile.position,
         state: new_state,
         ..projectile
     }
 }
 
+pub fn projectile_position(projectile: &Projectile) -> Vec2 {
+    projectile.position
+}
+
 pub fn is_projectile_alive(projectile: &Projectile) -> bool {
     matches!(projectile.state, ProjectileState::Alive)
 }
@@ -62,12 +81,20 @@ pub fn hit_projectile(projectile: Projectile) -> Projectile {
     }
 }
 
-pub fn draw_projectile(projectile: Projectile) {
-    draw_circle(
-        projectile.position.x,
-        projectile.position.y,
-        PROJECTILE_SIZE,
-        ORANGE,
+pub fn draw_projectile(projectile: Projectile, sheet: &SpriteSheet, alpha: f32) {
+    let position = projectile
+        .previous_position
+        .lerp(projectile.position, alpha);
+
+    draw_sprite(
+        sheet,
+        SpriteKind::Projectile,
+        SpriteAction::Idle,
+        0.,
+        position,
+        PROJECTILE_SIZE * 2.,
+        projectile.direction.y.atan2(projectile.direction.x),
+        WHITE,
     );
 }
 
@@ -83,3 +110,28 @@ pub fn check_collision(projectile: Projectile, balloon: Balloon) -> bool {
 
     true
 }
+
+pub fn encode_projectile(projectile: &Projectile, writer: &mut SaveWriter) {
+    writer.write_vec2(projectile.position);
+    writer.write_vec2(projectile.previous_position);
+    writer.write_vec2(projectile.direction);
+    writer.write_u8(match projectile.state {
+        ProjectileState::Alive => 0,
+        ProjectileState::Dead => 1,
+        ProjectileState::Hit => 2,
+    });
+}
+
+pub fn decode_projectile(reader: &mut SaveReader) -> Result<Projectile, Error> {
+    Ok(Projectile {
+        position: reader.read_vec2()?,
+        previous_position: reader.read_vec2()?,
+        direction: reader.read_vec2()?,
+        state: match reader.read_u8()? {
+            0 => ProjectileState::Alive,
+            1 => ProjectileState::Dead,
+            2 => ProjectileState::Hit,
+            _ => return Err(invalid_data("Invalid projectile state")),
+        },
+    })
+}
@@ -0,0 +1,33 @@
+// SplitMix64, small and good enough for gameplay randomness, and fully
+// determined by its seed so a replayed session draws the same numbers.
+
+#[derive(Copy, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+pub const fn new_rng(seed: u64) -> Rng {
+    Rng { state: seed }
+}
+
+pub fn next_random(rng: Rng) -> (Rng, u64) {
+    let state = rng.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut value = state;
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^= value >> 31;
+
+    (Rng { state }, value)
+}
+
+pub fn random_range(rng: Rng, min: f32, max: f32) -> (Rng, f32) {
+    let (next_rng, value) = next_random(rng);
+    let unit = (value >> 40) as f32 / (1u64 << 24) as f32;
+
+    (next_rng, min + (max - min) * unit)
+}
+
+pub fn rng_state(rng: Rng) -> u64 {
+    rng.state
+}
@@ -0,0 +1,233 @@
+// Replays store the implementation and seed followed by the Keys fed to every
+// fixed tick, which is everything a simulation needs to reproduce a session.
+// A replay is only played back by the implementation that recorded it, the
+// only one guaranteed to reproduce it. Ticks are streamed as they happen, so
+// a replay is valid even if the game is closed abruptly.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+use macroquad::prelude::*;
+
+use crate::input::ButtonState;
+use crate::input::Keys;
+use crate::simulation::implementation_name;
+use crate::simulation::Implementation;
+
+const REPLAY_MAGIC: &[u8; 4] = b"BTDR";
+const REPLAY_VERSION: u8 = 7;
+const HEADER_SIZE: usize = 14;
+const TICK_SIZE: usize = 40;
+const NONE: u8 = u8::MAX;
+
+pub struct Replay {
+    pub seed: u64,
+    pub ticks: Vec<Keys>,
+}
+
+pub struct ReplayWriter {
+    writer: BufWriter<File>,
+}
+
+fn action_states(keys: &Keys) -> [ButtonState; 19] {
+    [
+        keys.tower_placement,
+        keys.cancel_tower_placement,
+        keys.place_tower,
+        keys.rotate_tower_clockwise,
+        keys.rotate_tower_counter_clockwise,
+        keys.upgrade_tower,
+        keys.sell_tower,
+        keys.start_round,
+        keys.spawn_balloon,
+        keys.pause,
+        keys.step_frame,
+        keys.change_game_speed,
+        keys.confirm,
+        keys.continue_game,
+        keys.nudge_left,
+        keys.nudge_right,
+        keys.nudge_up,
+        keys.nudge_down,
+        keys.undo,
+    ]
+}
+
+fn implementation_id(implementation: Implementation) -> u8 {
+    match implementation {
+        Implementation::Functional => 0,
+        Implementation::ObjectOriented => 1,
+        Implementation::BevyEcs => 2,
+    }
+}
+
+fn implementation_from_id(id: u8) -> Option<Implementation> {
+    match id {
+        0 => Some(Implementation::Functional),
+        1 => Some(Implementation::ObjectOriented),
+        2 => Some(Implementation::BevyEcs),
+        _ => None,
+    }
+}
+
+fn encode_vec2(bytes: &mut [u8], value: Vec2) {
+    bytes[0..4].copy_from_slice(&value.x.to_le_bytes());
+    bytes[4..8].copy_from_slice(&value.y.to_le_bytes());
+}
+
+fn decode_vec2(bytes: &[u8]) -> Vec2 {
+    Vec2::new(
+        f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        f32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+    )
+}
+
+// Every action takes three bits, for pressed, held and released.
+fn encode_tick(keys: &Keys) -> [u8; TICK_SIZE] {
+    let flags = action_states(keys)
+        .iter()
+        .enumerate()
+        .fold(0u64, |flags, (index, state)| {
+            flags
+                | ((state.pressed as u64) << (index * 3))
+                | ((state.held as u64) << (index * 3 + 1))
+                | ((state.released as u64) << (index * 3 + 2))
+        });
+
+    let option = |index: Option<usize>| index.map_or(NONE, |index| index as u8);
+
+    let mut bytes = [0; TICK_SIZE];
+
+    bytes[0..8].copy_from_slice(&flags.to_le_bytes());
+    bytes[8] = option(keys.select_map);
+    bytes[9] = option(keys.select_difficulty);
+    bytes[10] = option(keys.select_game_mode);
+    encode_vec2(&mut bytes[11..19], keys.pointer_position);
+    encode_vec2(&mut bytes[19..27], keys.pointer_delta);
+    // Grids are never zero sized, so zero stands for not snapping.
+    bytes[27..31].copy_from_slice(&keys.grid_size.unwrap_or(0.).to_le_bytes());
+    encode_vec2(&mut bytes[31..39], keys.interface_pointer_position);
+    bytes[39] = option(keys.select_shop_item);
+
+    bytes
+}
+
+fn decode_tick(bytes: &[u8]) -> Keys {
+    let mut flags = [0; 8];
+    flags.copy_from_slice(&bytes[0..8]);
+
+    let flags = u64::from_le_bytes(flags);
+    let flag = |index: usize| flags & (1 << index) != 0;
+    let state = |index: usize| ButtonState {
+        pressed: flag(index * 3),
+        held: flag(index * 3 + 1),
+        released: flag(index * 3 + 2),
+    };
+    let option = |byte: u8| match byte {
+        NONE => None,
+        index => Some(index as usize),
+    };
+
+    Keys {
+        tower_placement: state(0),
+        cancel_tower_placement: state(1),
+        place_tower: state(2),
+        rotate_tower_clockwise: state(3),
+        rotate_tower_counter_clockwise: state(4),
+        upgrade_tower: state(5),
+        sell_tower: state(6),
+        start_round: state(7),
+        spawn_balloon: state(8),
+        pause: state(9),
+        step_frame: state(10),
+        change_game_speed: state(11),
+        confirm: state(12),
+        continue_game: state(13),
+        nudge_left: state(14),
+        nudge_right: state(15),
+        nudge_up: state(16),
+        nudge_down: state(17),
+        undo: state(18),
+        select_map: option(bytes[8]),
+        select_difficulty: option(bytes[9]),
+        select_game_mode: option(bytes[10]),
+        select_shop_item: option(bytes[39]),
+        pointer_position: decode_vec2(&bytes[11..19]),
+        interface_pointer_position: decode_vec2(&bytes[31..39]),
+        pointer_delta: decode_vec2(&bytes[19..27]),
+        grid_size: Some(f32::from_le_bytes([
+            bytes[27], bytes[28], bytes[29], bytes[30],
+        ]))
+        .filter(|grid_size| *grid_size > 0.),
+    }
+}
+
+// Fails unless the replay was recorded by the given implementation.
+pub fn decode_replay(bytes: &[u8], implementation: Implementation) -> Result<Replay, Error> {
+    if bytes.len() < HEADER_SIZE || &bytes[0..4] != REPLAY_MAGIC {
+        return Err(Error::new(ErrorKind::InvalidData, "Not a replay file"));
+    }
+
+    if bytes[4] != REPLAY_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unsupported replay version {}", bytes[4]),
+        ));
+    }
+
+    let recorded_implementation = implementation_from_id(bytes[5])
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Unknown replay implementation"))?;
+
+    if recorded_implementation != implementation {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Replay was recorded with the {} implementation",
+                implementation_name(recorded_implementation)
+            ),
+        ));
+    }
+
+    let mut seed = [0; 8];
+    seed.copy_from_slice(&bytes[6..HEADER_SIZE]);
+
+    Ok(Replay {
+        seed: u64::from_le_bytes(seed),
+        ticks: bytes[HEADER_SIZE..]
+            .chunks_exact(TICK_SIZE)
+            .map(decode_tick)
+            .collect(),
+    })
+}
+
+pub fn load_replay(path: &str, implementation: Implementation) -> Result<Replay, Error> {
+    let mut bytes = Vec::new();
+
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    decode_replay(&bytes, implementation)
+}
+
+impl ReplayWriter {
+    pub fn create(path: &str, implementation: Implementation, seed: u64) -> Result<Self, Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(REPLAY_MAGIC)?;
+        writer.write_all(&[REPLAY_VERSION, implementation_id(implementation)])?;
+        writer.write_all(&seed.to_le_bytes())?;
+
+        Ok(Self { writer })
+    }
+
+    pub fn record(&mut self, keys: &Keys) -> Result<(), Error> {
+        self.writer.write_all(&encode_tick(keys))
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()
+    }
+}
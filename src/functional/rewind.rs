@@ -0,0 +1,70 @@
+// Every tick produces a new state, so rewinding is just keeping the last
+// few of them around. Scrubbing moves a cursor through the kept states, and
+// recording a new state while scrubbed back drops everything after the cursor.
+
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+pub const REWIND_CAPACITY: usize = 600;
+
+pub struct History<T> {
+    states: VecDeque<T>,
+    cursor: usize,
+}
+
+pub fn new_history<T>() -> History<T> {
+    History {
+        states: VecDeque::with_capacity(REWIND_CAPACITY),
+        cursor: 0,
+    }
+}
+
+pub fn record_state<T>(history: History<T>, state: T) -> History<T> {
+    let mut states = history.states;
+
+    states.truncate(history.cursor + 1);
+    states.push_back(state);
+
+    if states.len() > REWIND_CAPACITY {
+        states.pop_front();
+    }
+
+    History {
+        cursor: states.len() - 1,
+        states,
+    }
+}
+
+pub fn step_back<T>(history: History<T>) -> History<T> {
+    History {
+        cursor: history.cursor.saturating_sub(1),
+        ..history
+    }
+}
+
+pub fn step_forward<T>(history: History<T>) -> History<T> {
+    let last = history.states.len().saturating_sub(1);
+
+    History {
+        cursor: (history.cursor + 1).min(last),
+        ..history
+    }
+}
+
+pub fn current_state<T>(history: &History<T>) -> Option<&T> {
+    history.states.get(history.cursor)
+}
+
+pub fn draw_rewind_overlay<T>(history: &History<T>) {
+    let text = format!("REWIND: {}/{}", history.cursor + 1, history.states.len());
+    let text_size = measure_text(text.as_str(), None, 32, 1.0);
+
+    draw_text(
+        text.as_str(),
+        screen_width() - text_size.width - 10.,
+        32.,
+        32.,
+        YELLOW,
+    );
+}
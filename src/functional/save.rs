@@ -0,0 +1,174 @@
+// Save files start with a magic tag and a format version, followed by the
+// logical game state written field by field in little endian. Bumping
+// SAVE_VERSION is required whenever the layout of any encoded type changes.
+
+use std::fs::File;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+use macroquad::prelude::*;
+
+use crate::functional::scene::decode_game_state;
+use crate::functional::scene::encode_game_state;
+use crate::functional::scene::GameState;
+
+const SAVE_MAGIC: &[u8; 4] = b"BTDS";
+const SAVE_VERSION: u8 = 4;
+
+#[derive(Default)]
+pub struct SaveWriter {
+    bytes: Vec<u8>,
+}
+
+pub struct SaveReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl SaveWriter {
+    pub fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_vec2(&mut self, value: Vec2) {
+        self.write_f32(value.x);
+        self.write_f32(value.y);
+    }
+}
+
+impl<'a> SaveReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let end = self.offset + N;
+
+        if end > self.bytes.len() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Save file is truncated",
+            ));
+        }
+
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.bytes[self.offset..end]);
+        self.offset = end;
+
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.read_bytes()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_le_bytes(self.read_bytes()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.read_bytes()?))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_le_bytes(self.read_bytes()?))
+    }
+
+    pub fn read_vec2(&mut self) -> Result<Vec2, Error> {
+        Ok(Vec2::new(self.read_f32()?, self.read_f32()?))
+    }
+}
+
+pub fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+// Writes the header, then whatever the implementation encodes. Every
+// implementation writes the same layout, so a save from one loads in another.
+pub fn write_save_file(path: &str, encode: impl FnOnce(&mut SaveWriter)) -> Result<(), Error> {
+    let mut writer = SaveWriter::new();
+
+    writer.write_u8(SAVE_MAGIC[0]);
+    writer.write_u8(SAVE_MAGIC[1]);
+    writer.write_u8(SAVE_MAGIC[2]);
+    writer.write_u8(SAVE_MAGIC[3]);
+    writer.write_u8(SAVE_VERSION);
+
+    encode(&mut writer);
+
+    File::create(path)?.write_all(&writer.bytes)
+}
+
+pub fn read_save_file<T>(
+    path: &str,
+    decode: impl FnOnce(&mut SaveReader) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut bytes = Vec::new();
+
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let mut reader = SaveReader::new(&bytes);
+
+    let magic = [
+        reader.read_u8()?,
+        reader.read_u8()?,
+        reader.read_u8()?,
+        reader.read_u8()?,
+    ];
+
+    if &magic != SAVE_MAGIC {
+        return Err(invalid_data("Not a save file"));
+    }
+
+    let version = reader.read_u8()?;
+
+    if version != SAVE_VERSION {
+        return Err(invalid_data(
+            format!("Unsupported save version {}", version).as_str(),
+        ));
+    }
+
+    decode(&mut reader)
+}
+
+pub fn save_game(path: &str, state: &GameState) -> Result<(), Error> {
+    write_save_file(path, |writer| encode_game_state(state, writer))
+}
+
+pub fn load_game(path: &str) -> Result<GameState, Error> {
+    read_save_file(path, decode_game_state)
+}
@@ -1,18 +1,120 @@
 // this is the functional version of the Scene
 
+use std::io::Error;
+use std::sync::OnceLock;
+
 use macroquad::prelude::*;
 
+use crate::events::GameEvent;
+use crate::guides::draw_placement_guides;
+use crate::guides::draw_range;
+use crate::input::is_drag;
+use crate::input::Keys;
+use crate::input::NO_KEYS;
+use crate::placement::clamp_to_world;
+use crate::placement::draw_grid;
+use crate::placement::nudge_offset;
+use crate::placement::rotation_offset;
+use crate::placement::snap_to_grid;
+use crate::shop::chosen_shop_item;
+use crate::shop::draw_shop;
+use crate::shop::is_on_sidebar;
+use crate::simulation::GameSimulation;
+use crate::snapshot::GameSnapshot;
+use crate::snapshot::PlacementSnapshot;
+use crate::view::set_interface_camera;
+use crate::view::set_view_camera;
+use crate::view::View;
+use crate::view::INTERFACE_HEIGHT;
+use crate::view::INTERFACE_WIDTH;
+use crate::world::is_in_world;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
+use crate::functional::balloon::decode_balloon;
 use crate::functional::balloon::draw_balloon;
+use crate::functional::balloon::encode_balloon;
 use crate::functional::balloon::has_escaped;
 use crate::functional::balloon::new_balloon;
 use crate::functional::balloon::update_balloon;
 use crate::functional::balloon::Balloon;
 use crate::functional::balloon::BalloonState;
 
+use crate::functional::command::record_command;
+use crate::functional::command::Command;
+
+use crate::functional::difficulty::balloon_speed_multiplier;
+use crate::functional::difficulty::difficulty_from_id;
+use crate::functional::difficulty::difficulty_id;
+use crate::functional::difficulty::difficulty_name;
+use crate::functional::difficulty::final_round;
+use crate::functional::difficulty::scale_price;
+use crate::functional::difficulty::starting_cash;
+use crate::functional::difficulty::starting_lives;
+use crate::functional::difficulty::Difficulty;
+use crate::functional::difficulty::DIFFICULTIES;
+
+use crate::functional::game_mode::allows_continues;
+use crate::functional::game_mode::allows_selling;
+use crate::functional::game_mode::allows_spawning_on_demand;
+use crate::functional::game_mode::game_mode_from_id;
+use crate::functional::game_mode::game_mode_id;
+use crate::functional::game_mode::game_mode_name;
+use crate::functional::game_mode::has_infinite_resources;
+use crate::functional::game_mode::income_multiplier;
+use crate::functional::game_mode::pauses_between_rounds;
+use crate::functional::game_mode::starting_cash_for_mode;
+use crate::functional::game_mode::GameMode;
+use crate::functional::game_mode::GAME_MODES;
+
+use crate::functional::map::MAPS;
+
+use crate::functional::random::new_rng;
+use crate::functional::random::random_range;
+use crate::functional::random::rng_state;
+use crate::functional::random::Rng;
+
+use crate::functional::save::invalid_data;
+use crate::functional::save::load_game;
+use crate::functional::save::save_game;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+
+use crate::functional::schedule::add_stage;
+use crate::functional::schedule::new_schedule;
+use crate::functional::schedule::run_if;
+use crate::functional::schedule::run_schedule;
+use crate::functional::schedule::stage;
+use crate::functional::schedule::Schedule;
+use crate::functional::schedule::Stage;
+
+use crate::functional::screen::screen_snapshot;
+use crate::functional::screen::Screen;
+
+use crate::functional::sprites::Sprites;
+
+use crate::functional::statistics::decode_statistics;
+use crate::functional::statistics::encode_statistics;
+use crate::functional::statistics::record_event;
+use crate::functional::statistics::Statistics;
+use crate::functional::statistics::INITIAL_STATISTICS;
+
+use crate::functional::tower::add_tower_spent;
+use crate::functional::tower::can_upgrade_tower;
+use crate::functional::tower::decode_tower;
+use crate::functional::tower::downgrade_tower;
 use crate::functional::tower::draw_tower;
+use crate::functional::tower::encode_tower;
 use crate::functional::tower::increase_tower_pop_count;
+use crate::functional::tower::is_point_on_tower;
 use crate::functional::tower::new_tower;
+use crate::functional::tower::overlaps_tower;
+use crate::functional::tower::preview_tower_snapshot;
+use crate::functional::tower::tower_guides;
+use crate::functional::tower::tower_sell_value;
+use crate::functional::tower::tower_snapshot;
 use crate::functional::tower::update_tower;
+use crate::functional::tower::upgrade_tower;
 use crate::functional::tower::Tower;
 
 use super::projectile::check_collision;
@@ -22,151 +124,623 @@ use super::projectile::is_projectile_alive;
 use super::projectile::is_projectile_hit;
 use super::projectile::update_projectile;
 
-#[derive(Clone)]
-pub struct Keys {
-    pub tower_placement: bool,
-    pub cancel_tower_placement: bool,
-    pub place_tower: bool,
-    pub rotate_tower_clockwise: bool,
-    pub rotate_tower_counter_clockwise: bool,
-    pub reset: bool,
-}
-
 #[derive(Clone)]
 pub struct GameState {
     delta_time: f32,
     keys: Keys,
-    balloon_sprite: Option<Texture2D>,
-    background_sprite: Option<Texture2D>,
+    game_speed: u32,
+    rng: Rng,
+    screen: Screen,
+    map: usize,
+    difficulty: Difficulty,
+    game_mode: GameMode,
     coins: u32,
+    income_remainder: f32,
     lives: i32,
-    game_over: bool,
+    round: u32,
+    balloons_to_spawn: u32,
     spawn_timer: f32,
     is_placing_tower: bool,
     preview_tower: Option<Tower>,
+    // Where the pointer was pressed while placing, for aiming by dragging.
+    drag_start: Option<Vec2>,
+    // Where the arrow keys moved the preview to, until the pointer moves.
+    nudged_position: Option<Vec2>,
     balloons: Vec<Balloon>,
     towers: Vec<Tower>,
+    // The placements and upgrades made since the last round, which can be
+    // undone until the next one starts.
+    commands: Vec<Command>,
+    statistics: Statistics,
+    // Everything that happened since the step started, and where the events
+    // of the tick being simulated begin.
+    events: Vec<GameEvent>,
+    tick_events_start: usize,
 }
 
 const INITIAL_STATE: GameState = GameState {
     delta_time: 0.0,
-    keys: Keys {
-        tower_placement: false,
-        cancel_tower_placement: false,
-        place_tower: false,
-        rotate_tower_clockwise: false,
-        rotate_tower_counter_clockwise: false,
-        reset: false,
-    },
-    balloon_sprite: None,
-    background_sprite: None,
-    coins: 30,
-    lives: 3,
-    game_over: false,
+    keys: NO_KEYS,
+    game_speed: 1,
+    rng: new_rng(0),
+    screen: Screen::MainMenu,
+    map: 0,
+    difficulty: Difficulty::Medium,
+    game_mode: GameMode::Standard,
+    coins: starting_cash(Difficulty::Medium),
+    income_remainder: 0.0,
+    lives: starting_lives(Difficulty::Medium),
+    round: 0,
+    balloons_to_spawn: 0,
     spawn_timer: 0.0,
     is_placing_tower: false,
     preview_tower: None,
+    drag_start: None,
+    nudged_position: None,
     balloons: Vec::new(),
     towers: Vec::new(),
+    commands: Vec::new(),
+    statistics: INITIAL_STATISTICS,
+    events: Vec::new(),
+    tick_events_start: 0,
 };
 
-const TOWER_COST: u32 = 15;
+// The stages run between rounds and on every simulated tick. Extra stages
+// can be added to the ones from `default_schedules` and the result passed to
+// `update_scene_with_schedules`.
+pub struct Schedules {
+    pub between_rounds: Schedule<GameState>,
+    pub simulation: Schedule<GameState>,
+}
+
+static DEFAULT_SCHEDULES: OnceLock<Schedules> = OnceLock::new();
+
+pub const MAX_GAME_SPEED: u32 = 3;
+
+pub const SPAWN_INTERVAL: f32 = 1.;
+pub const SPAWN_JITTER: f32 = 0.25;
+
+pub const TOWER_COST: u32 = 15;
+pub const UPGRADE_COST: u32 = 10;
+
+pub fn new_scene(seed: u64) -> GameState {
+    GameState {
+        rng: new_rng(seed),
+        ..INITIAL_STATE
+    }
+}
+
+pub fn game_snapshot(state: &GameState) -> GameSnapshot {
+    GameSnapshot {
+        screen: screen_snapshot(state.screen),
+        game_speed: state.game_speed,
+        map: state.map,
+        difficulty: difficulty_id(state.difficulty) as usize,
+        game_mode: game_mode_id(state.game_mode) as usize,
+        coins: state.coins,
+        lives: state.lives,
+        round: state.round,
+        balloons_to_spawn: state.balloons_to_spawn,
+        balloons: state
+            .balloons
+            .iter()
+            .map(|balloon| balloon.position)
+            .collect(),
+        towers: state.towers.iter().map(tower_snapshot).collect(),
+        placement: PlacementSnapshot {
+            is_placing_tower: state.is_placing_tower,
+            preview_tower: state.preview_tower.as_ref().map(preview_tower_snapshot),
+            drag_start: state.drag_start,
+            nudged_position: state.nudged_position,
+        },
+    }
+}
 
-pub fn new_scene() -> GameState {
-    INITIAL_STATE
+pub fn can_save_game(state: &GameState) -> bool {
+    matches!(
+        state.screen,
+        Screen::BetweenRounds | Screen::Playing | Screen::Paused
+    )
 }
 
-pub async fn init_scene(state: GameState) -> GameState {
-    let mut next_state = state.clone();
+pub fn encode_game_state(state: &GameState, writer: &mut SaveWriter) {
+    // A game saved mid-round is restored paused, so the player is not
+    // dropped straight into the action when loading it.
+    writer.write_bool(state.screen == Screen::BetweenRounds);
+    writer.write_u32(state.map as u32);
+    writer.write_u8(difficulty_id(state.difficulty));
+    writer.write_u8(game_mode_id(state.game_mode));
+    writer.write_u32(state.coins);
+    writer.write_f32(state.income_remainder);
+    writer.write_i32(state.lives);
+    writer.write_u32(state.round);
+    writer.write_u32(state.balloons_to_spawn);
+    writer.write_f32(state.spawn_timer);
+    writer.write_u32(state.game_speed);
+    writer.write_u64(rng_state(state.rng));
+
+    writer.write_u32(state.balloons.len() as u32);
+    state
+        .balloons
+        .iter()
+        .for_each(|balloon| encode_balloon(balloon, writer));
 
-    next_state.background_sprite = Some(
-        load_texture("resources/sprites/background2.png")
-            .await
-            .unwrap(),
-    );
-    next_state.balloon_sprite = Some(load_texture("resources/sprites/balloon.png").await.unwrap());
+    writer.write_u32(state.towers.len() as u32);
+    state
+        .towers
+        .iter()
+        .for_each(|tower| encode_tower(tower, writer));
 
-    if next_state.background_sprite.is_none() || next_state.balloon_sprite.is_none() {
-        panic!("Failed to load sprites");
+    encode_statistics(&state.statistics, writer);
+}
+
+pub fn decode_game_state(reader: &mut SaveReader) -> Result<GameState, Error> {
+    let screen = match reader.read_bool()? {
+        true => Screen::BetweenRounds,
+        false => Screen::Paused,
+    };
+
+    let map = reader.read_u32()? as usize;
+
+    if map >= MAPS.len() {
+        return Err(invalid_data("Invalid map"));
     }
 
-    next_state
-        .background_sprite
-        .as_mut()
-        .unwrap()
-        .set_filter(FilterMode::Nearest);
+    let difficulty =
+        difficulty_from_id(reader.read_u8()?).ok_or_else(|| invalid_data("Invalid difficulty"))?;
+    let game_mode =
+        game_mode_from_id(reader.read_u8()?).ok_or_else(|| invalid_data("Invalid game mode"))?;
+    let coins = reader.read_u32()?;
+    let income_remainder = reader.read_f32()?;
+    let lives = reader.read_i32()?;
+    let round = reader.read_u32()?;
+    let balloons_to_spawn = reader.read_u32()?;
+    let spawn_timer = reader.read_f32()?;
+    let game_speed = reader.read_u32()?.clamp(1, MAX_GAME_SPEED);
+    let rng = new_rng(reader.read_u64()?);
+
+    let balloons_count = reader.read_u32()?;
+    let balloons = (0..balloons_count)
+        .map(|_| decode_balloon(reader))
+        .collect::<Result<Vec<Balloon>, Error>>()?;
+
+    let towers_count = reader.read_u32()?;
+    let towers = (0..towers_count)
+        .map(|_| decode_tower(reader))
+        .collect::<Result<Vec<Tower>, Error>>()?;
+
+    let statistics = decode_statistics(reader)?;
+
+    Ok(GameState {
+        game_speed,
+        rng,
+        screen,
+        map,
+        difficulty,
+        game_mode,
+        coins,
+        income_remainder,
+        lives,
+        round,
+        balloons_to_spawn,
+        spawn_timer,
+        balloons,
+        towers,
+        statistics,
+        ..INITIAL_STATE
+    })
+}
 
-    next_state
-        .balloon_sprite
-        .as_mut()
-        .unwrap()
-        .set_filter(FilterMode::Nearest);
+fn round_balloon_count(round: u32) -> u32 {
+    5 + round * 2
+}
 
-    next_state
+fn transition(state: GameState, screen: Screen) -> GameState {
+    GameState { screen, ..state }
+}
+
+fn emit(state: GameState, event: GameEvent) -> GameState {
+    let mut events = state.events;
+
+    events.push(event);
+
+    GameState { events, ..state }
+}
+
+pub fn step_events(state: &GameState) -> &[GameEvent] {
+    &state.events
 }
 
-fn reset(state: GameState) -> GameState {
+fn tick_events(state: &GameState) -> &[GameEvent] {
+    &state.events[state.tick_events_start..]
+}
+
+fn return_to_main_menu(state: GameState) -> GameState {
     GameState {
-        delta_time: 0.0,
-        keys: Keys {
-            tower_placement: false,
-            cancel_tower_placement: false,
-            place_tower: false,
-            rotate_tower_clockwise: false,
-            rotate_tower_counter_clockwise: false,
-            reset: false,
-        },
-        balloon_sprite: state.balloon_sprite,
-        background_sprite: state.background_sprite,
-        coins: 30,
-        lives: 3,
-        game_over: false,
-        spawn_timer: 0.0,
-        is_placing_tower: false,
-        preview_tower: None,
+        rng: state.rng,
+        ..INITIAL_STATE
+    }
+}
+
+fn reset(state: GameState, difficulty: Difficulty, game_mode: GameMode) -> GameState {
+    GameState {
+        rng: state.rng,
+        screen: Screen::BetweenRounds,
+        map: state.map,
+        difficulty,
+        game_mode,
+        coins: starting_cash_for_mode(game_mode, difficulty),
+        lives: starting_lives(difficulty),
+        ..INITIAL_STATE
+    }
+}
+
+fn continue_game(state: GameState) -> GameState {
+    GameState {
+        lives: starting_lives(state.difficulty),
+        screen: Screen::BetweenRounds,
+        balloons_to_spawn: 0,
         balloons: Vec::new(),
-        towers: Vec::new(),
+        ..state
     }
 }
 
-fn draw_game_over(state: GameState) -> GameState {
-    clear_background(LIGHTGRAY);
+fn start_round(state: GameState) -> GameState {
+    let round = state.round + 1;
+
+    emit(
+        GameState {
+            screen: Screen::Playing,
+            round,
+            balloons_to_spawn: round_balloon_count(round),
+            spawn_timer: 0.0,
+            commands: Vec::new(),
+            ..state
+        },
+        GameEvent::RoundStarted { round },
+    )
+}
+
+// Towers placed or upgraded during the round have already fired, so they can
+// no longer be undone.
+fn finish_round(state: GameState) -> GameState {
+    let state = GameState {
+        commands: Vec::new(),
+        ..state
+    };
+
+    if state.round >= final_round(state.difficulty) {
+        return transition(state, Screen::Victory);
+    }
+
+    if !pauses_between_rounds(state.game_mode) {
+        return start_round(state);
+    }
+
+    transition(state, Screen::BetweenRounds)
+}
+
+fn update_main_menu(_schedules: &Schedules, state: GameState) -> GameState {
+    if state.keys.confirm.pressed {
+        return transition(state, Screen::MapSelect);
+    }
+
+    state
+}
+
+fn update_map_select(_schedules: &Schedules, state: GameState) -> GameState {
+    match state.keys.select_map {
+        Some(map) if map < MAPS.len() => GameState {
+            screen: Screen::DifficultySelect,
+            map,
+            ..state
+        },
+        _ => state,
+    }
+}
+
+fn update_difficulty_select(_schedules: &Schedules, state: GameState) -> GameState {
+    match state
+        .keys
+        .select_difficulty
+        .and_then(|index| DIFFICULTIES.get(index))
+    {
+        Some(difficulty) => GameState {
+            screen: Screen::GameModeSelect,
+            difficulty: *difficulty,
+            ..state
+        },
+        None => state,
+    }
+}
+
+fn update_game_mode_select(_schedules: &Schedules, state: GameState) -> GameState {
+    match state
+        .keys
+        .select_game_mode
+        .and_then(|index| GAME_MODES.get(index))
+    {
+        Some(game_mode) => {
+            let difficulty = state.difficulty;
+
+            reset(state, difficulty, *game_mode)
+        }
+        None => state,
+    }
+}
+
+fn update_between_rounds(schedules: &Schedules, state: GameState) -> GameState {
+    let next_state = run_schedule(&schedules.between_rounds, state);
+
+    if next_state.keys.start_round.pressed || !pauses_between_rounds(next_state.game_mode) {
+        return start_round(next_state);
+    }
+
+    next_state
+}
+
+fn update_playing(schedules: &Schedules, state: GameState) -> GameState {
+    if state.keys.pause.pressed {
+        return transition(state, Screen::Paused);
+    }
+
+    let next_state = simulate_tick(schedules, handle_game_speed(state));
+
+    // Extra substeps reuse the same delta so that collisions behave exactly
+    // as they would at 1x, and input is only consumed by the first one.
+    (1..next_state.game_speed).fold(next_state, |state, _| {
+        if state.screen != Screen::Playing {
+            return state;
+        }
+
+        simulate_tick(
+            schedules,
+            GameState {
+                keys: NO_KEYS,
+                ..state
+            },
+        )
+    })
+}
+
+fn simulate_tick(schedules: &Schedules, state: GameState) -> GameState {
+    run_schedule(
+        &schedules.simulation,
+        GameState {
+            tick_events_start: state.events.len(),
+            ..state
+        },
+    )
+}
+
+fn update_paused(schedules: &Schedules, state: GameState) -> GameState {
+    if state.keys.pause.pressed {
+        return transition(state, Screen::Playing);
+    }
+
+    if state.keys.step_frame.pressed {
+        let next_state = simulate_tick(
+            schedules,
+            transition(
+                GameState {
+                    keys: NO_KEYS,
+                    ..state
+                },
+                Screen::Playing,
+            ),
+        );
+
+        if next_state.screen == Screen::Playing {
+            return transition(next_state, Screen::Paused);
+        }
+
+        return next_state;
+    }
+
+    handle_game_speed(state)
+}
 
+fn handle_game_speed(state: GameState) -> GameState {
+    if !state.keys.change_game_speed.pressed {
+        return state;
+    }
+
+    GameState {
+        game_speed: state.game_speed % MAX_GAME_SPEED + 1,
+        ..state
+    }
+}
+
+fn update_victory(_schedules: &Schedules, state: GameState) -> GameState {
+    if state.keys.confirm.pressed {
+        return return_to_main_menu(state);
+    }
+
+    state
+}
+
+fn update_defeat(_schedules: &Schedules, state: GameState) -> GameState {
+    if state.keys.confirm.pressed {
+        let difficulty = state.difficulty;
+        let game_mode = state.game_mode;
+
+        return reset(state, difficulty, game_mode);
+    }
+
+    if state.keys.continue_game.pressed && allows_continues(state.game_mode) {
+        return continue_game(state);
+    }
+
+    state
+}
+
+fn draw_message(text: &str) {
     clear_background(WHITE);
-    let text = "Game Over. Press [enter] to play again.";
+
     let font_size = 30.;
     let text_size = measure_text(text, None, font_size as _, 1.0);
 
     draw_text(
         text,
-        screen_width() / 2. - text_size.width / 2.,
-        screen_height() / 2. + text_size.height / 2.,
+        INTERFACE_WIDTH / 2. - text_size.width / 2.,
+        WORLD_HEIGHT / 2. + text_size.height / 2.,
+        font_size,
+        DARKGRAY,
+    );
+}
+
+fn draw_menu(title: &str, options: Vec<&str>) {
+    clear_background(WHITE);
+
+    let font_size = 30.;
+    let title_size = measure_text(title, None, font_size as _, 1.0);
+
+    draw_text(
+        title,
+        INTERFACE_WIDTH / 2. - title_size.width / 2.,
+        WORLD_HEIGHT / 4.,
         font_size,
         DARKGRAY,
     );
 
-    if state.keys.reset {
-        return reset(state);
+    options.iter().enumerate().for_each(|(index, option)| {
+        let line = format!("[{}] {}", index + 1, option);
+        let line_size = measure_text(line.as_str(), None, font_size as _, 1.0);
+
+        draw_text(
+            line.as_str(),
+            INTERFACE_WIDTH / 2. - line_size.width / 2.,
+            WORLD_HEIGHT / 4. + font_size * 1.5 * (index + 1) as f32,
+            font_size,
+            DARKGRAY,
+        );
+    });
+}
+
+fn draw_main_menu(_state: &GameState, _sprites: &Sprites, _alpha: f32, _view: &View) {
+    draw_message("Balloons. Press [enter] to start.");
+}
+
+fn draw_map_select(_state: &GameState, _sprites: &Sprites, _alpha: f32, _view: &View) {
+    draw_menu("Select a map:", MAPS.iter().map(|map| map.name).collect());
+}
+
+fn draw_difficulty_select(_state: &GameState, _sprites: &Sprites, _alpha: f32, _view: &View) {
+    draw_menu(
+        "Select a difficulty:",
+        DIFFICULTIES
+            .iter()
+            .map(|difficulty| difficulty_name(*difficulty))
+            .collect(),
+    );
+}
+
+fn draw_game_mode_select(_state: &GameState, _sprites: &Sprites, _alpha: f32, _view: &View) {
+    draw_menu(
+        "Select a game mode:",
+        GAME_MODES
+            .iter()
+            .map(|game_mode| game_mode_name(*game_mode))
+            .collect(),
+    );
+}
+
+fn draw_between_rounds(state: &GameState, sprites: &Sprites, _alpha: f32, view: &View) {
+    draw_world(state, sprites, 1., view);
+    draw_statistics(state);
+    draw_sidebar(state);
+    draw_undo_hint(state);
+
+    draw_text(
+        "Press [space] to start the next round",
+        10.,
+        WORLD_HEIGHT - 20.,
+        32.,
+        WHITE,
+    );
+}
+
+fn draw_playing(state: &GameState, sprites: &Sprites, alpha: f32, view: &View) {
+    draw_world(state, sprites, alpha, view);
+    draw_statistics(state);
+    draw_sidebar(state);
+}
+
+fn draw_undo_hint(state: &GameState) {
+    if state.commands.is_empty() {
+        return;
     }
 
-    state
+    draw_text(
+        "Press [z] to undo the last placement or upgrade",
+        10.,
+        WORLD_HEIGHT - 52.,
+        24.,
+        WHITE,
+    );
 }
 
-fn draw_background(state: GameState) -> GameState {
-    clear_background(LIGHTGRAY);
+fn draw_paused(state: &GameState, sprites: &Sprites, _alpha: f32, view: &View) {
+    draw_world(state, sprites, 1., view);
+    draw_statistics(state);
+    draw_sidebar(state);
+
+    draw_rectangle(
+        0.,
+        0.,
+        INTERFACE_WIDTH,
+        INTERFACE_HEIGHT,
+        Color::new(0., 0., 0., 0.5),
+    );
+
+    let text = "Paused. Press [p] to resume or [n] to step a frame.";
+    let font_size = 30.;
+    let text_size = measure_text(text, None, font_size as _, 1.0);
+
+    draw_text(
+        text,
+        INTERFACE_WIDTH / 2. - text_size.width / 2.,
+        WORLD_HEIGHT / 2. + text_size.height / 2.,
+        font_size,
+        WHITE,
+    );
+}
+
+fn draw_victory(_state: &GameState, _sprites: &Sprites, _alpha: f32, _view: &View) {
+    draw_message("Victory! Press [enter] to return to the main menu.");
+}
 
-    if state.background_sprite.is_none() {
-        panic!("Background sprite is not loaded");
+fn draw_defeat(state: &GameState, _sprites: &Sprites, _alpha: f32, _view: &View) {
+    if allows_continues(state.game_mode) {
+        draw_message("Game Over. Press [enter] to play again or [c] to continue.");
+    } else {
+        draw_message("Game Over. Press [enter] to play again.");
     }
+}
+
+fn draw_world(state: &GameState, sprites: &Sprites, alpha: f32, view: &View) {
+    set_view_camera(view);
+
+    draw_background(state, sprites);
+    draw_balloons(state, sprites, alpha);
+    draw_towers(state, sprites, alpha);
+    draw_hovered_tower_range(state);
+    draw_preview_tower(state, sprites);
+
+    set_interface_camera();
+}
+
+fn draw_background(state: &GameState, sprites: &Sprites) {
+    clear_background(LIGHTGRAY);
 
-    let sprite = state.background_sprite.as_ref().unwrap();
+    let sprite = &sprites.maps[state.map];
 
-    let scale_factor = screen_width() / sprite.width();
+    let scale_factor = WORLD_WIDTH / sprite.width();
     let adjusted_width = sprite.width() * scale_factor;
     let adjusted_height = sprite.height() * scale_factor;
 
-    let x = (screen_width() - adjusted_width) / 2.0;
-    let y = (screen_height() - adjusted_height) / 2.0;
+    let x = (WORLD_WIDTH - adjusted_width) / 2.0;
+    let y = (WORLD_HEIGHT - adjusted_height) / 2.0;
 
     draw_texture_ex(
         sprite,
@@ -178,51 +752,242 @@ fn draw_background(state: GameState) -> GameState {
             ..Default::default()
         },
     );
-
-    state
 }
 
-fn draw_statistics(state: GameState) -> GameState {
+fn draw_statistics(state: &GameState) {
+    let (coins, lives) = if has_infinite_resources(state.game_mode) {
+        (String::from("INF"), String::from("INF"))
+    } else {
+        (state.coins.to_string(), state.lives.to_string())
+    };
+
+    draw_text(format!("COINS: {}", coins).as_str(), 10., 32., 32., WHITE);
+
+    draw_text(format!("LIVES: {}", lives).as_str(), 10., 64., 32., WHITE);
+
     draw_text(
-        format!("COINS: {}", state.coins).as_str(),
+        format!("ROUND: {}/{}", state.round, final_round(state.difficulty)).as_str(),
         10.,
+        96.,
         32.,
+        WHITE,
+    );
+
+    draw_text(
+        format!("SPEED: {}x", state.game_speed).as_str(),
+        10.,
+        128.,
         32.,
         WHITE,
     );
 
     draw_text(
-        format!("LIVES: {}", state.lives).as_str(),
+        format!(
+            "{} - {} - {}",
+            MAPS[state.map].name,
+            difficulty_name(state.difficulty),
+            game_mode_name(state.game_mode)
+        )
+        .as_str(),
         10.,
-        64.,
+        160.,
         32.,
         WHITE,
     );
+}
 
-    state
+fn draw_sidebar(state: &GameState) {
+    let coins = (!has_infinite_resources(state.game_mode)).then_some(state.coins);
+
+    draw_shop(
+        &[scale_price(state.difficulty, TOWER_COST)],
+        coins,
+        state.keys.interface_pointer_position,
+    );
 }
 
-fn spawn_balloon(state: GameState) -> GameState {
+fn draw_balloons(state: &GameState, sprites: &Sprites, alpha: f32) {
+    for balloon in state.balloons.iter() {
+        draw_balloon(*balloon, &sprites.sheet, alpha);
+    }
+}
+
+fn draw_towers(state: &GameState, sprites: &Sprites, alpha: f32) {
+    state.towers.iter().for_each(|tower| {
+        draw_tower(tower.clone(), &sprites.sheet, false);
+
+        tower
+            .projectiles
+            .iter()
+            .for_each(|projectile| draw_projectile(*projectile, &sprites.sheet, alpha));
+    });
+}
+
+// Shows the range of the tower under the pointer, unless a tower is being
+// placed, which shows its own.
+fn draw_hovered_tower_range(state: &GameState) {
+    if state.is_placing_tower {
+        return;
+    }
+
+    let hovered_tower = state
+        .towers
+        .iter()
+        .find(|tower| is_point_on_tower(tower, state.keys.pointer_position));
+
+    if let Some(tower) = hovered_tower {
+        let guides = tower_guides(tower);
+
+        draw_range(guides.position, guides.range);
+    }
+}
+
+fn draw_preview_tower(state: &GameState, sprites: &Sprites) {
+    if !state.is_placing_tower {
+        return;
+    }
+
+    if let Some(preview_tower) = state.preview_tower.as_ref() {
+        let tower_cost = scale_price(state.difficulty, TOWER_COST);
+
+        if let Some(grid_size) = state.keys.grid_size {
+            draw_grid(grid_size);
+        }
+
+        let is_valid = can_afford(state, tower_cost)
+            && is_free_for_tower(state, preview_tower.position)
+            && !is_on_sidebar(state.keys.interface_pointer_position);
+
+        draw_tower(preview_tower.clone(), &sprites.sheet, !is_valid);
+        draw_placement_guides(&tower_guides(preview_tower), is_valid);
+    }
+}
+
+// On the map and clear of every other tower's footprint.
+fn is_free_for_tower(state: &GameState, position: Vec2) -> bool {
+    is_in_world(position)
+        && !state
+            .towers
+            .iter()
+            .any(|tower| overlaps_tower(tower, position))
+}
+
+fn can_afford(state: &GameState, price: u32) -> bool {
+    has_infinite_resources(state.game_mode) || state.coins >= price
+}
+
+fn spend(state: GameState, price: u32) -> GameState {
+    if has_infinite_resources(state.game_mode) {
+        return state;
+    }
+
     GameState {
-        balloons: state
-            .balloons
+        coins: state.coins - price,
+        ..state
+    }
+}
+
+fn earn(state: GameState, amount: u32) -> GameState {
+    let income = state.income_remainder + amount as f32 * income_multiplier(state.game_mode);
+
+    GameState {
+        coins: state.coins + income.floor() as u32,
+        income_remainder: income.fract(),
+        ..state
+    }
+}
+
+// Pays for pops and refunds, and takes a life for every escape, from the
+// events of the current tick.
+fn update_economy(state: GameState) -> GameState {
+    let (popped_balloons, escaped_balloons, refunds) = tick_events(&state).iter().fold(
+        (0, 0, 0),
+        |(popped_balloons, escaped_balloons, refunds), event| match event {
+            GameEvent::BalloonPopped { .. } => (popped_balloons + 1, escaped_balloons, refunds),
+            GameEvent::BalloonEscaped { .. } => (popped_balloons, escaped_balloons + 1, refunds),
+            GameEvent::TowerSold { refund, .. }
+            | GameEvent::TowerPlacementUndone { refund, .. }
+            | GameEvent::TowerUpgradeUndone { refund, .. } => {
+                (popped_balloons, escaped_balloons, refunds + refund)
+            }
+            _ => (popped_balloons, escaped_balloons, refunds),
+        },
+    );
+
+    let mut new_state = earn(
+        GameState {
+            coins: state.coins + refunds,
+            ..state
+        },
+        popped_balloons,
+    );
+
+    if !has_infinite_resources(new_state.game_mode) {
+        new_state = GameState {
+            lives: new_state.lives - escaped_balloons,
+            ..new_state
+        };
+    }
+
+    if new_state.lives <= 0 {
+        new_state = transition(new_state, Screen::Defeat);
+    }
+
+    new_state
+}
+
+fn update_statistics(state: GameState) -> GameState {
+    GameState {
+        statistics: tick_events(&state)
             .iter()
-            .chain(std::iter::once(&new_balloon()))
-            .cloned()
-            .collect(),
+            .fold(state.statistics, record_event),
         ..state
     }
 }
 
+fn handle_rounds(state: GameState) -> GameState {
+    if state.balloons_to_spawn > 0 {
+        return state;
+    }
+
+    if state.balloons.is_empty() {
+        return finish_round(state);
+    }
+
+    let is_final_round = state.round >= final_round(state.difficulty);
+
+    if !pauses_between_rounds(state.game_mode) && !is_final_round {
+        return start_round(state);
+    }
+
+    state
+}
+
+fn spawn_balloon(state: GameState) -> GameState {
+    let mut balloons = state.balloons;
+
+    balloons.push(new_balloon(balloon_speed_multiplier(state.difficulty)));
+
+    GameState { balloons, ..state }
+}
+
 fn handle_spawn_timer(state: GameState) -> GameState {
+    if state.balloons_to_spawn == 0 {
+        return state;
+    }
+
     let new_state = GameState {
         spawn_timer: state.spawn_timer + state.delta_time,
         ..state
     };
 
-    if new_state.spawn_timer > 1. {
+    if new_state.spawn_timer > SPAWN_INTERVAL {
+        let (rng, jitter) = random_range(new_state.rng, 0., SPAWN_JITTER);
+
         return spawn_balloon(GameState {
-            spawn_timer: new_state.spawn_timer - 1.,
+            rng,
+            spawn_timer: new_state.spawn_timer - SPAWN_INTERVAL - jitter,
+            balloons_to_spawn: new_state.balloons_to_spawn - 1,
             ..new_state
         });
     }
@@ -230,251 +995,569 @@ fn handle_spawn_timer(state: GameState) -> GameState {
     new_state
 }
 
+fn handle_balloon_spawning(state: GameState) -> GameState {
+    if state.keys.spawn_balloon.pressed {
+        return spawn_balloon(state);
+    }
+
+    state
+}
+
 fn update_balloons(state: GameState) -> GameState {
     GameState {
         balloons: state
             .balloons
-            .iter()
-            .map(|balloon| update_balloon(*balloon, state.delta_time))
+            .into_iter()
+            .map(|balloon| update_balloon(balloon, state.delta_time))
             .collect(),
         ..state
     }
 }
 
-fn draw_balloons(state: GameState) -> GameState {
-    for balloon in state.balloons.iter() {
-        draw_balloon(*balloon, state.balloon_sprite.as_ref().unwrap().clone());
-    }
-
-    state
-}
-
 fn clear_balloons(state: GameState) -> GameState {
     let mut new_state = GameState {
         balloons: state
             .balloons
-            .iter()
-            .map(|balloon| {
-                if has_escaped(*balloon) {
-                    let mut next_balloon = *balloon;
-
-                    next_balloon.state = BalloonState::Escaped;
-
-                    return next_balloon;
+            .into_iter()
+            .map(|mut balloon| {
+                if balloon.state == BalloonState::Alive && has_escaped(balloon) {
+                    balloon.state = BalloonState::Escaped;
                 }
 
-                *balloon
+                balloon
             })
             .collect(),
         ..state
     };
 
-    let escaped_balloons = new_state
+    new_state = new_state
         .balloons
         .iter()
         .filter(|balloon| balloon.state == BalloonState::Escaped)
-        .count();
+        .map(|balloon| GameEvent::BalloonEscaped {
+            position: balloon.position,
+        })
+        .collect::<Vec<GameEvent>>()
+        .into_iter()
+        .fold(new_state, emit);
 
-    new_state = GameState {
-        lives: new_state.lives - escaped_balloons as i32,
-        ..new_state
-    };
+    let mut balloons = new_state.balloons;
 
-    if new_state.lives <= 0 {
-        new_state = GameState {
-            game_over: true,
-            ..new_state
-        };
-    }
+    balloons.retain(|balloon| balloon.state == BalloonState::Alive);
 
     GameState {
-        balloons: new_state
-            .balloons
-            .iter()
-            .filter(|balloon| balloon.state == BalloonState::Alive)
-            .cloned()
-            .collect(),
+        balloons,
         ..new_state
     }
 }
 
+// Clicking places the tower where it is previewed, while dragging from the
+// press keeps it where the press started and aims it at the pointer. Towers
+// are only placed on free spots of the map and never by releasing over the
+// sidebar, and pressing an affordable shop button starts placing one.
 fn handle_tower_placement(state: GameState) -> GameState {
-    let mut next_state = state.clone();
+    let keys = &state.keys;
+    let tower_cost = scale_price(state.difficulty, TOWER_COST);
+
+    let is_shop_choice = chosen_shop_item(keys).is_some() && can_afford(&state, tower_cost);
+
+    let (is_placing_tower, preview_tower, drag_start, nudged_position) =
+        if keys.tower_placement.pressed || is_shop_choice {
+            (true, Some(new_tower(keys.pointer_position)), None, None)
+        } else if keys.cancel_tower_placement.pressed {
+            (false, None, None, None)
+        } else {
+            (
+                state.is_placing_tower,
+                state.preview_tower,
+                state.drag_start,
+                state.nudged_position,
+            )
+        };
 
-    if state.keys.cancel_tower_placement {
-        next_state.is_placing_tower = false;
-        next_state.preview_tower = None;
-    }
+    let drag_start = match drag_start {
+        None if is_placing_tower
+            && keys.place_tower.pressed
+            && !is_on_sidebar(keys.interface_pointer_position) =>
+        {
+            Some(keys.pointer_position)
+        }
+        drag_start => drag_start,
+    };
 
-    if state.keys.tower_placement {
-        let mouse_position = mouse_position();
+    let pointer_position = clamp_to_world(snap_to_grid(keys.pointer_position, keys.grid_size));
 
-        next_state.is_placing_tower = true;
-        next_state.preview_tower = Some(new_tower(Vec2::new(mouse_position.0, mouse_position.1)));
-    }
+    // Moving the pointer takes over from the arrow keys again.
+    let nudged_position = nudged_position.filter(|_| keys.pointer_delta == Vec2::ZERO);
+    let nudge = nudge_offset(keys);
 
-    if next_state.is_placing_tower {
-        let mut new_preview_tower = next_state.preview_tower.unwrap().clone();
+    let nudged_position = if is_placing_tower && nudge != Vec2::ZERO {
+        Some(clamp_to_world(
+            nudged_position.unwrap_or(pointer_position) + nudge,
+        ))
+    } else {
+        nudged_position
+    };
 
-        if state.keys.rotate_tower_clockwise {
-            let new_tower_angle = new_preview_tower.angle + 5. * state.delta_time;
-            new_preview_tower.angle = new_tower_angle;
-        } else if state.keys.rotate_tower_counter_clockwise {
-            let new_tower_angle = new_preview_tower.angle - 5. * state.delta_time;
-            new_preview_tower.angle = new_tower_angle;
-        }
+    let preview_tower = match preview_tower {
+        Some(mut tower) if is_placing_tower => {
+            tower.angle += rotation_offset(keys);
+            tower.position = nudged_position.unwrap_or(pointer_position);
 
-        new_preview_tower.position = Vec2::new(mouse_position().0, mouse_position().1);
+            if let Some(start) = drag_start {
+                let is_dragging = keys.place_tower.held || keys.place_tower.released;
 
-        draw_tower(new_preview_tower.clone(), next_state.coins < TOWER_COST);
+                if is_dragging && is_drag(start, keys.pointer_position) {
+                    let aim = keys.pointer_position - start;
 
-        if state.keys.place_tower && next_state.coins >= TOWER_COST {
-            next_state.towers.push(new_preview_tower.clone());
+                    tower.position = clamp_to_world(snap_to_grid(start, keys.grid_size));
+                    tower.angle = aim.y.atan2(aim.x);
+                }
+            }
 
-            next_state.is_placing_tower = false;
-            next_state.preview_tower = None;
-            next_state.coins -= TOWER_COST;
+            Some(tower)
         }
+        preview_tower => preview_tower,
+    };
 
-        next_state.preview_tower = Some(new_preview_tower);
+    let next_state = GameState {
+        is_placing_tower,
+        preview_tower,
+        drag_start: drag_start.filter(|_| !keys.place_tower.released),
+        nudged_position,
+        ..state
+    };
+
+    let is_blocked = next_state
+        .preview_tower
+        .as_ref()
+        .is_some_and(|tower| !is_free_for_tower(&next_state, tower.position));
+
+    if !next_state.is_placing_tower
+        || !next_state.keys.place_tower.released
+        || !can_afford(&next_state, tower_cost)
+        || is_blocked
+        || is_on_sidebar(next_state.keys.interface_pointer_position)
+    {
+        return next_state;
     }
 
-    next_state
+    let mut towers = next_state.towers;
+    let mut events = next_state.events;
+    let mut commands = next_state.commands;
+
+    if let Some(preview_tower) = &next_state.preview_tower {
+        commands = record_command(
+            commands,
+            Command::PlaceTower {
+                tower: towers.len(),
+                cost: tower_cost,
+            },
+        );
+        towers.push(add_tower_spent(preview_tower.clone(), tower_cost));
+        events.push(GameEvent::TowerPlaced {
+            position: preview_tower.position,
+            cost: tower_cost,
+        });
+    }
+
+    spend(
+        GameState {
+            is_placing_tower: false,
+            preview_tower: None,
+            towers,
+            events,
+            commands,
+            ..next_state
+        },
+        tower_cost,
+    )
 }
 
-fn update_towers(state: GameState) -> GameState {
-    let mut next_state = state.clone();
+fn handle_tower_upgrade(state: GameState) -> GameState {
+    if !state.keys.upgrade_tower.pressed || state.is_placing_tower {
+        return state;
+    }
+
+    let upgrade_cost = scale_price(state.difficulty, UPGRADE_COST);
+    let pointer_position = state.keys.pointer_position;
 
-    next_state.towers = next_state
+    let selected_tower = state
         .towers
-        .iter_mut()
-        .map(|tower| {
-            let mut new_tower = update_tower(tower.clone(), state.delta_time);
+        .iter()
+        .position(|tower| is_point_on_tower(tower, pointer_position) && can_upgrade_tower(tower));
+
+    match selected_tower {
+        Some(index) if can_afford(&state, upgrade_cost) => {
+            let position = state.towers[index].position;
+
+            spend(
+                emit(
+                    GameState {
+                        towers: state
+                            .towers
+                            .into_iter()
+                            .enumerate()
+                            .map(|(tower_index, tower)| {
+                                if tower_index == index {
+                                    return add_tower_spent(upgrade_tower(tower), upgrade_cost);
+                                }
+
+                                tower
+                            })
+                            .collect(),
+                        commands: record_command(
+                            state.commands,
+                            Command::UpgradeTower {
+                                tower: index,
+                                cost: upgrade_cost,
+                            },
+                        ),
+                        ..state
+                    },
+                    GameEvent::TowerUpgraded {
+                        position,
+                        cost: upgrade_cost,
+                    },
+                ),
+                upgrade_cost,
+            )
+        }
+        _ => state,
+    }
+}
 
-            new_tower.projectiles = new_tower
-                .projectiles
-                .iter_mut()
-                .map(|projectile| update_projectile(*projectile, state.delta_time))
-                .collect();
+fn handle_tower_selling(state: GameState) -> GameState {
+    if !state.keys.sell_tower.pressed || state.is_placing_tower || !allows_selling(state.game_mode)
+    {
+        return state;
+    }
 
-            new_tower.clone()
-        })
-        .collect();
+    let pointer_position = state.keys.pointer_position;
 
-    next_state.towers.iter().for_each(|tower| {
-        draw_tower(tower.clone(), false);
+    let selected_tower = state
+        .towers
+        .iter()
+        .position(|tower| is_point_on_tower(tower, pointer_position));
+
+    match selected_tower {
+        Some(index) => {
+            let mut towers = state.towers;
+            let tower = towers.remove(index);
+
+            emit(
+                GameState {
+                    towers,
+                    commands: Vec::new(),
+                    ..state
+                },
+                GameEvent::TowerSold {
+                    position: tower.position,
+                    refund: tower_sell_value(&tower),
+                },
+            )
+        }
+        None => state,
+    }
+}
 
-        tower
-            .projectiles
-            .iter()
-            .for_each(|projectile| draw_projectile(*projectile));
-    });
+// Reverses the latest placement or upgrade since the last round, leaving the
+// refund to the economy stage. It only runs between rounds.
+fn handle_undo(state: GameState) -> GameState {
+    if !state.keys.undo.pressed {
+        return state;
+    }
 
-    next_state
+    let mut commands = state.commands;
+    let mut towers = state.towers;
+
+    match commands.pop() {
+        Some(Command::PlaceTower { tower, cost }) if tower < towers.len() => {
+            let removed_tower = towers.remove(tower);
+
+            emit(
+                GameState {
+                    towers,
+                    commands,
+                    ..state
+                },
+                GameEvent::TowerPlacementUndone {
+                    position: removed_tower.position,
+                    refund: cost,
+                },
+            )
+        }
+        Some(Command::UpgradeTower { tower, cost }) if tower < towers.len() => {
+            let position = towers[tower].position;
+
+            towers[tower] = downgrade_tower(towers[tower].clone(), cost);
+
+            emit(
+                GameState {
+                    towers,
+                    commands,
+                    ..state
+                },
+                GameEvent::TowerUpgradeUndone {
+                    position,
+                    refund: cost,
+                },
+            )
+        }
+        _ => GameState {
+            towers,
+            commands,
+            ..state
+        },
+    }
+}
+
+fn update_towers(state: GameState) -> GameState {
+    let delta_time = state.delta_time;
+
+    GameState {
+        towers: state
+            .towers
+            .into_iter()
+            .map(|tower| {
+                let mut tower = update_tower(tower, delta_time);
+
+                tower.projectiles = tower
+                    .projectiles
+                    .into_iter()
+                    .map(|projectile| update_projectile(projectile, delta_time))
+                    .collect();
+
+                tower
+            })
+            .collect(),
+        ..state
+    }
 }
 
 fn handle_popping(state: GameState) -> GameState {
-    let mut next_state = state.clone();
+    let mut balloons = state.balloons;
+    let mut events = state.events;
 
-    next_state.towers = next_state
+    let towers = state
         .towers
-        .iter()
-        .map(|tower| {
-            let mut new_tower = tower.clone();
-
-            new_tower.projectiles = new_tower
+        .into_iter()
+        .map(|mut tower| {
+            tower.projectiles = tower
                 .projectiles
-                .iter()
+                .into_iter()
                 .map(|projectile| {
-                    let mut new_projectile = *projectile;
-
-                    next_state.balloons.iter_mut().for_each(|balloon| {
-                        if check_collision(new_projectile, *balloon) {
-                            new_projectile = hit_projectile(new_projectile);
-                            balloon.state = BalloonState::Popped;
-                            next_state.coins += 1;
+                    // A dart pops one balloon, and a balloon is popped once.
+                    balloons.iter_mut().fold(projectile, |projectile, balloon| {
+                        if is_projectile_hit(&projectile)
+                            || balloon.state != BalloonState::Alive
+                            || !check_collision(projectile, *balloon)
+                        {
+                            return projectile;
                         }
-                    });
 
-                    new_projectile
+                        balloon.state = BalloonState::Popped;
+                        events.push(GameEvent::BalloonPopped {
+                            position: balloon.position,
+                        });
+
+                        hit_projectile(projectile)
+                    })
                 })
                 .collect();
 
-            new_tower
+            tower
         })
         .collect();
 
-    next_state
+    GameState {
+        balloons,
+        towers,
+        events,
+        ..state
+    }
 }
 
 fn clean_projectiles(state: GameState) -> GameState {
-    let mut next_state = state.clone();
-
-    next_state.towers = next_state
-        .towers
-        .iter_mut()
-        .map(|tower| {
-            let mut new_tower = tower.clone();
+    GameState {
+        towers: state
+            .towers
+            .into_iter()
+            .map(|mut tower| {
+                let projectiles_hit = tower
+                    .projectiles
+                    .iter()
+                    .filter(|projectile| is_projectile_hit(projectile))
+                    .count();
+
+                tower.projectiles.retain(is_projectile_alive);
+
+                if projectiles_hit > 0 {
+                    return increase_tower_pop_count(tower, projectiles_hit as u32);
+                }
 
-            let projectiles_count = new_tower.projectiles.len();
+                tower
+            })
+            .collect(),
+        ..state
+    }
+}
 
-            new_tower
-                .projectiles
-                .retain(|projectile| !is_projectile_hit(projectile));
+fn update_stateful(
+    schedules: &Schedules,
+    state: GameState,
+    delta_time: f32,
+    keys: Keys,
+    next_fn: fn(&Schedules, GameState) -> GameState,
+) -> GameState {
+    let mut events = state.events;
+
+    events.clear();
+
+    next_fn(
+        schedules,
+        GameState {
+            delta_time,
+            keys,
+            events,
+            tick_events_start: 0,
+            ..state
+        },
+    )
+}
 
-            let projectiles_hit = projectiles_count - new_tower.projectiles.len();
+pub fn is_playing(state: &GameState) -> bool {
+    state.screen == Screen::Playing
+}
 
-            new_tower.projectiles.retain(is_projectile_alive);
+pub fn is_sandbox(state: &GameState) -> bool {
+    state.game_mode == GameMode::Sandbox
+}
 
-            if projectiles_hit > 0 {
-                new_tower = increase_tower_pop_count(new_tower, projectiles_hit as u32);
-            }
+fn can_spawn_on_demand(state: &GameState) -> bool {
+    allows_spawning_on_demand(state.game_mode)
+}
 
-            new_tower
-        })
-        .collect();
+fn add_stages(schedule: Schedule<GameState>, stages: Vec<Stage<GameState>>) -> Schedule<GameState> {
+    stages.into_iter().fold(schedule, |schedule, stage| {
+        add_stage(schedule, stage)
+            .map_err(|(_, error)| error)
+            .expect("The default stages are well formed")
+    })
+}
 
-    next_state
+pub fn default_schedules() -> Schedules {
+    Schedules {
+        between_rounds: add_stages(
+            new_schedule(),
+            vec![
+                stage("handle_game_speed", handle_game_speed),
+                stage("handle_tower_placement", handle_tower_placement),
+                stage("handle_tower_upgrade", handle_tower_upgrade),
+                stage("handle_tower_selling", handle_tower_selling),
+                stage("handle_undo", handle_undo),
+                stage("update_economy", update_economy),
+                stage("update_statistics", update_statistics),
+            ],
+        ),
+        simulation: add_stages(
+            new_schedule(),
+            vec![
+                stage("handle_spawn_timer", handle_spawn_timer),
+                run_if(
+                    stage("handle_balloon_spawning", handle_balloon_spawning),
+                    can_spawn_on_demand,
+                ),
+                stage("handle_tower_placement", handle_tower_placement),
+                stage("handle_tower_upgrade", handle_tower_upgrade),
+                stage("handle_tower_selling", handle_tower_selling),
+                stage("update_balloons", update_balloons),
+                stage("update_towers", update_towers),
+                stage("handle_popping", handle_popping),
+                stage("clean_projectiles", clean_projectiles),
+                stage("clear_balloons", clear_balloons),
+                stage("update_economy", update_economy),
+                stage("update_statistics", update_statistics),
+                run_if(stage("handle_rounds", handle_rounds), is_playing),
+            ],
+        ),
+    }
 }
 
-fn update_stateful(
-    state: GameState,
+pub fn update_scene_with_schedules(
+    schedules: &Schedules,
     delta_time: f32,
     keys: Keys,
-    next_fn: fn(GameState) -> GameState,
+    state: GameState,
 ) -> GameState {
-    next_fn(GameState {
+    let next_fn: fn(&Schedules, GameState) -> GameState = match state.screen {
+        Screen::MainMenu => update_main_menu,
+        Screen::MapSelect => update_map_select,
+        Screen::DifficultySelect => update_difficulty_select,
+        Screen::GameModeSelect => update_game_mode_select,
+        Screen::BetweenRounds => update_between_rounds,
+        Screen::Playing => update_playing,
+        Screen::Paused => update_paused,
+        Screen::Victory => update_victory,
+        Screen::Defeat => update_defeat,
+    };
+
+    update_stateful(schedules, state, delta_time, keys, next_fn)
+}
+
+pub fn update_scene(delta_time: f32, keys: Keys, state: GameState) -> GameState {
+    update_scene_with_schedules(
+        DEFAULT_SCHEDULES.get_or_init(default_schedules),
         delta_time,
         keys,
-        ..state
-    })
+        state,
+    )
 }
 
-pub fn pipe(actions: Vec<fn(GameState) -> GameState>, initial_state: GameState) -> GameState {
-    actions
-        .into_iter()
-        .fold(initial_state, |state, action| action(state))
+pub fn draw_scene(state: &GameState, sprites: &Sprites, alpha: f32, view: &View) {
+    let draw_fn: fn(&GameState, &Sprites, f32, &View) = match state.screen {
+        Screen::MainMenu => draw_main_menu,
+        Screen::MapSelect => draw_map_select,
+        Screen::DifficultySelect => draw_difficulty_select,
+        Screen::GameModeSelect => draw_game_mode_select,
+        Screen::BetweenRounds => draw_between_rounds,
+        Screen::Playing => draw_playing,
+        Screen::Paused => draw_paused,
+        Screen::Victory => draw_victory,
+        Screen::Defeat => draw_defeat,
+    };
+
+    draw_fn(state, sprites, alpha, view)
 }
 
-pub fn update_scene(delta_time: f32, keys: Keys, state: GameState) -> GameState {
-    match state.game_over {
-        true => update_stateful(state, delta_time, keys, draw_game_over),
-        false => update_stateful(state, delta_time, keys, |state| {
-            pipe(
-                vec![
-                    draw_background,
-                    handle_spawn_timer,
-                    handle_tower_placement,
-                    update_balloons,
-                    update_towers,
-                    draw_balloons,
-                    handle_popping,
-                    clean_projectiles,
-                    clear_balloons,
-                    draw_statistics,
-                ],
-                state,
-            )
-        }),
+impl GameSimulation for GameState {
+    type Sprites = Sprites;
+
+    fn step(self, delta_time: f32, keys: Keys) -> Self {
+        update_scene(delta_time, keys, self)
+    }
+
+    fn render(&self, sprites: &Sprites, alpha: f32, view: &View) {
+        draw_scene(self, sprites, alpha, view);
+    }
+
+    fn snapshot(&self) -> GameSnapshot {
+        game_snapshot(self)
+    }
+
+    fn events(&self) -> &[GameEvent] {
+        step_events(self)
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        if !can_save_game(self) {
+            return Err(invalid_data("Games can only be saved while playing"));
+        }
+
+        save_game(path, self)
+    }
+
+    fn load(path: &str) -> Result<Self, Error> {
+        load_game(path)
     }
 }
@@ -0,0 +1,202 @@
+// A schedule is a list of named stages that each take the state and return
+// the next one. Stages are run in the order they were added, except where a
+// stage asks to run before or after another one, and a stage whose run
+// conditions do not all hold simply passes the state along.
+
+use std::fmt;
+
+pub type RunCondition<T> = fn(&T) -> bool;
+
+pub struct Stage<T> {
+    name: &'static str,
+    run: fn(T) -> T,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+    run_conditions: Vec<RunCondition<T>>,
+}
+
+pub struct Schedule<T> {
+    stages: Vec<Stage<T>>,
+    order: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScheduleError {
+    DuplicateStage(&'static str),
+    // The stages that could not be ordered, in the order they were added.
+    Cycle(Vec<&'static str>),
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScheduleError::DuplicateStage(name) => {
+                write!(formatter, "Stage {} is already in the schedule", name)
+            }
+            ScheduleError::Cycle(names) => {
+                write!(formatter, "Stages {} form a cycle", names.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+pub fn stage<T>(name: &'static str, run: fn(T) -> T) -> Stage<T> {
+    Stage {
+        name,
+        run,
+        before: Vec::new(),
+        after: Vec::new(),
+        run_conditions: Vec::new(),
+    }
+}
+
+// Naming a stage that is not in the schedule is allowed, so optional stages
+// can be referred to without checking whether they were added.
+pub fn before<T>(stage: Stage<T>, name: &'static str) -> Stage<T> {
+    let mut before = stage.before;
+
+    before.push(name);
+
+    Stage { before, ..stage }
+}
+
+pub fn after<T>(stage: Stage<T>, name: &'static str) -> Stage<T> {
+    let mut after = stage.after;
+
+    after.push(name);
+
+    Stage { after, ..stage }
+}
+
+pub fn run_if<T>(stage: Stage<T>, condition: RunCondition<T>) -> Stage<T> {
+    let mut run_conditions = stage.run_conditions;
+
+    run_conditions.push(condition);
+
+    Stage {
+        run_conditions,
+        ..stage
+    }
+}
+
+pub fn new_schedule<T>() -> Schedule<T> {
+    Schedule {
+        stages: Vec::new(),
+        order: Vec::new(),
+    }
+}
+
+fn stage_index<T>(stages: &[Stage<T>], name: &str) -> Option<usize> {
+    stages.iter().position(|stage| stage.name == name)
+}
+
+// Orders the stages so that every constraint holds, picking the earliest
+// added stage whenever several are free to run next.
+fn sort_stages<T>(stages: &[Stage<T>]) -> Result<Vec<usize>, ScheduleError> {
+    let mut successors = vec![Vec::new(); stages.len()];
+    let mut predecessor_counts = vec![0; stages.len()];
+
+    let mut add_edge = |from: usize, to: usize| {
+        successors[from].push(to);
+        predecessor_counts[to] += 1;
+    };
+
+    stages.iter().enumerate().for_each(|(index, stage)| {
+        stage
+            .before
+            .iter()
+            .filter_map(|name| stage_index(stages, name))
+            .for_each(|other| add_edge(index, other));
+
+        stage
+            .after
+            .iter()
+            .filter_map(|name| stage_index(stages, name))
+            .for_each(|other| add_edge(other, index));
+    });
+
+    let mut order = Vec::with_capacity(stages.len());
+    let mut is_ordered = vec![false; stages.len()];
+
+    while let Some(next) =
+        (0..stages.len()).find(|index| !is_ordered[*index] && predecessor_counts[*index] == 0)
+    {
+        is_ordered[next] = true;
+        order.push(next);
+
+        successors[next]
+            .iter()
+            .for_each(|successor| predecessor_counts[*successor] -= 1);
+    }
+
+    if order.len() < stages.len() {
+        return Err(ScheduleError::Cycle(
+            stages
+                .iter()
+                .zip(is_ordered)
+                .filter(|(_, is_ordered)| !is_ordered)
+                .map(|(stage, _)| stage.name)
+                .collect(),
+        ));
+    }
+
+    Ok(order)
+}
+
+// A rejected stage is dropped and the schedule is handed back unchanged with
+// the error, so it can still be used.
+pub fn add_stage<T>(
+    schedule: Schedule<T>,
+    stage: Stage<T>,
+) -> Result<Schedule<T>, (Schedule<T>, ScheduleError)> {
+    if stage_index(&schedule.stages, stage.name).is_some() {
+        return Err((schedule, ScheduleError::DuplicateStage(stage.name)));
+    }
+
+    let mut stages = schedule.stages;
+
+    stages.push(stage);
+
+    match sort_stages(&stages) {
+        Ok(order) => Ok(Schedule { stages, order }),
+        Err(error) => {
+            stages.pop();
+
+            Err((
+                Schedule {
+                    stages,
+                    order: schedule.order,
+                },
+                error,
+            ))
+        }
+    }
+}
+
+pub fn stage_names<T>(schedule: &Schedule<T>) -> Vec<&'static str> {
+    schedule
+        .order
+        .iter()
+        .map(|index| schedule.stages[*index].name)
+        .collect()
+}
+
+fn run_stage<T>(stage: &Stage<T>, state: T) -> T {
+    if stage
+        .run_conditions
+        .iter()
+        .all(|condition| condition(&state))
+    {
+        return (stage.run)(state);
+    }
+
+    state
+}
+
+pub fn run_schedule<T>(schedule: &Schedule<T>, initial_state: T) -> T {
+    schedule.order.iter().fold(initial_state, |state, index| {
+        run_stage(&schedule.stages[*index], state)
+    })
+}
@@ -0,0 +1,43 @@
+// Texture handles live here rather than in GameState, so the logical state
+// stays plain data that can be written to disk and restored.
+
+use macroquad::prelude::*;
+
+use crate::atlas::load_sprite_sheet;
+use crate::atlas::HasSpriteSheet;
+use crate::atlas::SpriteSheet;
+use crate::functional::map::MAPS;
+
+pub struct Sprites {
+    pub sheet: SpriteSheet,
+    pub maps: Vec<Texture2D>,
+}
+
+impl HasSpriteSheet for Sprites {
+    fn sprite_sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+}
+
+async fn load_sprite(path: &str) -> Texture2D {
+    let sprite = load_texture(path)
+        .await
+        .unwrap_or_else(|_| panic!("Failed to load sprite {}", path));
+
+    sprite.set_filter(FilterMode::Nearest);
+
+    sprite
+}
+
+pub async fn load_sprites() -> Sprites {
+    let mut maps = Vec::new();
+
+    for map in MAPS.iter() {
+        maps.push(load_sprite(map.background_path).await);
+    }
+
+    Sprites {
+        sheet: load_sprite_sheet().await,
+        maps,
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::Error;
+
+use crate::events::GameEvent;
+
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+
+#[derive(Copy, Clone)]
+pub struct Statistics {
+    pub balloons_popped: u32,
+    pub balloons_escaped: u32,
+    pub towers_placed: u32,
+    pub towers_upgraded: u32,
+    pub towers_sold: u32,
+}
+
+pub const INITIAL_STATISTICS: Statistics = Statistics {
+    balloons_popped: 0,
+    balloons_escaped: 0,
+    towers_placed: 0,
+    towers_upgraded: 0,
+    towers_sold: 0,
+};
+
+pub fn record_event(statistics: Statistics, event: &GameEvent) -> Statistics {
+    match event {
+        GameEvent::BalloonPopped { .. } => Statistics {
+            balloons_popped: statistics.balloons_popped + 1,
+            ..statistics
+        },
+        GameEvent::BalloonEscaped { .. } => Statistics {
+            balloons_escaped: statistics.balloons_escaped + 1,
+            ..statistics
+        },
+        GameEvent::TowerPlaced { .. } => Statistics {
+            towers_placed: statistics.towers_placed + 1,
+            ..statistics
+        },
+        GameEvent::TowerUpgraded { .. } => Statistics {
+            towers_upgraded: statistics.towers_upgraded + 1,
+            ..statistics
+        },
+        GameEvent::TowerSold { .. } => Statistics {
+            towers_sold: statistics.towers_sold + 1,
+            ..statistics
+        },
+        GameEvent::TowerPlacementUndone { .. } => Statistics {
+            towers_placed: statistics.towers_placed.saturating_sub(1),
+            ..statistics
+        },
+        GameEvent::TowerUpgradeUndone { .. } => Statistics {
+            towers_upgraded: statistics.towers_upgraded.saturating_sub(1),
+            ..statistics
+        },
+        GameEvent::RoundStarted { .. } => statistics,
+    }
+}
+
+pub fn encode_statistics(statistics: &Statistics, writer: &mut SaveWriter) {
+    writer.write_u32(statistics.balloons_popped);
+    writer.write_u32(statistics.balloons_escaped);
+    writer.write_u32(statistics.towers_placed);
+    writer.write_u32(statistics.towers_upgraded);
+    writer.write_u32(statistics.towers_sold);
+}
+
+pub fn decode_statistics(reader: &mut SaveReader) -> Result<Statistics, Error> {
+    Ok(Statistics {
+        balloons_popped: reader.read_u32()?,
+        balloons_escaped: reader.read_u32()?,
+        towers_placed: reader.read_u32()?,
+        towers_upgraded: reader.read_u32()?,
+        towers_sold: reader.read_u32()?,
+    })
+}
@@ -1,7 +1,24 @@
+use std::io::Error;
+
 use macroquad::prelude::*;
 
+use crate::atlas::draw_sprite;
+use crate::atlas::is_action_playing;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::functional::projectile::decode_projectile;
+use crate::functional::projectile::encode_projectile;
 use crate::functional::projectile::new_projectile;
+use crate::functional::projectile::projectile_position;
 use crate::functional::projectile::Projectile;
+use crate::functional::projectile::PROJECTILE_SIZE;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+use crate::guides::TowerGuides;
+use crate::snapshot::PreviewTowerSnapshot;
+use crate::snapshot::TowerSnapshot;
+use crate::world::distance_to_world_edge;
 
 #[derive(Clone)]
 pub struct Tower {
@@ -11,9 +28,11 @@ pub struct Tower {
     pub projectiles: Vec<Projectile>,
     pop_count: u32,
     level: u32,
+    spent: u32,
 }
 
-const TOWER_SIZE: f32 = 50.;
+pub const TOWER_SIZE: f32 = 50.;
+pub const MAX_TOWER_LEVEL: u32 = 5;
 
 pub fn new_tower(position: Vec2) -> Tower {
     Tower {
@@ -23,32 +42,26 @@ pub fn new_tower(position: Vec2) -> Tower {
         projectiles: Vec::new(),
         pop_count: 0,
         level: 1,
+        spent: 0,
     }
 }
 
 pub fn update_tower(tower: Tower, delta_time: f32) -> Tower {
     let new_shot_cooldown = tower.shot_cooldown - delta_time;
 
-    let (new_shot_cooldown, new_projectiles) = if new_shot_cooldown < 0. {
-        let new_projectile = new_projectile(
+    let mut new_projectiles = tower.projectiles;
+
+    let new_shot_cooldown = if new_shot_cooldown < 0. {
+        new_projectiles.push(new_projectile(
             Vec2::new(tower.position.x, tower.position.y),
             Vec2::new(tower.angle.cos(), tower.angle.sin()),
-        );
+        ));
 
-        (
-            new_shot_cooldown + 2. / tower.level as f32,
-            vec![new_projectile],
-        )
+        new_shot_cooldown + 2. / tower.level as f32
     } else {
-        (new_shot_cooldown, vec![])
+        new_shot_cooldown
     };
 
-    let new_projectiles = tower
-        .projectiles
-        .into_iter()
-        .chain(new_projectiles.into_iter())
-        .collect();
-
     Tower {
         shot_cooldown: new_shot_cooldown,
         projectiles: new_projectiles,
@@ -59,7 +72,7 @@ pub fn update_tower(tower: Tower, delta_time: f32) -> Tower {
 pub fn increase_tower_pop_count(tower: Tower, pop_count: u32) -> Tower {
     let new_pop_count = tower.pop_count + pop_count;
 
-    let new_level = if new_pop_count % 10 == 0 {
+    let new_level = if new_pop_count.is_multiple_of(10) {
         tower.level + 1
     } else {
         tower.level
@@ -72,7 +85,67 @@ pub fn increase_tower_pop_count(tower: Tower, pop_count: u32) -> Tower {
     }
 }
 
-pub fn draw_tower(tower: Tower, is_disabled: bool) {
+pub fn is_point_on_tower(tower: &Tower, point: Vec2) -> bool {
+    tower.position.distance(point) <= TOWER_SIZE
+}
+
+// Footprints are circles, so they overlap when closer than two radii.
+pub fn overlaps_tower(tower: &Tower, position: Vec2) -> bool {
+    tower.position.distance(position) < TOWER_SIZE * 2.
+}
+
+pub fn can_upgrade_tower(tower: &Tower) -> bool {
+    tower.level < MAX_TOWER_LEVEL
+}
+
+pub fn upgrade_tower(tower: Tower) -> Tower {
+    Tower {
+        level: tower.level + 1,
+        ..tower
+    }
+}
+
+// Reverses an upgrade that cost the given amount.
+pub fn downgrade_tower(tower: Tower, cost: u32) -> Tower {
+    Tower {
+        level: tower.level.saturating_sub(1).max(1),
+        spent: tower.spent.saturating_sub(cost),
+        ..tower
+    }
+}
+
+pub fn add_tower_spent(tower: Tower, amount: u32) -> Tower {
+    Tower {
+        spent: tower.spent + amount,
+        ..tower
+    }
+}
+
+pub fn tower_sell_value(tower: &Tower) -> u32 {
+    tower.spent * 7 / 10
+}
+
+pub fn tower_snapshot(tower: &Tower) -> TowerSnapshot {
+    TowerSnapshot {
+        position: tower.position,
+        angle: tower.angle,
+        level: tower.level,
+        pop_count: tower.pop_count,
+        spent: tower.spent,
+        projectiles: tower.projectiles.iter().map(projectile_position).collect(),
+    }
+}
+
+pub fn preview_tower_snapshot(tower: &Tower) -> PreviewTowerSnapshot {
+    PreviewTowerSnapshot {
+        position: tower.position,
+        angle: tower.angle,
+    }
+}
+
+// The art is a quarter as wide as its barrel is long, reaching twice the
+// tower's size from its middle. Towers play their attack after every shot.
+pub fn draw_tower(tower: Tower, sheet: &SpriteSheet, is_disabled: bool) {
     let color = if is_disabled {
         GRAY
     } else {
@@ -86,13 +159,76 @@ pub fn draw_tower(tower: Tower, is_disabled: bool) {
         }
     };
 
-    draw_circle(tower.position.x, tower.position.y, TOWER_SIZE, color);
-    draw_line(
-        tower.position.x,
-        tower.position.y,
-        tower.position.x + tower.angle.cos() * TOWER_SIZE * 2.,
-        tower.position.y + tower.angle.sin() * TOWER_SIZE * 2.,
-        2.,
+    let time_since_shot = 2. / tower.level as f32 - tower.shot_cooldown;
+
+    let action = if is_action_playing(
+        sheet,
+        SpriteKind::Tower,
+        SpriteAction::Attack,
+        time_since_shot,
+    ) {
+        SpriteAction::Attack
+    } else {
+        SpriteAction::Idle
+    };
+
+    draw_sprite(
+        sheet,
+        SpriteKind::Tower,
+        action,
+        time_since_shot,
+        tower.position,
+        TOWER_SIZE * 4.,
+        tower.angle,
         color,
     );
 }
+
+// Darts fly until they leave the world, so a tower reaches as far as the edge
+// of the world in the direction it aims.
+pub fn tower_guides(tower: &Tower) -> TowerGuides {
+    TowerGuides {
+        position: tower.position,
+        angle: tower.angle,
+        footprint: TOWER_SIZE,
+        range: distance_to_world_edge(tower.position, Vec2::from_angle(tower.angle)),
+        lane_width: PROJECTILE_SIZE * 2.,
+    }
+}
+
+pub fn encode_tower(tower: &Tower, writer: &mut SaveWriter) {
+    writer.write_vec2(tower.position);
+    writer.write_f32(tower.angle);
+    writer.write_f32(tower.shot_cooldown);
+    writer.write_u32(tower.pop_count);
+    writer.write_u32(tower.level);
+    writer.write_u32(tower.spent);
+    writer.write_u32(tower.projectiles.len() as u32);
+
+    tower
+        .projectiles
+        .iter()
+        .for_each(|projectile| encode_projectile(projectile, writer));
+}
+
+pub fn decode_tower(reader: &mut SaveReader) -> Result<Tower, Error> {
+    let position = reader.read_vec2()?;
+    let angle = reader.read_f32()?;
+    let shot_cooldown = reader.read_f32()?;
+    let pop_count = reader.read_u32()?;
+    let level = reader.read_u32()?;
+    let spent = reader.read_u32()?;
+    let projectiles_count = reader.read_u32()?;
+
+    Ok(Tower {
+        position,
+        angle,
+        shot_cooldown,
+        projectiles: (0..projectiles_count)
+            .map(|_| decode_projectile(reader))
+            .collect::<Result<Vec<Projectile>, Error>>()?,
+        pop_count,
+        level,
+        spent,
+    })
+}
@@ -0,0 +1,220 @@
+// Gamepads point with a virtual cursor moved by the left stick, which stands
+// in for the mouse until the mouse is moved again. Macroquad does not read
+// gamepads itself, so a backend hands over a snapshot of the pad every frame.
+// The gilrs backend is behind the `gamepad` feature, as it needs libudev on
+// Linux.
+
+use macroquad::prelude::*;
+
+#[cfg(feature = "gamepad")]
+use gilrs::Axis;
+#[cfg(feature = "gamepad")]
+use gilrs::Button;
+#[cfg(feature = "gamepad")]
+use gilrs::Gilrs;
+
+#[cfg(feature = "gamepad")]
+use crate::bindings::GamepadButton;
+use crate::bindings::GamepadButtons;
+#[cfg(feature = "gamepad")]
+use crate::bindings::GAMEPAD_BUTTONS;
+
+// Stick deflections below this are treated as the stick resting.
+const STICK_DEAD_ZONE: f32 = 0.2;
+// Pixels per second at full deflection.
+const CURSOR_SPEED: f32 = 600.;
+
+#[derive(Clone, Default)]
+pub struct Gamepad {
+    pub buttons: GamepadButtons,
+    // Each axis ranges from -1 to 1, with positive y pointing down.
+    pub left_stick: Vec2,
+}
+
+pub trait GamepadBackend {
+    fn poll(&mut self) -> Gamepad;
+}
+
+// Used when no backend is available, so gamepad bindings never fire.
+pub struct NoGamepad;
+
+impl GamepadBackend for NoGamepad {
+    fn poll(&mut self) -> Gamepad {
+        Gamepad::default()
+    }
+}
+
+// Reads the first connected pad. If gilrs fails to start, it behaves like
+// NoGamepad rather than keeping the game from starting.
+#[cfg(feature = "gamepad")]
+pub struct GilrsGamepad {
+    gilrs: Option<Gilrs>,
+    previous_down: Vec<GamepadButton>,
+}
+
+#[cfg(feature = "gamepad")]
+impl GilrsGamepad {
+    pub fn new() -> Self {
+        let gilrs = Gilrs::new()
+            .map_err(|error| eprintln!("Failed to read gamepads: {}", error))
+            .ok();
+
+        GilrsGamepad {
+            gilrs,
+            previous_down: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl Default for GilrsGamepad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Gilrs calls the shoulder buttons triggers and the triggers second triggers.
+#[cfg(feature = "gamepad")]
+fn gilrs_button(button: GamepadButton) -> Button {
+    match button {
+        GamepadButton::South => Button::South,
+        GamepadButton::East => Button::East,
+        GamepadButton::West => Button::West,
+        GamepadButton::North => Button::North,
+        GamepadButton::LeftShoulder => Button::LeftTrigger,
+        GamepadButton::RightShoulder => Button::RightTrigger,
+        GamepadButton::LeftTrigger => Button::LeftTrigger2,
+        GamepadButton::RightTrigger => Button::RightTrigger2,
+        GamepadButton::Select => Button::Select,
+        GamepadButton::Start => Button::Start,
+        GamepadButton::DPadUp => Button::DPadUp,
+        GamepadButton::DPadDown => Button::DPadDown,
+        GamepadButton::DPadLeft => Button::DPadLeft,
+        GamepadButton::DPadRight => Button::DPadRight,
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadBackend for GilrsGamepad {
+    fn poll(&mut self) -> Gamepad {
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return Gamepad::default();
+        };
+
+        // Gilrs only updates the state of the pads while its events are read.
+        while gilrs.next_event().is_some() {}
+
+        let (down, left_stick) =
+            gilrs
+                .gamepads()
+                .next()
+                .map_or((Vec::new(), Vec2::ZERO), |(_, pad)| {
+                    let down = GAMEPAD_BUTTONS
+                        .iter()
+                        .copied()
+                        .filter(|button| pad.is_pressed(gilrs_button(*button)))
+                        .collect();
+                    // Gilrs points y up.
+                    let left_stick =
+                        Vec2::new(pad.value(Axis::LeftStickX), -pad.value(Axis::LeftStickY));
+
+                    (down, left_stick)
+                });
+
+        let previous_down = std::mem::replace(&mut self.previous_down, down.clone());
+
+        Gamepad {
+            buttons: GamepadButtons {
+                down,
+                previous_down,
+            },
+            left_stick,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct VirtualCursor {
+    pub position: Vec2,
+    pub is_active: bool,
+}
+
+pub fn new_virtual_cursor(position: Vec2) -> VirtualCursor {
+    VirtualCursor {
+        position,
+        is_active: false,
+    }
+}
+
+fn apply_dead_zone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+
+    if length < STICK_DEAD_ZONE {
+        return Vec2::ZERO;
+    }
+
+    // Rescales so the cursor starts moving slowly right past the dead zone
+    // instead of jumping to the dead zone speed.
+    stick / length * ((length.min(1.) - STICK_DEAD_ZONE) / (1. - STICK_DEAD_ZONE))
+}
+
+// Moving the stick takes the cursor over from the mouse, and moving the mouse
+// hands it back, starting from wherever the mouse is.
+pub fn update_virtual_cursor(
+    cursor: VirtualCursor,
+    stick: Vec2,
+    mouse_position: Vec2,
+    has_mouse_moved: bool,
+    bounds: Vec2,
+    delta_time: f32,
+) -> VirtualCursor {
+    let stick = apply_dead_zone(stick);
+
+    if stick != Vec2::ZERO {
+        let start = if cursor.is_active {
+            cursor.position
+        } else {
+            mouse_position
+        };
+
+        return VirtualCursor {
+            position: (start + stick * CURSOR_SPEED * delta_time).clamp(Vec2::ZERO, bounds),
+            is_active: true,
+        };
+    }
+
+    if has_mouse_moved || !cursor.is_active {
+        return VirtualCursor {
+            position: mouse_position,
+            is_active: false,
+        };
+    }
+
+    cursor
+}
+
+pub fn draw_virtual_cursor(cursor: &VirtualCursor) {
+    if !cursor.is_active {
+        return;
+    }
+
+    let VirtualCursor { position, .. } = *cursor;
+
+    draw_circle_lines(position.x, position.y, 10., 2., WHITE);
+    draw_line(
+        position.x - 15.,
+        position.y,
+        position.x + 15.,
+        position.y,
+        2.,
+        WHITE,
+    );
+    draw_line(
+        position.x,
+        position.y - 15.,
+        position.x,
+        position.y + 15.,
+        2.,
+        WHITE,
+    );
+}
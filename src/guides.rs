@@ -0,0 +1,59 @@
+// Guides drawn over towers: the range of a tower being pointed at, and while
+// placing one, its range, the lane its darts fly down and its footprint,
+// tinted by whether it can be placed there. Each implementation passes in its
+// own tower dimensions.
+
+use macroquad::prelude::*;
+
+const RANGE_FILL: Color = Color::new(1., 1., 1., 0.1);
+const RANGE_OUTLINE: Color = Color::new(1., 1., 1., 0.6);
+const VALID_TINT: Color = Color::new(0., 1., 0., 0.2);
+const INVALID_TINT: Color = Color::new(1., 0., 0., 0.2);
+
+pub struct TowerGuides {
+    pub position: Vec2,
+    pub angle: f32,
+    pub footprint: f32,
+    pub range: f32,
+    // How wide the lane swept by the tower's darts is.
+    pub lane_width: f32,
+}
+
+pub fn draw_range(position: Vec2, range: f32) {
+    draw_circle(position.x, position.y, range, RANGE_FILL);
+    draw_circle_lines(position.x, position.y, range, 2., RANGE_OUTLINE);
+}
+
+fn draw_firing_lane(guides: &TowerGuides, color: Color) {
+    let direction = Vec2::from_angle(guides.angle);
+    let side = direction.perp() * guides.lane_width / 2.;
+    let start = guides.position;
+    let end = guides.position + direction * guides.range;
+
+    draw_triangle(start + side, start - side, end + side, color);
+    draw_triangle(start - side, end - side, end + side, color);
+}
+
+pub fn draw_placement_guides(guides: &TowerGuides, is_valid: bool) {
+    let tint = if is_valid { VALID_TINT } else { INVALID_TINT };
+    let outline = Color { a: 1., ..tint };
+
+    draw_circle(guides.position.x, guides.position.y, guides.range, tint);
+    draw_circle_lines(
+        guides.position.x,
+        guides.position.y,
+        guides.range,
+        2.,
+        outline,
+    );
+
+    draw_firing_lane(guides, tint);
+
+    draw_circle_lines(
+        guides.position.x,
+        guides.position.y,
+        guides.footprint,
+        3.,
+        outline,
+    );
+}
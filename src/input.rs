@@ -0,0 +1,211 @@
+// Input for one tick of the simulation, gathered by the front end so that
+// both implementations can be driven by the same keys, replays and tests.
+// Menu selections are option indices, as each implementation has its own
+// difficulty and game mode types.
+
+use macroquad::prelude::*;
+
+// How far the pointer has to move while a button is held before it counts as
+// a drag rather than a click.
+pub const DRAG_THRESHOLD: f32 = 10.;
+
+// Whether an action went down or up during the tick, and whether it is held
+// at the end of it. A press and a release can both happen in one tick.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ButtonState {
+    pub pressed: bool,
+    pub held: bool,
+    pub released: bool,
+}
+
+pub const IDLE: ButtonState = ButtonState {
+    pressed: false,
+    held: false,
+    released: false,
+};
+
+pub const PRESSED: ButtonState = ButtonState {
+    pressed: true,
+    held: true,
+    released: false,
+};
+
+pub const HELD: ButtonState = ButtonState {
+    pressed: false,
+    held: true,
+    released: false,
+};
+
+pub const RELEASED: ButtonState = ButtonState {
+    pressed: false,
+    held: false,
+    released: true,
+};
+
+// Pressed and released within the same tick.
+pub const CLICKED: ButtonState = ButtonState {
+    pressed: true,
+    held: false,
+    released: true,
+};
+
+pub fn is_drag(start: Vec2, pointer_position: Vec2) -> bool {
+    start.distance(pointer_position) > DRAG_THRESHOLD
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keys {
+    pub tower_placement: ButtonState,
+    pub cancel_tower_placement: ButtonState,
+    pub place_tower: ButtonState,
+    pub rotate_tower_clockwise: ButtonState,
+    pub rotate_tower_counter_clockwise: ButtonState,
+    pub upgrade_tower: ButtonState,
+    pub sell_tower: ButtonState,
+    pub start_round: ButtonState,
+    pub spawn_balloon: ButtonState,
+    pub pause: ButtonState,
+    pub step_frame: ButtonState,
+    pub change_game_speed: ButtonState,
+    pub confirm: ButtonState,
+    pub continue_game: ButtonState,
+    pub nudge_left: ButtonState,
+    pub nudge_right: ButtonState,
+    pub nudge_up: ButtonState,
+    pub nudge_down: ButtonState,
+    pub undo: ButtonState,
+    pub select_map: Option<usize>,
+    pub select_difficulty: Option<usize>,
+    pub select_game_mode: Option<usize>,
+    // The shop item stepped to with the shoulder buttons, which starts placing
+    // it like clicking its button does.
+    pub select_shop_item: Option<usize>,
+    // Where the pointer is in the world, as seen through the zoomed view.
+    pub pointer_position: Vec2,
+    // Where the pointer is over the interface, which is drawn unzoomed. The
+    // sidebar is hit-tested against this one only.
+    pub interface_pointer_position: Vec2,
+    // How far the pointer moved since the previous tick.
+    pub pointer_delta: Vec2,
+    // The size of the grid towers snap to, when snapping is on.
+    pub grid_size: Option<f32>,
+}
+
+pub const NO_KEYS: Keys = Keys {
+    tower_placement: IDLE,
+    cancel_tower_placement: IDLE,
+    place_tower: IDLE,
+    rotate_tower_clockwise: IDLE,
+    rotate_tower_counter_clockwise: IDLE,
+    upgrade_tower: IDLE,
+    sell_tower: IDLE,
+    start_round: IDLE,
+    spawn_balloon: IDLE,
+    pause: IDLE,
+    step_frame: IDLE,
+    change_game_speed: IDLE,
+    confirm: IDLE,
+    continue_game: IDLE,
+    nudge_left: IDLE,
+    nudge_right: IDLE,
+    nudge_up: IDLE,
+    nudge_down: IDLE,
+    undo: IDLE,
+    select_map: None,
+    select_difficulty: None,
+    select_game_mode: None,
+    select_shop_item: None,
+    pointer_position: Vec2::ZERO,
+    interface_pointer_position: Vec2::ZERO,
+    pointer_delta: Vec2::ZERO,
+    grid_size: None,
+};
+
+fn merge_button_state(state: ButtonState, later: ButtonState) -> ButtonState {
+    ButtonState {
+        pressed: state.pressed || later.pressed,
+        held: later.held,
+        released: state.released || later.released,
+    }
+}
+
+// Presses and releases that happen on frames where no tick runs are kept until
+// the next tick, so they are never dropped when rendering faster than the tick
+// rate.
+pub fn merge_keys(keys: Keys, other: Keys) -> Keys {
+    Keys {
+        tower_placement: merge_button_state(keys.tower_placement, other.tower_placement),
+        cancel_tower_placement: merge_button_state(
+            keys.cancel_tower_placement,
+            other.cancel_tower_placement,
+        ),
+        place_tower: merge_button_state(keys.place_tower, other.place_tower),
+        rotate_tower_clockwise: merge_button_state(
+            keys.rotate_tower_clockwise,
+            other.rotate_tower_clockwise,
+        ),
+        rotate_tower_counter_clockwise: merge_button_state(
+            keys.rotate_tower_counter_clockwise,
+            other.rotate_tower_counter_clockwise,
+        ),
+        upgrade_tower: merge_button_state(keys.upgrade_tower, other.upgrade_tower),
+        sell_tower: merge_button_state(keys.sell_tower, other.sell_tower),
+        start_round: merge_button_state(keys.start_round, other.start_round),
+        spawn_balloon: merge_button_state(keys.spawn_balloon, other.spawn_balloon),
+        pause: merge_button_state(keys.pause, other.pause),
+        step_frame: merge_button_state(keys.step_frame, other.step_frame),
+        change_game_speed: merge_button_state(keys.change_game_speed, other.change_game_speed),
+        confirm: merge_button_state(keys.confirm, other.confirm),
+        continue_game: merge_button_state(keys.continue_game, other.continue_game),
+        nudge_left: merge_button_state(keys.nudge_left, other.nudge_left),
+        nudge_right: merge_button_state(keys.nudge_right, other.nudge_right),
+        nudge_up: merge_button_state(keys.nudge_up, other.nudge_up),
+        nudge_down: merge_button_state(keys.nudge_down, other.nudge_down),
+        undo: merge_button_state(keys.undo, other.undo),
+        select_map: keys.select_map.or(other.select_map),
+        select_difficulty: keys.select_difficulty.or(other.select_difficulty),
+        select_game_mode: keys.select_game_mode.or(other.select_game_mode),
+        select_shop_item: keys.select_shop_item.or(other.select_shop_item),
+        pointer_position: other.pointer_position,
+        interface_pointer_position: other.interface_pointer_position,
+        pointer_delta: keys.pointer_delta + other.pointer_delta,
+        grid_size: other.grid_size,
+    }
+}
+
+fn still_held(state: ButtonState) -> ButtonState {
+    ButtonState {
+        held: state.held,
+        ..IDLE
+    }
+}
+
+// Only the held inputs carry over to the extra ticks of a slow frame,
+// otherwise a single press would be applied once per tick.
+pub fn held_keys(keys: &Keys) -> Keys {
+    Keys {
+        tower_placement: still_held(keys.tower_placement),
+        cancel_tower_placement: still_held(keys.cancel_tower_placement),
+        place_tower: still_held(keys.place_tower),
+        rotate_tower_clockwise: still_held(keys.rotate_tower_clockwise),
+        rotate_tower_counter_clockwise: still_held(keys.rotate_tower_counter_clockwise),
+        upgrade_tower: still_held(keys.upgrade_tower),
+        sell_tower: still_held(keys.sell_tower),
+        start_round: still_held(keys.start_round),
+        spawn_balloon: still_held(keys.spawn_balloon),
+        pause: still_held(keys.pause),
+        step_frame: still_held(keys.step_frame),
+        change_game_speed: still_held(keys.change_game_speed),
+        confirm: still_held(keys.confirm),
+        continue_game: still_held(keys.continue_game),
+        nudge_left: still_held(keys.nudge_left),
+        nudge_right: still_held(keys.nudge_right),
+        nudge_up: still_held(keys.nudge_up),
+        nudge_down: still_held(keys.nudge_down),
+        undo: still_held(keys.undo),
+        pointer_position: keys.pointer_position,
+        interface_pointer_position: keys.interface_pointer_position,
+        grid_size: keys.grid_size,
+        ..NO_KEYS
+    }
+}
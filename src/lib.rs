@@ -0,0 +1,17 @@
+pub mod atlas;
+pub mod bevy_ecs;
+pub mod bindings;
+pub mod effects;
+pub mod events;
+pub mod functional;
+pub mod gamepad;
+pub mod guides;
+pub mod input;
+pub mod object_oriented;
+pub mod placement;
+pub mod shop;
+pub mod simulation;
+pub mod snapshot;
+pub mod timestep;
+pub mod view;
+pub mod world;
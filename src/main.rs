@@ -1,42 +1,483 @@
 use macroquad::prelude::*;
 
-mod functional;
-mod object_oriented;
+use bloonstd7::atlas::HasSpriteSheet;
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::bindings::action_state;
+use bloonstd7::bindings::default_bindings;
+use bloonstd7::bindings::draw_rebind_menu;
+use bloonstd7::bindings::is_action_down;
+use bloonstd7::bindings::is_action_pressed;
+use bloonstd7::bindings::load_bindings;
+use bloonstd7::bindings::new_rebind_menu;
+use bloonstd7::bindings::pressed_option;
+use bloonstd7::bindings::save_bindings;
+use bloonstd7::bindings::update_rebind_menu;
+use bloonstd7::bindings::Action;
+use bloonstd7::bindings::Bindings;
+use bloonstd7::bindings::GamepadButtons;
+use bloonstd7::effects::draw_effects;
+use bloonstd7::effects::spawn_effect;
+use bloonstd7::effects::update_effects;
+use bloonstd7::effects::Effect;
+use bloonstd7::events::publish;
+use bloonstd7::events::GameEvent;
+use bloonstd7::functional::replay::load_replay;
+use bloonstd7::functional::replay::ReplayWriter;
+use bloonstd7::functional::rewind::current_state;
+use bloonstd7::functional::rewind::draw_rewind_overlay;
+use bloonstd7::functional::rewind::new_history;
+use bloonstd7::functional::rewind::record_state;
+use bloonstd7::functional::rewind::step_back;
+use bloonstd7::functional::rewind::step_forward;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::sprites::load_sprites;
+use bloonstd7::gamepad::draw_virtual_cursor;
+use bloonstd7::gamepad::new_virtual_cursor;
+use bloonstd7::gamepad::update_virtual_cursor;
+use bloonstd7::gamepad::GamepadBackend;
+#[cfg(feature = "gamepad")]
+use bloonstd7::gamepad::GilrsGamepad;
+#[cfg(not(feature = "gamepad"))]
+use bloonstd7::gamepad::NoGamepad;
+use bloonstd7::input::Keys;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::object_oriented::sprites::Sprites;
+use bloonstd7::shop::step_shop_item;
+use bloonstd7::simulation::parse_implementation;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::simulation::Implementation;
+use bloonstd7::timestep::advance_timestep;
+use bloonstd7::timestep::new_timestep;
+use bloonstd7::timestep::timestep_alpha;
+use bloonstd7::timestep::FIXED_DELTA_TIME;
+use bloonstd7::view::clamp_view;
+use bloonstd7::view::draw_letterbox;
+use bloonstd7::view::edge_pan_direction;
+use bloonstd7::view::pan_view;
+use bloonstd7::view::screen_to_interface;
+use bloonstd7::view::screen_to_world;
+use bloonstd7::view::set_interface_camera;
+use bloonstd7::view::set_view_camera;
+use bloonstd7::view::world_bounds;
+use bloonstd7::view::zoom_view;
+use bloonstd7::view::View;
+use bloonstd7::view::DEFAULT_VIEW;
+use bloonstd7::view::EDGE_PAN_SPEED;
+use bloonstd7::view::INTERFACE_HEIGHT;
+use bloonstd7::view::INTERFACE_WIDTH;
 
-use functional::scene::init_scene;
-use functional::scene::new_scene;
-use functional::scene::update_scene;
-use functional::scene::Keys;
+const SAVE_PATH: &str = "savegame.btd";
+const BINDINGS_PATH: &str = "bindings.cfg";
+const DEFAULT_GRID_SIZE: f32 = 25.;
 
-// use object_oriented::scene::Scene;
+fn read_keys(
+    bindings: &Bindings,
+    gamepad: &GamepadButtons,
+    pointer_position: Vec2,
+    previous_pointer_position: Vec2,
+    interface_pointer_position: Vec2,
+    grid_size: Option<f32>,
+    select_shop_item: Option<usize>,
+) -> Keys {
+    let state = |action| action_state(bindings, gamepad, action);
 
-#[macroquad::main("Balloons")]
-async fn main() {
-    let mut scene = new_scene();
-    scene = init_scene(scene).await;
+    Keys {
+        tower_placement: state(Action::TowerPlacement),
+        cancel_tower_placement: state(Action::CancelTowerPlacement),
+        place_tower: state(Action::PlaceTower),
+        rotate_tower_clockwise: state(Action::RotateTowerClockwise),
+        rotate_tower_counter_clockwise: state(Action::RotateTowerCounterClockwise),
+        upgrade_tower: state(Action::UpgradeTower),
+        sell_tower: state(Action::SellTower),
+        start_round: state(Action::StartRound),
+        spawn_balloon: state(Action::SpawnBalloon),
+        pause: state(Action::Pause),
+        step_frame: state(Action::StepFrame),
+        change_game_speed: state(Action::ChangeGameSpeed),
+        confirm: state(Action::Confirm),
+        continue_game: state(Action::ContinueGame),
+        nudge_left: state(Action::NudgeLeft),
+        nudge_right: state(Action::NudgeRight),
+        nudge_up: state(Action::NudgeUp),
+        nudge_down: state(Action::NudgeDown),
+        undo: state(Action::Undo),
+        select_map: pressed_option(bindings, gamepad),
+        select_difficulty: pressed_option(bindings, gamepad),
+        select_game_mode: pressed_option(bindings, gamepad),
+        select_shop_item,
+        pointer_position,
+        interface_pointer_position,
+        pointer_delta: pointer_position - previous_pointer_position,
+        grid_size,
+    }
+}
+
+// Draws the scene and the effects over it into the frame that fits the world
+// to the window, covering the rest of the window afterwards.
+fn render_world<S: GameSimulation>(
+    simulation: &S,
+    sprites: &S::Sprites,
+    alpha: f32,
+    view: &View,
+    effects: &[Effect],
+) where
+    S::Sprites: HasSpriteSheet,
+{
+    set_interface_camera();
+    simulation.render(sprites, alpha, view);
+    set_view_camera(view);
+    draw_effects(effects, sprites.sprite_sheet());
+    set_default_camera();
+    draw_letterbox(Vec2::new(screen_width(), screen_height()));
+}
+
+// Zooms around the pointer with the mouse wheel, pans by dragging or by
+// resting the pointer against the edges of the frame, and keeps the view on
+// the map.
+fn update_view(
+    view: View,
+    bindings: &Bindings,
+    gamepad: &GamepadButtons,
+    cursor_position: Vec2,
+    previous_cursor_position: Vec2,
+) -> View {
+    let screen_size = Vec2::new(screen_width(), screen_height());
+    let wheel = mouse_wheel().1;
+
+    let view = if wheel != 0. {
+        let anchor = screen_to_world(cursor_position, screen_size, &view);
+
+        zoom_view(view, wheel.signum(), anchor)
+    } else {
+        view
+    };
+
+    let view = if is_action_down(bindings, gamepad, Action::PanView) {
+        let drag = screen_to_world(previous_cursor_position, screen_size, &view)
+            - screen_to_world(cursor_position, screen_size, &view);
+
+        pan_view(view, drag)
+    } else {
+        view
+    };
+
+    let edge_pan = edge_pan_direction(cursor_position, screen_size) * EDGE_PAN_SPEED / view.zoom
+        * get_frame_time();
+
+    clamp_view(pan_view(view, edge_pan), world_bounds())
+}
+
+fn argument_value(name: &str) -> Option<String> {
+    let arguments: Vec<String> = std::env::args().collect();
+
+    arguments
+        .iter()
+        .position(|argument| argument == name)
+        .and_then(|index| arguments.get(index + 1).cloned())
+}
+
+fn has_argument(name: &str) -> bool {
+    std::env::args().any(|argument| argument == name)
+}
+
+async fn run<S: GameSimulation + Clone>(
+    mut simulation: S,
+    sprites: S::Sprites,
+    mut replay_ticks: Option<std::vec::IntoIter<Keys>>,
+    mut recorder: Option<ReplayWriter>,
+    mut bindings: Bindings,
+    mut gamepad_backend: impl GamepadBackend,
+) where
+    S::Sprites: HasSpriteSheet,
+{
+    let mut rebind_menu = None;
+
+    let grid_size = argument_value("--grid-size")
+        .map(|value| {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|grid_size| *grid_size > 0.)
+                .expect("Invalid grid size")
+        })
+        .unwrap_or(DEFAULT_GRID_SIZE);
+    let mut is_snapping_to_grid = false;
+    // The shop item the shoulder buttons last stepped to.
+    let mut selected_shop_item = None;
+    let mut cursor = new_virtual_cursor(Vec2::from(mouse_position()));
+    let mut previous_cursor_position = cursor.position;
+    let mut view = DEFAULT_VIEW;
+    // The pointer is kept in world coordinates, so resizing the window or
+    // moving the view does not move it within the world.
+    let mut previous_pointer_position = screen_to_world(
+        cursor.position,
+        Vec2::new(screen_width(), screen_height()),
+        &view,
+    );
+
+    // Resuming from a past state would desynchronise a recording or a replay,
+    // so the rewind history is only kept in live play.
+    let mut history = (has_argument("--rewind") && recorder.is_none() && replay_ticks.is_none())
+        .then(new_history::<S>);
+    let mut is_rewinding = false;
+
+    let mut timestep = new_timestep();
+
+    // Effects are only for show, so they are kept out of the simulation and
+    // are not rewound, saved or replayed.
+    let mut effects: Vec<Effect> = Vec::new();
 
     loop {
-        let keys = Keys {
-            tower_placement: is_key_pressed(KeyCode::T),
-            cancel_tower_placement: is_key_pressed(KeyCode::Escape),
-            place_tower: is_mouse_button_down(MouseButton::Left),
-            rotate_tower_clockwise: is_key_down(KeyCode::R),
-            rotate_tower_counter_clockwise: is_key_down(KeyCode::E),
-            reset: is_key_pressed(KeyCode::Enter),
+        let pad = gamepad_backend.poll();
+        let gamepad = &pad.buttons;
+
+        cursor = update_virtual_cursor(
+            cursor,
+            pad.left_stick,
+            Vec2::from(mouse_position()),
+            mouse_delta_position() != Vec2::ZERO,
+            Vec2::new(screen_width(), screen_height()),
+            get_frame_time(),
+        );
+
+        if let Some(menu) = rebind_menu.take() {
+            let (next_menu, next_bindings) = update_rebind_menu(menu, bindings, gamepad);
+
+            bindings = next_bindings;
+
+            match next_menu {
+                Some(menu) => {
+                    draw_rebind_menu(&menu, &bindings);
+                    rebind_menu = Some(menu);
+                }
+                None => {
+                    if let Err(error) = save_bindings(BINDINGS_PATH, &bindings) {
+                        eprintln!("Failed to save bindings: {}", error);
+                    }
+                }
+            }
+
+            next_frame().await;
+            continue;
+        }
+
+        if is_action_pressed(&bindings, gamepad, Action::OpenBindings) {
+            rebind_menu = Some(new_rebind_menu());
+        }
+
+        if is_action_pressed(&bindings, gamepad, Action::ToggleGridSnapping) {
+            is_snapping_to_grid = !is_snapping_to_grid;
+        }
+
+        if is_action_pressed(&bindings, gamepad, Action::ToggleRewind) && history.is_some() {
+            is_rewinding = !is_rewinding;
+
+            // Leaving rewind resumes from the selected state, the states after
+            // it are dropped once the next tick is recorded.
+            if !is_rewinding {
+                if let Some(state) = history.as_ref().and_then(current_state) {
+                    simulation = state.clone();
+                }
+            }
+        }
+
+        view = update_view(
+            view,
+            &bindings,
+            gamepad,
+            cursor.position,
+            previous_cursor_position,
+        );
+        previous_cursor_position = cursor.position;
+
+        if is_rewinding {
+            history = history.map(|history| {
+                if is_action_pressed(&bindings, gamepad, Action::RewindBack) {
+                    step_back(history)
+                } else if is_action_pressed(&bindings, gamepad, Action::RewindForward) {
+                    step_forward(history)
+                } else {
+                    history
+                }
+            });
+
+            if let Some(history) = history.as_ref() {
+                if let Some(state) = current_state(history) {
+                    render_world(state, &sprites, 1., &view, &[]);
+                }
+
+                draw_rewind_overlay(history);
+            }
+
+            next_frame().await;
+            continue;
+        }
+
+        let screen_size = Vec2::new(screen_width(), screen_height());
+        let pointer_position = screen_to_world(cursor.position, screen_size, &view);
+
+        let select_shop_item = if is_action_pressed(&bindings, gamepad, Action::NextShopItem) {
+            Some(step_shop_item(selected_shop_item, true))
+        } else if is_action_pressed(&bindings, gamepad, Action::PreviousShopItem) {
+            Some(step_shop_item(selected_shop_item, false))
+        } else {
+            None
         };
+        selected_shop_item = select_shop_item.or(selected_shop_item);
+
+        let keys = read_keys(
+            &bindings,
+            gamepad,
+            pointer_position,
+            previous_pointer_position,
+            screen_to_interface(cursor.position, screen_size),
+            is_snapping_to_grid.then_some(grid_size),
+            select_shop_item,
+        );
+
+        previous_pointer_position = pointer_position;
+
+        let (next_timestep, ticks) = advance_timestep(timestep, get_frame_time(), keys);
+        timestep = next_timestep;
+
+        for live_keys in ticks {
+            // Once a replay runs out of ticks the player takes over from there.
+            let tick_keys = replay_ticks
+                .as_mut()
+                .and_then(|ticks| ticks.next())
+                .unwrap_or(live_keys);
+
+            if let Some(recorder) = recorder.as_mut() {
+                recorder
+                    .record(&tick_keys)
+                    .expect("Failed to record replay");
+            }
+
+            simulation = simulation.step(FIXED_DELTA_TIME, tick_keys);
+            publish(
+                simulation.events(),
+                &mut [&mut |event: &GameEvent| spawn_effect(&mut effects, event)],
+            );
+            history = history.map(|history| record_state(history, simulation.clone()));
+        }
+
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.flush().expect("Failed to record replay");
+        }
+
+        if is_action_pressed(&bindings, gamepad, Action::SaveGame) {
+            if let Err(error) = simulation.save(SAVE_PATH) {
+                eprintln!("Failed to save game: {}", error);
+            }
+        }
+
+        // Loading would swap the state under a recording or replay, so it is
+        // only allowed in live play.
+        if is_action_pressed(&bindings, gamepad, Action::LoadGame)
+            && recorder.is_none()
+            && replay_ticks.is_none()
+        {
+            match S::load(SAVE_PATH) {
+                Ok(loaded_simulation) => simulation = loaded_simulation,
+                Err(error) => eprintln!("Failed to load game: {}", error),
+            }
+        }
+
+        effects = update_effects(effects, get_frame_time(), sprites.sprite_sheet());
+
+        render_world(
+            &simulation,
+            &sprites,
+            timestep_alpha(&timestep),
+            &view,
+            &effects,
+        );
+        draw_virtual_cursor(&cursor);
 
-        scene = update_scene(get_frame_time(), keys, scene);
         next_frame().await;
     }
 }
 
-// #[macroquad::main("Balloons")]
-// async fn main() {
-//     let mut scene = Scene::new().await;
+// The window starts out the size of the world and the sidebar and can be
+// resized freely, the two are scaled to fit.
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Balloons".to_owned(),
+        window_width: INTERFACE_WIDTH as i32,
+        window_height: INTERFACE_HEIGHT as i32,
+        window_resizable: true,
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn gamepad_backend() -> impl GamepadBackend {
+    GilrsGamepad::new()
+}
+
+#[cfg(not(feature = "gamepad"))]
+fn gamepad_backend() -> impl GamepadBackend {
+    NoGamepad
+}
+
+#[macroquad::main(window_conf)]
+async fn main() {
+    let implementation =
+        argument_value("--implementation").map_or(Implementation::Functional, |name| {
+            parse_implementation(&name).unwrap_or_else(|| panic!("Unknown implementation {}", name))
+        });
+
+    let replay = argument_value("--replay")
+        .map(|path| load_replay(&path, implementation).expect("Failed to load replay"));
+
+    let seed = replay
+        .as_ref()
+        .map_or(miniquad::date::now().to_bits(), |replay| replay.seed);
 
-//     loop {
-//         scene.update(get_frame_time());
+    let replay_ticks = replay.map(|replay| replay.ticks.into_iter());
 
-//         next_frame().await;
-//     }
-// }
+    let recorder = argument_value("--record").map(|path| {
+        ReplayWriter::create(&path, implementation, seed).expect("Failed to create replay")
+    });
+
+    let bindings = load_bindings(BINDINGS_PATH).unwrap_or_else(|error| {
+        eprintln!("Failed to load bindings, using the defaults: {}", error);
+        default_bindings()
+    });
+
+    match implementation {
+        Implementation::Functional => {
+            run(
+                new_scene(seed),
+                load_sprites().await,
+                replay_ticks,
+                recorder,
+                bindings,
+                gamepad_backend(),
+            )
+            .await
+        }
+        Implementation::ObjectOriented => {
+            run(
+                Scene::new(seed),
+                Sprites::load().await,
+                replay_ticks,
+                recorder,
+                bindings,
+                gamepad_backend(),
+            )
+            .await
+        }
+        Implementation::BevyEcs => {
+            run(
+                EcsScene::new(seed),
+                load_sprites().await,
+                replay_ticks,
+                recorder,
+                bindings,
+                gamepad_backend(),
+            )
+            .await
+        }
+    }
+}
@@ -1,6 +1,18 @@
+use std::io::Error;
+
+use crate::atlas::draw_sprite;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::functional::save::invalid_data;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
 use crate::object_oriented::drawable_object::DrawableObject;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
 use macroquad::prelude::*;
 
+#[allow(dead_code)]
 #[derive(Copy, Clone)]
 enum Direction {
     Right,
@@ -33,23 +45,31 @@ const BALLOON_SPEED: f32 = 150.;
 #[derive(Copy, Clone)]
 pub struct Balloon {
     position: Vec2,
+    previous_position: Vec2,
     direction: Direction,
+    speed: f32,
     state: BalloonState,
 }
 
 impl Balloon {
-    pub fn new() -> Self {
+    pub fn new(speed_multiplier: f32) -> Self {
+        let position = Vec2::new(BALLOON_SIZE, WORLD_HEIGHT / 2.);
+
         Self {
-            position: Vec2::new(BALLOON_SIZE, screen_height() / 2.),
+            position,
+            previous_position: position,
             direction: Direction::Right,
+            speed: BALLOON_SPEED * speed_multiplier,
             state: BalloonState::Alive,
         }
     }
 
     pub fn update(&mut self, delta_time: f32) {
+        self.previous_position = self.position;
+
         match self.direction {
-            Direction::Right => self.position.x += BALLOON_SPEED * delta_time,
-            Direction::Left => self.position.x -= BALLOON_SPEED * delta_time,
+            Direction::Right => self.position.x += self.speed * delta_time,
+            Direction::Left => self.position.x -= self.speed * delta_time,
         }
     }
 
@@ -61,6 +81,11 @@ impl Balloon {
         BALLOON_COLLIDER_SIZE
     }
 
+    pub fn has_escaped(&self) -> bool {
+        self.position.x > WORLD_WIDTH + BALLOON_COLLIDER_SIZE
+            || self.position.x < -BALLOON_COLLIDER_SIZE
+    }
+
     pub fn get_state(&self) -> BalloonState {
         self.state
     }
@@ -68,27 +93,58 @@ impl Balloon {
     pub fn set_state(&mut self, state: BalloonState) {
         self.state = state;
     }
+
+    // Saved in the functional layout.
+    pub fn encode(&self, writer: &mut SaveWriter) {
+        writer.write_vec2(self.position);
+        writer.write_vec2(self.previous_position);
+        writer.write_u8(match self.direction {
+            Direction::Right => 0,
+            Direction::Left => 1,
+        });
+        writer.write_f32(self.speed);
+        writer.write_u8(match self.state {
+            BalloonState::Alive => 0,
+            BalloonState::Popped => 1,
+            BalloonState::Escaped => 2,
+        });
+    }
+
+    pub fn decode(reader: &mut SaveReader) -> Result<Self, Error> {
+        Ok(Self {
+            position: reader.read_vec2()?,
+            previous_position: reader.read_vec2()?,
+            direction: match reader.read_u8()? {
+                0 => Direction::Right,
+                1 => Direction::Left,
+                _ => return Err(invalid_data("Invalid balloon direction")),
+            },
+            speed: reader.read_f32()?,
+            state: match reader.read_u8()? {
+                0 => BalloonState::Alive,
+                1 => BalloonState::Popped,
+                2 => BalloonState::Escaped,
+                _ => return Err(invalid_data("Invalid balloon state")),
+            },
+        })
+    }
 }
 
 impl DrawableObject for Balloon {
-    fn draw(&self, sprite: Option<&Texture2D>, _is_disabled: Option<bool>) {
-        draw_texture_ex(
-            sprite.unwrap(),
-            self.position.x - BALLOON_SIZE / 2.,
-            screen_height() / 2. - (BALLOON_SIZE / 2.),
+    fn draw(&self, sheet: Option<&SpriteSheet>, _is_disabled: Option<bool>, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+
+        draw_sprite(
+            sheet.unwrap(),
+            SpriteKind::Balloon,
+            SpriteAction::Idle,
+            get_time() as f32,
+            position,
+            BALLOON_SIZE,
+            0.,
             WHITE,
-            DrawTextureParams {
-                dest_size: Some(Vec2::new(BALLOON_SIZE, BALLOON_SIZE)),
-                ..Default::default()
-            },
         );
 
-        draw_circle_lines(
-            self.position.x,
-            screen_height() / 2.,
-            BALLOON_COLLIDER_SIZE,
-            2.,
-            RED,
-        );
+        draw_circle_lines(position.x, position.y, BALLOON_COLLIDER_SIZE, 2., RED);
     }
 }
@@ -0,0 +1,10 @@
+// A tower action the player took since the current round started, kept so the
+// latest placement or upgrade can be undone with a full refund.
+
+#[derive(Copy, Clone)]
+pub enum Command {
+    // Towers are always placed at the end of the list, so undoing in order
+    // always removes the last one.
+    PlaceTower { tower: usize, cost: u32 },
+    UpgradeTower { tower: usize, cost: u32 },
+}
@@ -0,0 +1,94 @@
+#[derive(Copy, Clone, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Impoppable,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Impoppable,
+    ];
+
+    pub fn starting_lives(&self) -> i32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Medium => 3,
+            Difficulty::Hard => 2,
+            Difficulty::Impoppable => 1,
+        }
+    }
+
+    pub fn starting_cash(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 40,
+            Difficulty::Medium => 30,
+            Difficulty::Hard => 25,
+            Difficulty::Impoppable => 25,
+        }
+    }
+
+    pub fn final_round(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 20,
+            Difficulty::Medium => 30,
+            Difficulty::Hard => 40,
+            Difficulty::Impoppable => 50,
+        }
+    }
+
+    fn price_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.85,
+            Difficulty::Medium => 1.,
+            Difficulty::Hard => 1.08,
+            Difficulty::Impoppable => 1.2,
+        }
+    }
+
+    pub fn balloon_speed_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.9,
+            Difficulty::Medium => 1.,
+            Difficulty::Hard => 1.1,
+            Difficulty::Impoppable => 1.2,
+        }
+    }
+
+    pub fn scale_price(&self, base_price: u32) -> u32 {
+        (base_price as f32 * self.price_multiplier()).round() as u32
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Impoppable => "Impoppable",
+        }
+    }
+
+    // Matches the ids the functional version saves.
+    pub fn get_id(&self) -> u8 {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Medium => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Impoppable => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Difficulty::Easy),
+            1 => Some(Difficulty::Medium),
+            2 => Some(Difficulty::Hard),
+            3 => Some(Difficulty::Impoppable),
+            _ => None,
+        }
+    }
+}
@@ -1,5 +1,7 @@
-use macroquad::texture::Texture2D;
+use crate::atlas::SpriteSheet;
 
 pub trait DrawableObject {
-    fn draw(&self, sprite: Option<&Texture2D>, is_disabled: Option<bool>);
+    // Moving objects are drawn alpha of the way from where they were on the
+    // previous tick to where they are now.
+    fn draw(&self, sheet: Option<&SpriteSheet>, is_disabled: Option<bool>, alpha: f32);
 }
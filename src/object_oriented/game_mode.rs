@@ -0,0 +1,95 @@
+use crate::object_oriented::difficulty::Difficulty;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum GameMode {
+    Standard,
+    Sandbox,
+    Deflation,
+    HalfCash,
+    Apopalypse,
+    Chimps,
+}
+
+const DEFLATION_CASH: u32 = 100;
+
+impl GameMode {
+    pub const ALL: [GameMode; 6] = [
+        GameMode::Standard,
+        GameMode::Sandbox,
+        GameMode::Deflation,
+        GameMode::HalfCash,
+        GameMode::Apopalypse,
+        GameMode::Chimps,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            GameMode::Standard => "Standard",
+            GameMode::Sandbox => "Sandbox",
+            GameMode::Deflation => "Deflation",
+            GameMode::HalfCash => "Half Cash",
+            GameMode::Apopalypse => "Apopalypse",
+            GameMode::Chimps => "CHIMPS",
+        }
+    }
+
+    pub fn starting_cash(&self, difficulty: Difficulty) -> u32 {
+        match self {
+            GameMode::Deflation => DEFLATION_CASH,
+            GameMode::HalfCash => difficulty.starting_cash() / 2,
+            _ => difficulty.starting_cash(),
+        }
+    }
+
+    pub fn income_multiplier(&self) -> f32 {
+        match self {
+            GameMode::Deflation => 0.,
+            GameMode::HalfCash => 0.5,
+            _ => 1.,
+        }
+    }
+
+    pub fn has_infinite_resources(&self) -> bool {
+        *self == GameMode::Sandbox
+    }
+
+    pub fn allows_spawning_on_demand(&self) -> bool {
+        *self == GameMode::Sandbox
+    }
+
+    pub fn pauses_between_rounds(&self) -> bool {
+        *self != GameMode::Apopalypse
+    }
+
+    pub fn allows_selling(&self) -> bool {
+        *self != GameMode::Chimps
+    }
+
+    pub fn allows_continues(&self) -> bool {
+        *self != GameMode::Chimps
+    }
+
+    // Matches the ids the functional version saves.
+    pub fn get_id(&self) -> u8 {
+        match self {
+            GameMode::Standard => 0,
+            GameMode::Sandbox => 1,
+            GameMode::Deflation => 2,
+            GameMode::HalfCash => 3,
+            GameMode::Apopalypse => 4,
+            GameMode::Chimps => 5,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(GameMode::Standard),
+            1 => Some(GameMode::Sandbox),
+            2 => Some(GameMode::Deflation),
+            3 => Some(GameMode::HalfCash),
+            4 => Some(GameMode::Apopalypse),
+            5 => Some(GameMode::Chimps),
+            _ => None,
+        }
+    }
+}
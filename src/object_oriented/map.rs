@@ -0,0 +1,23 @@
+#[derive(Copy, Clone, PartialEq)]
+pub enum Map {
+    Meadow,
+    Cobblestones,
+}
+
+impl Map {
+    pub const ALL: [Map; 2] = [Map::Meadow, Map::Cobblestones];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Map::Meadow => "Meadow",
+            Map::Cobblestones => "Cobblestones",
+        }
+    }
+
+    pub fn get_background_path(&self) -> &'static str {
+        match self {
+            Map::Meadow => "resources/sprites/background.png",
+            Map::Cobblestones => "resources/sprites/background2.png",
+        }
+    }
+}
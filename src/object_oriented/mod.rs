@@ -1,5 +1,12 @@
 pub mod balloon;
+pub mod command;
+pub mod difficulty;
 pub mod drawable_object;
+pub mod game_mode;
+pub mod map;
 pub mod projectile;
+pub mod random;
 pub mod scene;
+pub mod screen;
+pub mod sprites;
 pub mod tower;
@@ -1,6 +1,17 @@
+use std::io::Error;
+
 use macroquad::prelude::*;
 
+use crate::atlas::draw_sprite;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::functional::save::invalid_data;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
 use crate::object_oriented::balloon::Balloon;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
 
 #[derive(Copy, Clone)]
 pub enum ProjectileState {
@@ -12,31 +23,40 @@ pub enum ProjectileState {
 #[derive(Copy, Clone)]
 pub struct Projectile {
     position: Vec2,
+    previous_position: Vec2,
     direction: Vec2,
     state: ProjectileState,
 }
 
-const PROJECTILE_SIZE: f32 = 15.;
+pub const PROJECTILE_SIZE: f32 = 15.;
 
 impl Projectile {
     pub fn new(position: Vec2, direction: Vec2) -> Self {
         Self {
             position,
+            previous_position: position,
             direction,
             state: ProjectileState::Alive,
         }
     }
 
     pub fn update(&mut self, delta_time: f32) {
-        self.position += self.direction * 500. * delta_time;
-
+        // Bounds are checked before moving, so a projectile lives for one
+        // tick past the edge of the world, as in the functional version.
         if self.position.x < 0.
-            || self.position.x > screen_width()
+            || self.position.x > WORLD_WIDTH
             || self.position.y < 0.
-            || self.position.y > screen_height()
+            || self.position.y > WORLD_HEIGHT
         {
             self.state = ProjectileState::Dead;
         }
+
+        self.previous_position = self.position;
+        self.position += self.direction * 500. * delta_time;
+    }
+
+    pub fn get_position(&self) -> Vec2 {
+        self.position
     }
 
     pub fn check_collision(&self, balloon: &Balloon) -> bool {
@@ -75,7 +95,42 @@ impl Projectile {
         }
     }
 
-    pub fn draw(&self) {
-        draw_circle(self.position.x, self.position.y, PROJECTILE_SIZE, ORANGE);
+    // Saved in the functional layout.
+    pub fn encode(&self, writer: &mut SaveWriter) {
+        writer.write_vec2(self.position);
+        writer.write_vec2(self.previous_position);
+        writer.write_vec2(self.direction);
+        writer.write_u8(match self.state {
+            ProjectileState::Alive => 0,
+            ProjectileState::Dead => 1,
+            ProjectileState::Hit => 2,
+        });
+    }
+
+    pub fn decode(reader: &mut SaveReader) -> Result<Self, Error> {
+        Ok(Self {
+            position: reader.read_vec2()?,
+            previous_position: reader.read_vec2()?,
+            direction: reader.read_vec2()?,
+            state: match reader.read_u8()? {
+                0 => ProjectileState::Alive,
+                1 => ProjectileState::Dead,
+                2 => ProjectileState::Hit,
+                _ => return Err(invalid_data("Invalid projectile state")),
+            },
+        })
+    }
+
+    pub fn draw(&self, sheet: &SpriteSheet, alpha: f32) {
+        draw_sprite(
+            sheet,
+            SpriteKind::Projectile,
+            SpriteAction::Idle,
+            0.,
+            self.previous_position.lerp(self.position, alpha),
+            PROJECTILE_SIZE * 2.,
+            self.direction.y.atan2(self.direction.x),
+            WHITE,
+        );
     }
 }
@@ -1,81 +1,259 @@
 use macroquad::prelude::*;
 use std::collections::LinkedList;
+use std::io::Error;
 
+use crate::events::GameEvent;
+use crate::functional::save::invalid_data;
+use crate::functional::save::read_save_file;
+use crate::functional::save::write_save_file;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+use crate::functional::statistics::decode_statistics;
+use crate::functional::statistics::encode_statistics;
+use crate::functional::statistics::INITIAL_STATISTICS;
+use crate::guides::draw_placement_guides;
+use crate::guides::draw_range;
+use crate::input::is_drag;
+use crate::input::Keys;
+use crate::input::NO_KEYS;
 use crate::object_oriented::balloon::Balloon;
 use crate::object_oriented::balloon::BalloonState;
+use crate::object_oriented::command::Command;
+use crate::object_oriented::difficulty::Difficulty;
+use crate::object_oriented::game_mode::GameMode;
+use crate::object_oriented::map::Map;
+use crate::object_oriented::random::Rng;
+use crate::object_oriented::screen::Screen;
+use crate::object_oriented::sprites::Sprites;
 use crate::object_oriented::tower::Tower;
+use crate::placement::clamp_to_world;
+use crate::placement::draw_grid;
+use crate::placement::nudge_offset;
+use crate::placement::rotation_offset;
+use crate::placement::snap_to_grid;
+use crate::shop::chosen_shop_item;
+use crate::shop::draw_shop;
+use crate::shop::is_on_sidebar;
+use crate::simulation::GameSimulation;
+use crate::snapshot::GameSnapshot;
+use crate::snapshot::PlacementSnapshot;
+use crate::view::set_interface_camera;
+use crate::view::set_view_camera;
+use crate::view::View;
+use crate::view::INTERFACE_HEIGHT;
+use crate::view::INTERFACE_WIDTH;
+use crate::world::is_in_world;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
 
 use super::drawable_object::DrawableObject;
 
-struct Sprites {
-    background: Texture2D,
-    balloon: Texture2D,
-}
-
+#[derive(Clone)]
 pub struct Scene {
+    rng: Rng,
+    screen: Screen,
+    game_speed: u32,
+    map: Map,
+    difficulty: Difficulty,
+    game_mode: GameMode,
     coins: u32,
+    income_remainder: f32,
     lives: i32,
-    game_over: bool,
-    sprites: Sprites,
     balloons: Vec<Balloon>,
     towers: LinkedList<Tower>,
+    // The placements and upgrades made since the last round, newest last,
+    // which can be undone until the next one starts.
+    commands: Vec<Command>,
+    round: u32,
+    balloons_to_spawn: u32,
     spawn_timer: f32,
     is_placing_tower: bool,
     preview_tower: Option<Tower>,
+    // Where the pointer was pressed while placing, for aiming by dragging.
+    drag_start: Option<Vec2>,
+    // Where the arrow keys moved the preview to, until the pointer moves.
+    nudged_position: Option<Vec2>,
+    // The grid towers snap to on the last tick, for drawing it.
+    grid_size: Option<f32>,
+    // Where the pointer was on the last tick, for highlighting the shop.
+    pointer_position: Vec2,
+    interface_pointer_position: Vec2,
+    // What happened during the last step, for subscribers outside the
+    // simulation.
+    events: Vec<GameEvent>,
 }
 
-const TOWER_COST: u32 = 15;
+const MAX_GAME_SPEED: u32 = 3;
 
-impl Scene {
-    pub async fn new() -> Self {
-        let background_sprite = load_texture("resources/sprites/background2.png")
-            .await
-            .unwrap();
+const SPAWN_INTERVAL: f32 = 1.;
+const SPAWN_JITTER: f32 = 0.25;
 
-        let balloon_sprite = load_texture("resources/sprites/balloon.png").await.unwrap();
+const TOWER_COST: u32 = 15;
+const UPGRADE_COST: u32 = 10;
 
-        background_sprite.set_filter(FilterMode::Nearest);
-        balloon_sprite.set_filter(FilterMode::Nearest);
+impl Scene {
+    pub fn new(seed: u64) -> Self {
+        let difficulty = Difficulty::Medium;
+        let game_mode = GameMode::Standard;
 
         Self {
-            coins: 30,
-            lives: 3,
-            game_over: false,
-            sprites: Sprites {
-                background: background_sprite,
-                balloon: balloon_sprite,
-            },
+            rng: Rng::new(seed),
+            screen: Screen::MainMenu,
+            game_speed: 1,
+            map: Map::Meadow,
+            difficulty,
+            game_mode,
+            coins: game_mode.starting_cash(difficulty),
+            income_remainder: 0.0,
+            lives: difficulty.starting_lives(),
             balloons: Vec::new(),
             towers: LinkedList::new(),
+            commands: Vec::new(),
+            round: 0,
+            balloons_to_spawn: 0,
             spawn_timer: 0.0,
             is_placing_tower: false,
             preview_tower: None,
+            drag_start: None,
+            nudged_position: None,
+            grid_size: None,
+            pointer_position: Vec2::ZERO,
+            interface_pointer_position: Vec2::ZERO,
+            events: Vec::new(),
         }
     }
 
-    pub fn reset(&mut self) {
-        self.coins = 30;
-        self.lives = 3;
-        self.game_over = false;
+    pub fn reset(&mut self, difficulty: Difficulty, game_mode: GameMode) {
+        self.screen = Screen::BetweenRounds;
+        self.game_speed = 1;
+        self.difficulty = difficulty;
+        self.game_mode = game_mode;
+        self.coins = game_mode.starting_cash(difficulty);
+        self.income_remainder = 0.0;
+        self.lives = difficulty.starting_lives();
+        self.round = 0;
+        self.balloons_to_spawn = 0;
         self.spawn_timer = 0.0;
         self.is_placing_tower = false;
+        self.preview_tower = None;
+        self.drag_start = None;
+        self.nudged_position = None;
 
         self.balloons.clear();
         self.towers.clear();
+        self.commands.clear();
+    }
+
+    fn can_save(&self) -> bool {
+        matches!(
+            self.screen,
+            Screen::BetweenRounds | Screen::Playing | Screen::Paused
+        )
     }
 
-    fn draw_background(&self) {
+    // Written in the same layout as the functional version, so saves load in
+    // either. The statistics are only kept by the functional version, so
+    // empty ones are written here and loaded ones are dropped.
+    fn encode(&self, writer: &mut SaveWriter) {
+        // A game saved mid-round is restored paused, so the player is not
+        // dropped straight into the action when loading it.
+        writer.write_bool(self.screen == Screen::BetweenRounds);
+        writer.write_u32(Map::ALL.iter().position(|map| *map == self.map).unwrap() as u32);
+        writer.write_u8(self.difficulty.get_id());
+        writer.write_u8(self.game_mode.get_id());
+        writer.write_u32(self.coins);
+        writer.write_f32(self.income_remainder);
+        writer.write_i32(self.lives);
+        writer.write_u32(self.round);
+        writer.write_u32(self.balloons_to_spawn);
+        writer.write_f32(self.spawn_timer);
+        writer.write_u32(self.game_speed);
+        writer.write_u64(self.rng.state());
+
+        writer.write_u32(self.balloons.len() as u32);
+        self.balloons
+            .iter()
+            .for_each(|balloon| balloon.encode(writer));
+
+        writer.write_u32(self.towers.len() as u32);
+        self.towers.iter().for_each(|tower| tower.encode(writer));
+
+        encode_statistics(&INITIAL_STATISTICS, writer);
+    }
+
+    fn decode(reader: &mut SaveReader) -> Result<Self, Error> {
+        let screen = match reader.read_bool()? {
+            true => Screen::BetweenRounds,
+            false => Screen::Paused,
+        };
+
+        let map = *Map::ALL
+            .get(reader.read_u32()? as usize)
+            .ok_or_else(|| invalid_data("Invalid map"))?;
+        let difficulty = Difficulty::from_id(reader.read_u8()?)
+            .ok_or_else(|| invalid_data("Invalid difficulty"))?;
+        let game_mode = GameMode::from_id(reader.read_u8()?)
+            .ok_or_else(|| invalid_data("Invalid game mode"))?;
+        let coins = reader.read_u32()?;
+        let income_remainder = reader.read_f32()?;
+        let lives = reader.read_i32()?;
+        let round = reader.read_u32()?;
+        let balloons_to_spawn = reader.read_u32()?;
+        let spawn_timer = reader.read_f32()?;
+        let game_speed = reader.read_u32()?.clamp(1, MAX_GAME_SPEED);
+        let rng = Rng::new(reader.read_u64()?);
+
+        let balloons_count = reader.read_u32()?;
+        let balloons = (0..balloons_count)
+            .map(|_| Balloon::decode(reader))
+            .collect::<Result<Vec<Balloon>, Error>>()?;
+
+        let towers_count = reader.read_u32()?;
+        let towers = (0..towers_count)
+            .map(|_| Tower::decode(reader))
+            .collect::<Result<LinkedList<Tower>, Error>>()?;
+
+        decode_statistics(reader)?;
+
+        Ok(Self {
+            rng,
+            screen,
+            game_speed,
+            map,
+            difficulty,
+            game_mode,
+            coins,
+            income_remainder,
+            lives,
+            balloons,
+            towers,
+            round,
+            balloons_to_spawn,
+            spawn_timer,
+            ..Self::new(0)
+        })
+    }
+
+    fn return_to_menu(&mut self) {
+        self.reset(Difficulty::Medium, GameMode::Standard);
+        self.map = Map::Meadow;
+        self.screen = Screen::MainMenu;
+    }
+
+    fn draw_background(&self, sprites: &Sprites) {
         clear_background(LIGHTGRAY);
 
-        let scale_factor = screen_width() / self.sprites.background.width();
-        let adjusted_width = self.sprites.background.width() * scale_factor;
-        let adjusted_height = self.sprites.background.height() * scale_factor;
+        let background = sprites.get_background(self.map);
 
-        let x = (screen_width() - adjusted_width) / 2.0;
-        let y = (screen_height() - adjusted_height) / 2.0;
+        let scale_factor = WORLD_WIDTH / background.width();
+        let adjusted_width = background.width() * scale_factor;
+        let adjusted_height = background.height() * scale_factor;
+
+        let x = (WORLD_WIDTH - adjusted_width) / 2.0;
+        let y = (WORLD_HEIGHT - adjusted_height) / 2.0;
 
         draw_texture_ex(
-            &self.sprites.background,
+            background,
             x,
             y,
             WHITE,
@@ -86,165 +264,772 @@ impl Scene {
         );
     }
 
+    pub fn continue_game(&mut self) {
+        self.lives = self.difficulty.starting_lives();
+        self.screen = Screen::BetweenRounds;
+        self.balloons_to_spawn = 0;
+
+        self.balloons.clear();
+    }
+
     fn draw_statistics(&self) {
+        let (coins, lives) = if self.game_mode.has_infinite_resources() {
+            (String::from("INF"), String::from("INF"))
+        } else {
+            (self.coins.to_string(), self.lives.to_string())
+        };
+
+        draw_text(format!("COINS: {}", coins).as_str(), 10., 32., 32., WHITE);
+
+        draw_text(format!("LIVES: {}", lives).as_str(), 10., 64., 32., WHITE);
+
         draw_text(
-            format!("COINS: {}", self.coins).as_str(),
+            format!("ROUND: {}/{}", self.round, self.difficulty.final_round()).as_str(),
             10.,
+            96.,
             32.,
+            WHITE,
+        );
+
+        draw_text(
+            format!("SPEED: {}x", self.game_speed).as_str(),
+            10.,
+            128.,
             32.,
             WHITE,
         );
 
         draw_text(
-            format!("LIVES: {}", self.lives).as_str(),
+            format!(
+                "{} - {}",
+                self.difficulty.get_name(),
+                self.game_mode.get_name()
+            )
+            .as_str(),
             10.,
-            64.,
+            160.,
             32.,
             WHITE,
         );
+
+        if self.screen == Screen::BetweenRounds {
+            draw_text(
+                "Press [space] to start the next round",
+                10.,
+                WORLD_HEIGHT - 20.,
+                32.,
+                WHITE,
+            );
+        }
+    }
+
+    fn draw_undo_hint(&self) {
+        if self.commands.is_empty() {
+            return;
+        }
+
+        draw_text(
+            "Press [z] to undo the last placement or upgrade",
+            10.,
+            WORLD_HEIGHT - 52.,
+            24.,
+            WHITE,
+        );
+    }
+
+    fn draw_sidebar(&self) {
+        let coins = (!self.game_mode.has_infinite_resources()).then_some(self.coins);
+
+        draw_shop(
+            &[self.difficulty.scale_price(TOWER_COST)],
+            coins,
+            self.interface_pointer_position,
+        );
+    }
+
+    // On the map and clear of every other tower's footprint.
+    fn is_free_for_tower(&self, position: Vec2) -> bool {
+        is_in_world(position) && !self.towers.iter().any(|tower| tower.overlaps(position))
+    }
+
+    fn can_afford(&self, price: u32) -> bool {
+        self.game_mode.has_infinite_resources() || self.coins >= price
+    }
+
+    fn spend(&mut self, price: u32) {
+        if !self.game_mode.has_infinite_resources() {
+            self.coins -= price;
+        }
+    }
+
+    fn earn(&mut self, amount: u32) {
+        let income = self.income_remainder + amount as f32 * self.game_mode.income_multiplier();
+
+        self.coins += income.floor() as u32;
+        self.income_remainder = income.fract();
+    }
+
+    fn start_round(&mut self) {
+        self.screen = Screen::Playing;
+        self.round += 1;
+        self.balloons_to_spawn = 5 + self.round * 2;
+        self.spawn_timer = 0.0;
+        self.commands.clear();
+        self.events
+            .push(GameEvent::RoundStarted { round: self.round });
+    }
+
+    // Towers placed or upgraded during the round have already fired, so they
+    // can no longer be undone.
+    fn finish_round(&mut self) {
+        self.commands.clear();
+
+        if self.round >= self.difficulty.final_round() {
+            self.screen = Screen::Victory;
+        } else if !self.game_mode.pauses_between_rounds() {
+            self.start_round();
+        } else {
+            self.screen = Screen::BetweenRounds;
+        }
+    }
+
+    fn update_rounds(&mut self) {
+        if self.screen != Screen::Playing || self.balloons_to_spawn > 0 {
+            return;
+        }
+
+        if self.balloons.is_empty() {
+            self.finish_round();
+        } else if !self.game_mode.pauses_between_rounds()
+            && self.round < self.difficulty.final_round()
+        {
+            self.start_round();
+        }
+    }
+
+    fn update_spawning(&mut self, delta_time: f32) {
+        if self.balloons_to_spawn == 0 {
+            return;
+        }
+
+        self.spawn_timer += delta_time;
+
+        if self.spawn_timer > SPAWN_INTERVAL {
+            let jitter = self.rng.range(0., SPAWN_JITTER);
+
+            self.spawn_timer -= SPAWN_INTERVAL;
+            self.spawn_timer -= jitter;
+            self.balloons_to_spawn -= 1;
+            self.spawn_balloon();
+        }
     }
 
     fn spawn_balloon(&mut self) {
-        self.balloons.push(Balloon::new());
+        self.balloons
+            .push(Balloon::new(self.difficulty.balloon_speed_multiplier()));
+    }
+
+    fn draw_menu(&self, title: &str, options: Vec<&str>) {
+        clear_background(WHITE);
+
+        let font_size = 30.;
+        let title_size = measure_text(title, None, font_size as _, 1.0);
+
+        draw_text(
+            title,
+            INTERFACE_WIDTH / 2. - title_size.width / 2.,
+            WORLD_HEIGHT / 4.,
+            font_size,
+            DARKGRAY,
+        );
+
+        for (index, option) in options.iter().enumerate() {
+            let line = format!("[{}] {}", index + 1, option);
+            let line_size = measure_text(line.as_str(), None, font_size as _, 1.0);
+
+            draw_text(
+                line.as_str(),
+                INTERFACE_WIDTH / 2. - line_size.width / 2.,
+                WORLD_HEIGHT / 4. + font_size * 1.5 * (index + 1) as f32,
+                font_size,
+                DARKGRAY,
+            );
+        }
+    }
+
+    fn draw_message(&self, text: &str) {
+        let font_size = 30.;
+        let text_size = measure_text(text, None, font_size as _, 1.0);
+
+        draw_text(
+            text,
+            INTERFACE_WIDTH / 2. - text_size.width / 2.,
+            WORLD_HEIGHT / 2. + text_size.height / 2.,
+            font_size,
+            DARKGRAY,
+        );
+    }
+
+    fn upgrade_tower_at(&mut self, position: Vec2) {
+        let upgrade_cost = self.difficulty.scale_price(UPGRADE_COST);
+
+        if !self.can_afford(upgrade_cost) {
+            return;
+        }
+
+        let selected_tower = self
+            .towers
+            .iter_mut()
+            .enumerate()
+            .find(|(_, tower)| tower.contains_point(position) && tower.can_upgrade());
+
+        if let Some((index, tower)) = selected_tower {
+            tower.upgrade();
+            tower.add_spent(upgrade_cost);
+            self.events.push(GameEvent::TowerUpgraded {
+                position: tower.get_position(),
+                cost: upgrade_cost,
+            });
+            self.spend(upgrade_cost);
+
+            self.commands.push(Command::UpgradeTower {
+                tower: index,
+                cost: upgrade_cost,
+            });
+        }
+    }
+
+    fn sell_tower_at(&mut self, position: Vec2) {
+        let selected_tower = self
+            .towers
+            .iter()
+            .position(|tower| tower.contains_point(position));
+
+        if let Some(index) = selected_tower {
+            let mut remaining_towers = self.towers.split_off(index);
+            let sold_tower = remaining_towers.pop_front().unwrap();
+
+            self.coins += sold_tower.get_sell_value();
+            self.towers.append(&mut remaining_towers);
+            self.events.push(GameEvent::TowerSold {
+                position: sold_tower.get_position(),
+                refund: sold_tower.get_sell_value(),
+            });
+
+            // Selling cannot be undone, and it shifts the towers the earlier
+            // commands refer to.
+            self.commands.clear();
+        }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
-        if !self.game_over {
-            self.draw_background();
+    // Reverses the latest placement or upgrade since the last round, with a
+    // full refund. It only runs between rounds.
+    fn undo(&mut self) {
+        match self.commands.pop() {
+            Some(Command::PlaceTower { tower, cost }) if tower < self.towers.len() => {
+                let mut remaining_towers = self.towers.split_off(tower);
 
-            self.spawn_timer += delta_time;
+                if let Some(removed_tower) = remaining_towers.pop_front() {
+                    self.events.push(GameEvent::TowerPlacementUndone {
+                        position: removed_tower.get_position(),
+                        refund: cost,
+                    });
+                }
 
-            if self.spawn_timer > 1. {
-                self.spawn_timer -= 1.;
-                self.spawn_balloon();
+                self.towers.append(&mut remaining_towers);
+                self.coins += cost;
+            }
+            Some(Command::UpgradeTower { tower, cost }) => {
+                if let Some(upgraded_tower) = self.towers.iter_mut().nth(tower) {
+                    upgraded_tower.downgrade(cost);
+                    self.coins += cost;
+                    self.events.push(GameEvent::TowerUpgradeUndone {
+                        position: upgraded_tower.get_position(),
+                        refund: cost,
+                    });
+                }
             }
+            _ => {}
+        }
+    }
+
+    // Clicking places the tower where it is previewed, while dragging from the
+    // press keeps it where the press started and aims it at the pointer. Towers
+    // are only placed on free spots of the map and never by releasing over the
+    // sidebar, and pressing an affordable shop button starts placing one.
+    fn update_tower_placement(&mut self, keys: &Keys) {
+        let tower_cost = self.difficulty.scale_price(TOWER_COST);
+        let is_shop_choice = chosen_shop_item(keys).is_some() && self.can_afford(tower_cost);
 
-            if is_key_down(KeyCode::T) {
-                self.is_placing_tower = true;
+        if keys.tower_placement.pressed || is_shop_choice {
+            self.is_placing_tower = true;
+            self.preview_tower = Some(Tower::new(keys.pointer_position));
+            self.drag_start = None;
+            self.nudged_position = None;
+        } else if keys.cancel_tower_placement.pressed {
+            self.is_placing_tower = false;
+            self.preview_tower = None;
+            self.drag_start = None;
+            self.nudged_position = None;
+        }
+
+        // Moving the pointer takes over from the arrow keys again.
+        if keys.pointer_delta != Vec2::ZERO {
+            self.nudged_position = None;
+        }
+
+        if !self.is_placing_tower {
+            if keys.place_tower.released {
+                self.drag_start = None;
             }
 
-            if is_key_down(KeyCode::Escape) {
-                self.is_placing_tower = false;
+            return;
+        }
+
+        if self.drag_start.is_none()
+            && keys.place_tower.pressed
+            && !is_on_sidebar(keys.interface_pointer_position)
+        {
+            self.drag_start = Some(keys.pointer_position);
+        }
+
+        let drag_start = self.drag_start;
+
+        if keys.place_tower.released {
+            self.drag_start = None;
+        }
+
+        let can_afford_tower = self.can_afford(tower_cost);
+
+        let Some(preview_tower) = self.preview_tower.as_mut() else {
+            return;
+        };
+
+        let pointer_position = clamp_to_world(snap_to_grid(keys.pointer_position, keys.grid_size));
+        let nudge = nudge_offset(keys);
+
+        if nudge != Vec2::ZERO {
+            self.nudged_position = Some(clamp_to_world(
+                self.nudged_position.unwrap_or(pointer_position) + nudge,
+            ));
+        }
+
+        preview_tower.set_angle(preview_tower.get_angle() + rotation_offset(keys));
+        preview_tower.set_position(self.nudged_position.unwrap_or(pointer_position));
+
+        if let Some(start) = drag_start {
+            let is_dragging = keys.place_tower.held || keys.place_tower.released;
+
+            if is_dragging && is_drag(start, keys.pointer_position) {
+                let aim = keys.pointer_position - start;
+
+                preview_tower.set_position(clamp_to_world(snap_to_grid(start, keys.grid_size)));
+                preview_tower.set_angle(aim.y.atan2(aim.x));
             }
+        }
 
-            if self.is_placing_tower {
-                let mouse_position = mouse_position();
+        let position = preview_tower.get_position();
 
-                if self.preview_tower.is_none() {
-                    self.preview_tower =
-                        Some(Tower::new(Vec2::new(mouse_position.0, mouse_position.1)));
-                }
+        if keys.place_tower.released
+            && can_afford_tower
+            && self.is_free_for_tower(position)
+            && !is_on_sidebar(keys.interface_pointer_position)
+        {
+            let Some(mut new_tower) = self.preview_tower.take() else {
+                return;
+            };
 
-                if is_key_down(KeyCode::R) {
-                    let new_tower_angle =
-                        self.preview_tower.as_mut().unwrap().get_angle() + 5. * get_frame_time();
-
-                    self.preview_tower
-                        .as_mut()
-                        .unwrap()
-                        .set_angle(new_tower_angle);
-                } else if is_key_down(KeyCode::E) {
-                    let new_tower_angle =
-                        self.preview_tower.as_mut().unwrap().get_angle() - 5. * get_frame_time();
-
-                    self.preview_tower
-                        .as_mut()
-                        .unwrap()
-                        .set_angle(new_tower_angle);
-                }
+            new_tower.add_spent(tower_cost);
+            self.events.push(GameEvent::TowerPlaced {
+                position,
+                cost: tower_cost,
+            });
+            self.commands.push(Command::PlaceTower {
+                tower: self.towers.len(),
+                cost: tower_cost,
+            });
+            self.towers.push_back(new_tower);
+
+            self.is_placing_tower = false;
+            self.spend(tower_cost);
+        }
+    }
+
+    fn update_tower_actions(&mut self, keys: &Keys) {
+        if keys.upgrade_tower.pressed && !self.is_placing_tower {
+            self.upgrade_tower_at(keys.pointer_position);
+        }
 
-                self.preview_tower
-                    .as_mut()
-                    .unwrap()
-                    .set_position(Vec2::new(mouse_position.0, mouse_position.1));
+        if keys.sell_tower.pressed && !self.is_placing_tower && self.game_mode.allows_selling() {
+            self.sell_tower_at(keys.pointer_position);
+        }
+    }
 
-                self.preview_tower
-                    .as_mut()
-                    .unwrap()
-                    .draw(None, Some(self.coins < TOWER_COST));
+    fn update_game_speed(&mut self, keys: &Keys) {
+        if keys.change_game_speed.pressed {
+            self.game_speed = self.game_speed % MAX_GAME_SPEED + 1;
+        }
+    }
 
-                if is_mouse_button_down(MouseButton::Left) && self.coins >= TOWER_COST {
-                    self.towers
-                        .push_back(self.preview_tower.as_mut().unwrap().clone());
+    fn update_between_rounds(&mut self, keys: &Keys) {
+        self.update_game_speed(keys);
+        self.update_tower_placement(keys);
+        self.update_tower_actions(keys);
 
-                    self.is_placing_tower = false;
-                    self.preview_tower = None;
-                    self.coins -= TOWER_COST;
-                }
+        if keys.undo.pressed {
+            self.undo();
+        }
+
+        if keys.start_round.pressed || !self.game_mode.pauses_between_rounds() {
+            self.start_round();
+        }
+    }
+
+    fn update_playing(&mut self, delta_time: f32, keys: &Keys) {
+        if keys.pause.pressed {
+            self.screen = Screen::Paused;
+            return;
+        }
+
+        self.update_game_speed(keys);
+        self.simulate_tick(delta_time, keys);
+
+        // Extra substeps reuse the same delta so that collisions behave exactly
+        // as they would at 1x, and input is only consumed by the first one.
+        for _ in 1..self.game_speed {
+            if self.screen != Screen::Playing {
+                break;
             }
 
-            self.update_balloons(delta_time);
-            self.update_towers(delta_time);
+            self.simulate_tick(delta_time, &NO_KEYS);
+        }
+    }
+
+    fn update_paused(&mut self, delta_time: f32, keys: &Keys) {
+        if keys.pause.pressed {
+            self.screen = Screen::Playing;
+        } else if keys.step_frame.pressed {
+            self.screen = Screen::Playing;
+            self.simulate_tick(delta_time, &NO_KEYS);
+
+            if self.screen == Screen::Playing {
+                self.screen = Screen::Paused;
+            }
+        } else {
+            self.update_game_speed(keys);
+        }
+    }
 
-            for balloon in &mut self.balloons {
-                let position = balloon.get_position();
-                let size = balloon.get_collision_size();
+    fn simulate_tick(&mut self, delta_time: f32, keys: &Keys) {
+        self.update_spawning(delta_time);
 
-                if position.x > screen_width() + size || position.x < -size {
-                    balloon.set_state(BalloonState::Escaped);
-                    self.lives -= 1;
+        if keys.spawn_balloon.pressed && self.game_mode.allows_spawning_on_demand() {
+            self.spawn_balloon();
+        }
+
+        self.update_tower_placement(keys);
+        self.update_tower_actions(keys);
+
+        self.update_balloons(delta_time);
+        self.update_towers(delta_time);
+
+        let mut popped_balloons = 0;
 
-                    if self.lives <= 0 {
-                        self.game_over = true;
+        for tower in &mut self.towers {
+            for projectile in &mut tower.get_projectiles().iter_mut() {
+                // A dart pops one balloon, and a balloon is popped once.
+                for balloon in &mut self.balloons {
+                    if !projectile.is_hit()
+                        && balloon.get_state() == BalloonState::Alive
+                        && projectile.check_collision(balloon)
+                    {
+                        projectile.hit();
+                        balloon.set_state(BalloonState::Popped);
+                        self.events.push(GameEvent::BalloonPopped {
+                            position: balloon.get_position(),
+                        });
+
+                        popped_balloons += 1;
                     }
                 }
             }
 
-            for tower in &mut self.towers {
-                for projectile in &mut tower.get_projectiles().iter_mut() {
-                    for balloon in &mut self.balloons {
-                        if projectile.check_collision(balloon) {
-                            projectile.hit();
-                            balloon.set_state(BalloonState::Popped);
+            tower.clean_projectiles();
+        }
+
+        self.earn(popped_balloons);
+
+        for balloon in &mut self.balloons {
+            if balloon.get_state() != BalloonState::Alive || !balloon.has_escaped() {
+                continue;
+            }
+
+            balloon.set_state(BalloonState::Escaped);
+            self.events.push(GameEvent::BalloonEscaped {
+                position: balloon.get_position(),
+            });
 
-                            self.coins += 1;
-                        }
-                    }
+            if !self.game_mode.has_infinite_resources() {
+                self.lives -= 1;
+            }
+        }
+
+        if self.lives <= 0 {
+            self.screen = Screen::Defeat;
+        }
+
+        self.balloons
+            .retain(|balloon| balloon.get_state() == BalloonState::Alive);
+
+        self.update_rounds();
+    }
+
+    pub fn update(&mut self, delta_time: f32, keys: &Keys) {
+        self.events.clear();
+        self.pointer_position = keys.pointer_position;
+        self.interface_pointer_position = keys.interface_pointer_position;
+        self.grid_size = keys.grid_size;
+
+        match self.screen {
+            Screen::MainMenu => {
+                if keys.confirm.pressed {
+                    self.screen = Screen::MapSelect;
+                }
+            }
+            Screen::MapSelect => {
+                if let Some(map) = keys.select_map.and_then(|index| Map::ALL.get(index)) {
+                    self.map = *map;
+                    self.screen = Screen::DifficultySelect;
                 }
+            }
+            Screen::DifficultySelect => {
+                let difficulty = keys
+                    .select_difficulty
+                    .and_then(|index| Difficulty::ALL.get(index));
 
-                tower.clean_projectiles();
+                if let Some(difficulty) = difficulty {
+                    self.difficulty = *difficulty;
+                    self.screen = Screen::GameModeSelect;
+                }
             }
+            Screen::GameModeSelect => {
+                let game_mode = keys
+                    .select_game_mode
+                    .and_then(|index| GameMode::ALL.get(index));
 
-            self.balloons
-                .retain(|balloon| balloon.get_state() == BalloonState::Alive);
+                if let Some(game_mode) = game_mode {
+                    self.reset(self.difficulty, *game_mode);
+                }
+            }
+            Screen::BetweenRounds => self.update_between_rounds(keys),
+            Screen::Playing => self.update_playing(delta_time, keys),
+            Screen::Paused => self.update_paused(delta_time, keys),
+            Screen::Victory => {
+                if keys.confirm.pressed {
+                    self.return_to_menu();
+                }
+            }
+            Screen::Defeat => {
+                if keys.confirm.pressed {
+                    self.reset(self.difficulty, self.game_mode);
+                } else if keys.continue_game.pressed && self.game_mode.allows_continues() {
+                    self.continue_game();
+                }
+            }
+        }
+    }
 
-            self.draw_statistics();
-        } else {
-            clear_background(WHITE);
-            let text = "Game Over. Press [enter] to play again.";
-            let font_size = 30.;
-            let text_size = measure_text(text, None, font_size as _, 1.0);
+    pub fn draw(&self, sprites: &Sprites, alpha: f32, view: &View) {
+        match self.screen {
+            Screen::MainMenu => {
+                clear_background(WHITE);
+                self.draw_message("Balloons. Press [enter] to start.");
+            }
+            Screen::MapSelect => self.draw_menu(
+                "Select a map:",
+                Map::ALL.iter().map(|map| map.get_name()).collect(),
+            ),
+            Screen::DifficultySelect => self.draw_menu(
+                "Select a difficulty:",
+                Difficulty::ALL
+                    .iter()
+                    .map(|difficulty| difficulty.get_name())
+                    .collect(),
+            ),
+            Screen::GameModeSelect => self.draw_menu(
+                "Select a game mode:",
+                GameMode::ALL
+                    .iter()
+                    .map(|game_mode| game_mode.get_name())
+                    .collect(),
+            ),
+            Screen::BetweenRounds | Screen::Playing | Screen::Paused => {
+                // Nothing moves outside of a round, so the world is drawn as
+                // it is.
+                let alpha = if self.screen == Screen::Playing {
+                    alpha
+                } else {
+                    1.
+                };
 
-            draw_text(
-                text,
-                screen_width() / 2. - text_size.width / 2.,
-                screen_height() / 2. + text_size.height / 2.,
-                font_size,
-                DARKGRAY,
-            );
+                self.draw_world(sprites, alpha, view);
+                self.draw_statistics();
+                self.draw_sidebar();
+
+                if self.screen == Screen::BetweenRounds {
+                    self.draw_undo_hint();
+                }
+
+                if self.screen == Screen::Paused {
+                    draw_rectangle(
+                        0.,
+                        0.,
+                        INTERFACE_WIDTH,
+                        INTERFACE_HEIGHT,
+                        Color::new(0., 0., 0., 0.5),
+                    );
+
+                    self.draw_message("Paused. Press [p] to resume or [n] to step a frame.");
+                }
+            }
+            Screen::Victory => {
+                clear_background(WHITE);
+                self.draw_message("Victory! Press [enter] to return to the main menu.");
+            }
+            Screen::Defeat => {
+                clear_background(WHITE);
+
+                if self.game_mode.allows_continues() {
+                    self.draw_message("Game Over. Press [enter] to play again or [c] to continue.");
+                } else {
+                    self.draw_message("Game Over. Press [enter] to play again.");
+                }
+            }
+        }
+    }
+
+    fn draw_world(&self, sprites: &Sprites, alpha: f32, view: &View) {
+        set_view_camera(view);
+        self.draw_background(sprites);
+
+        for balloon in &self.balloons {
+            balloon.draw(Some(sprites.get_sheet()), None, alpha);
+        }
 
-            if is_key_down(KeyCode::Enter) {
-                self.reset();
+        for tower in &self.towers {
+            tower.draw(Some(sprites.get_sheet()), Some(false), alpha);
+        }
+
+        self.draw_hovered_tower_range();
+
+        if let (true, Some(preview_tower)) = (self.is_placing_tower, self.preview_tower.as_ref()) {
+            let tower_cost = self.difficulty.scale_price(TOWER_COST);
+
+            if let Some(grid_size) = self.grid_size {
+                draw_grid(grid_size);
             }
+
+            let is_valid = self.can_afford(tower_cost)
+                && self.is_free_for_tower(preview_tower.get_position())
+                && !is_on_sidebar(self.interface_pointer_position);
+
+            preview_tower.draw(Some(sprites.get_sheet()), Some(!is_valid), 1.);
+            draw_placement_guides(&preview_tower.guides(), is_valid);
+        }
+
+        set_interface_camera();
+    }
+
+    // Shows the range of the tower under the pointer, unless a tower is being
+    // placed, which shows its own.
+    fn draw_hovered_tower_range(&self) {
+        if self.is_placing_tower {
+            return;
+        }
+
+        let hovered_tower = self
+            .towers
+            .iter()
+            .find(|tower| tower.contains_point(self.pointer_position));
+
+        if let Some(tower) = hovered_tower {
+            let guides = tower.guides();
+
+            draw_range(guides.position, guides.range);
         }
     }
 
     fn update_balloons(&mut self, delta_time: f32) {
         for balloon in &mut self.balloons {
             balloon.update(delta_time);
-            balloon.draw(Some(&self.sprites.balloon), None);
         }
     }
 
     fn update_towers(&mut self, delta_time: f32) {
         for tower in &mut self.towers {
             tower.update(delta_time);
-            tower.draw(None, Some(false));
 
             for projectile in &mut tower.get_projectiles().iter_mut() {
                 projectile.update(delta_time);
-                projectile.draw();
             }
         }
     }
 }
+
+impl GameSimulation for Scene {
+    type Sprites = Sprites;
+
+    fn step(mut self, delta_time: f32, keys: Keys) -> Self {
+        self.update(delta_time, &keys);
+        self
+    }
+
+    fn render(&self, sprites: &Sprites, alpha: f32, view: &View) {
+        self.draw(sprites, alpha, view);
+    }
+
+    fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            screen: self.screen.snapshot(),
+            game_speed: self.game_speed,
+            map: Map::ALL.iter().position(|map| *map == self.map).unwrap(),
+            difficulty: self.difficulty.get_id() as usize,
+            game_mode: self.game_mode.get_id() as usize,
+            coins: self.coins,
+            lives: self.lives,
+            round: self.round,
+            balloons_to_spawn: self.balloons_to_spawn,
+            balloons: self
+                .balloons
+                .iter()
+                .map(|balloon| balloon.get_position())
+                .collect(),
+            towers: self.towers.iter().map(|tower| tower.snapshot()).collect(),
+            placement: PlacementSnapshot {
+                is_placing_tower: self.is_placing_tower,
+                preview_tower: self
+                    .preview_tower
+                    .as_ref()
+                    .map(|preview_tower| preview_tower.preview_snapshot()),
+                drag_start: self.drag_start,
+                nudged_position: self.nudged_position,
+            },
+        }
+    }
+
+    fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        if !self.can_save() {
+            return Err(invalid_data("Games can only be saved while playing"));
+        }
+
+        write_save_file(path, |writer| self.encode(writer))
+    }
+
+    fn load(path: &str) -> Result<Self, Error> {
+        read_save_file(path, Self::decode)
+    }
+}
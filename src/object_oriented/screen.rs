@@ -0,0 +1,30 @@
+use crate::snapshot::ScreenSnapshot;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Screen {
+    MainMenu,
+    MapSelect,
+    DifficultySelect,
+    GameModeSelect,
+    BetweenRounds,
+    Playing,
+    Paused,
+    Victory,
+    Defeat,
+}
+
+impl Screen {
+    pub fn snapshot(&self) -> ScreenSnapshot {
+        match self {
+            Screen::MainMenu => ScreenSnapshot::MainMenu,
+            Screen::MapSelect => ScreenSnapshot::MapSelect,
+            Screen::DifficultySelect => ScreenSnapshot::DifficultySelect,
+            Screen::GameModeSelect => ScreenSnapshot::GameModeSelect,
+            Screen::BetweenRounds => ScreenSnapshot::BetweenRounds,
+            Screen::Playing => ScreenSnapshot::Playing,
+            Screen::Paused => ScreenSnapshot::Paused,
+            Screen::Victory => ScreenSnapshot::Victory,
+            Screen::Defeat => ScreenSnapshot::Defeat,
+        }
+    }
+}
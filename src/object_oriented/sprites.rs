@@ -0,0 +1,46 @@
+use macroquad::prelude::*;
+
+use crate::atlas::load_sprite_sheet;
+use crate::atlas::HasSpriteSheet;
+use crate::atlas::SpriteSheet;
+use crate::object_oriented::map::Map;
+
+pub struct Sprites {
+    // One per map, in the order of Map::ALL.
+    backgrounds: Vec<Texture2D>,
+    sheet: SpriteSheet,
+}
+
+impl Sprites {
+    pub async fn load() -> Self {
+        let mut backgrounds = Vec::new();
+
+        for map in Map::ALL.iter() {
+            let background_sprite = load_texture(map.get_background_path()).await.unwrap();
+
+            background_sprite.set_filter(FilterMode::Nearest);
+            backgrounds.push(background_sprite);
+        }
+
+        Self {
+            backgrounds,
+            sheet: load_sprite_sheet().await,
+        }
+    }
+
+    pub fn get_background(&self, map: Map) -> &Texture2D {
+        let index = Map::ALL.iter().position(|other| *other == map).unwrap_or(0);
+
+        &self.backgrounds[index]
+    }
+
+    pub fn get_sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+}
+
+impl HasSpriteSheet for Sprites {
+    fn sprite_sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+}
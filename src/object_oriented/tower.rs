@@ -1,7 +1,20 @@
 use crate::object_oriented::drawable_object::DrawableObject;
 use macroquad::prelude::*;
-
+use std::io::Error;
+
+use crate::atlas::draw_sprite;
+use crate::atlas::is_action_playing;
+use crate::atlas::SpriteAction;
+use crate::atlas::SpriteKind;
+use crate::atlas::SpriteSheet;
+use crate::functional::save::SaveReader;
+use crate::functional::save::SaveWriter;
+use crate::guides::TowerGuides;
 use crate::object_oriented::projectile::Projectile;
+use crate::object_oriented::projectile::PROJECTILE_SIZE;
+use crate::snapshot::PreviewTowerSnapshot;
+use crate::snapshot::TowerSnapshot;
+use crate::world::distance_to_world_edge;
 
 #[derive(Clone)]
 pub struct Tower {
@@ -11,9 +24,11 @@ pub struct Tower {
     projectiles: Vec<Projectile>,
     pop_count: u32,
     level: u32,
+    spent: u32,
 }
 
 const TOWER_SIZE: f32 = 50.;
+const MAX_TOWER_LEVEL: u32 = 5;
 
 impl Tower {
     pub fn new(position: Vec2) -> Self {
@@ -24,6 +39,7 @@ impl Tower {
             projectiles: Vec::new(),
             pop_count: 0,
             level: 1,
+            spent: 0,
         }
     }
 
@@ -41,6 +57,10 @@ impl Tower {
         }
     }
 
+    pub fn get_position(&self) -> Vec2 {
+        self.position
+    }
+
     pub fn set_position(&mut self, position: Vec2) {
         self.position = position;
     }
@@ -53,6 +73,107 @@ impl Tower {
         self.angle = angle;
     }
 
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.position.distance(point) <= TOWER_SIZE
+    }
+
+    // Footprints are circles, so they overlap when closer than two radii.
+    pub fn overlaps(&self, position: Vec2) -> bool {
+        self.position.distance(position) < TOWER_SIZE * 2.
+    }
+
+    // Darts fly until they leave the world, so a tower reaches as far as the
+    // edge of the world in the direction it aims.
+    pub fn guides(&self) -> TowerGuides {
+        TowerGuides {
+            position: self.position,
+            angle: self.angle,
+            footprint: TOWER_SIZE,
+            range: distance_to_world_edge(self.position, Vec2::from_angle(self.angle)),
+            lane_width: PROJECTILE_SIZE * 2.,
+        }
+    }
+
+    pub fn can_upgrade(&self) -> bool {
+        self.level < MAX_TOWER_LEVEL
+    }
+
+    pub fn upgrade(&mut self) {
+        self.level += 1;
+    }
+
+    // Reverses an upgrade that cost the given amount.
+    pub fn downgrade(&mut self, cost: u32) {
+        self.level = self.level.saturating_sub(1).max(1);
+        self.spent = self.spent.saturating_sub(cost);
+    }
+
+    pub fn add_spent(&mut self, amount: u32) {
+        self.spent += amount;
+    }
+
+    pub fn get_sell_value(&self) -> u32 {
+        self.spent * 7 / 10
+    }
+
+    pub fn preview_snapshot(&self) -> PreviewTowerSnapshot {
+        PreviewTowerSnapshot {
+            position: self.position,
+            angle: self.angle,
+        }
+    }
+
+    pub fn snapshot(&self) -> TowerSnapshot {
+        TowerSnapshot {
+            position: self.position,
+            angle: self.angle,
+            level: self.level,
+            pop_count: self.pop_count,
+            spent: self.spent,
+            projectiles: self
+                .projectiles
+                .iter()
+                .map(|projectile| projectile.get_position())
+                .collect(),
+        }
+    }
+
+    pub fn encode(&self, writer: &mut SaveWriter) {
+        writer.write_vec2(self.position);
+        writer.write_f32(self.angle);
+        writer.write_f32(self.shot_cooldown);
+        writer.write_u32(self.pop_count);
+        writer.write_u32(self.level);
+        writer.write_u32(self.spent);
+        writer.write_u32(self.projectiles.len() as u32);
+
+        self.projectiles
+            .iter()
+            .for_each(|projectile| projectile.encode(writer));
+    }
+
+    pub fn decode(reader: &mut SaveReader) -> Result<Self, Error> {
+        let position = reader.read_vec2()?;
+        let angle = reader.read_f32()?;
+        let shot_cooldown = reader.read_f32()?;
+        let pop_count = reader.read_u32()?;
+        let level = reader.read_u32()?;
+        let spent = reader.read_u32()?;
+        let projectiles_count = reader.read_u32()?;
+
+        Ok(Self {
+            position,
+            angle,
+            shot_cooldown,
+            projectiles: (0..projectiles_count)
+                .map(|_| Projectile::decode(reader))
+                .collect::<Result<Vec<Projectile>, Error>>()?,
+            pop_count,
+            level,
+            spent,
+        })
+    }
+
     pub fn get_projectiles(&mut self) -> &mut Vec<Projectile> {
         &mut self.projectiles
     }
@@ -74,14 +195,16 @@ impl Tower {
     fn increase_pop_count(&mut self, pop_count: Option<u32>) {
         self.pop_count += pop_count.unwrap_or(1);
 
-        if self.pop_count % 10 == 0 {
+        if self.pop_count.is_multiple_of(10) {
             self.level += 1;
         }
     }
 }
 
 impl DrawableObject for Tower {
-    fn draw(&self, _sprite: Option<&Texture2D>, is_disabled: Option<bool>) {
+    fn draw(&self, sheet: Option<&SpriteSheet>, is_disabled: Option<bool>, alpha: f32) {
+        let sheet = sheet.unwrap();
+
         let color = if is_disabled.unwrap() {
             GRAY
         } else {
@@ -95,14 +218,33 @@ impl DrawableObject for Tower {
             }
         };
 
-        draw_circle(self.position.x, self.position.y, TOWER_SIZE, color);
-        draw_line(
-            self.position.x,
-            self.position.y,
-            self.position.x + self.angle.cos() * TOWER_SIZE * 2.,
-            self.position.y + self.angle.sin() * TOWER_SIZE * 2.,
-            2.,
+        // The attack plays after every shot, counted from the cooldown.
+        let time_since_shot = 2. / self.level as f32 - self.shot_cooldown;
+
+        let action = if is_action_playing(
+            sheet,
+            SpriteKind::Tower,
+            SpriteAction::Attack,
+            time_since_shot,
+        ) {
+            SpriteAction::Attack
+        } else {
+            SpriteAction::Idle
+        };
+
+        draw_sprite(
+            sheet,
+            SpriteKind::Tower,
+            action,
+            time_since_shot,
+            self.position,
+            TOWER_SIZE * 4.,
+            self.angle,
             color,
         );
+
+        for projectile in &self.projectiles {
+            projectile.draw(sheet, alpha);
+        }
     }
 }
@@ -0,0 +1,75 @@
+// Helpers for placing towers precisely: snapping to a grid, nudging the
+// preview with the arrow keys and rotating in fixed steps, all kept on the
+// map. The grid size
+// arrives with every tick's keys, so replays snap exactly as the recording
+// did.
+
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+
+use crate::input::Keys;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
+// 15 degrees.
+pub const ROTATION_STEP: f32 = PI / 12.;
+// How far a nudge moves the preview when not snapping, snapping moves it by
+// a whole cell instead.
+pub const NUDGE_DISTANCE: f32 = 1.;
+
+const GRID_COLOR: Color = Color::new(1., 1., 1., 0.15);
+
+pub fn snap_to_grid(position: Vec2, grid_size: Option<f32>) -> Vec2 {
+    match grid_size {
+        Some(size) if size > 0. => (position / size).round() * size,
+        _ => position,
+    }
+}
+
+// Snapping and nudging can carry the preview past the edge of the map, where
+// towers are never placed.
+pub fn clamp_to_world(position: Vec2) -> Vec2 {
+    position.clamp(Vec2::ZERO, Vec2::new(WORLD_WIDTH, WORLD_HEIGHT))
+}
+
+pub fn nudge_offset(keys: &Keys) -> Vec2 {
+    let distance = keys.grid_size.unwrap_or(NUDGE_DISTANCE);
+
+    [
+        (keys.nudge_left, Vec2::NEG_X),
+        (keys.nudge_right, Vec2::X),
+        (keys.nudge_up, Vec2::NEG_Y),
+        (keys.nudge_down, Vec2::Y),
+    ]
+    .iter()
+    .filter(|(state, _)| state.pressed)
+    .fold(Vec2::ZERO, |offset, (_, direction)| {
+        offset + *direction * distance
+    })
+}
+
+pub fn rotation_offset(keys: &Keys) -> f32 {
+    let steps = keys.rotate_tower_clockwise.pressed as i32
+        - keys.rotate_tower_counter_clockwise.pressed as i32;
+
+    steps as f32 * ROTATION_STEP
+}
+
+pub fn draw_grid(grid_size: f32) {
+    if grid_size <= 0. {
+        return;
+    }
+
+    (1..=(WORLD_WIDTH / grid_size) as usize).for_each(|column| {
+        let x = column as f32 * grid_size;
+
+        draw_line(x, 0., x, WORLD_HEIGHT, 1., GRID_COLOR);
+    });
+
+    (1..=(WORLD_HEIGHT / grid_size) as usize).for_each(|row| {
+        let y = row as f32 * grid_size;
+
+        draw_line(0., y, WORLD_WIDTH, y, 1., GRID_COLOR);
+    });
+}
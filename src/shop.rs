@@ -0,0 +1,198 @@
+// The sidebar listing the towers that can be bought. It sits to the right of
+// the world rather than over it, so it never hides balloons or takes room
+// from towers. Its layout is in interface coordinates, which match the world
+// before any zoom, so every implementation can tell whether a click landed on
+// it the same way, headless or not. Prices depend on the difficulty, which each
+// implementation scales itself, so they are passed in when drawing.
+
+use macroquad::prelude::*;
+
+use crate::input::Keys;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
+pub const SIDEBAR_WIDTH: f32 = 150.;
+
+const BUTTON_MARGIN: f32 = 10.;
+const BUTTON_HEIGHT: f32 = 60.;
+// Leaves room for the title above the buttons.
+const BUTTONS_TOP: f32 = 50.;
+const ICON_SIZE: f32 = 15.;
+const TOOLTIP_WIDTH: f32 = 260.;
+const TOOLTIP_LINE_HEIGHT: f32 = 22.;
+
+pub struct ShopItem {
+    pub name: &'static str,
+    pub color: Color,
+    // Shown in the tooltip, one line each.
+    pub stats: &'static [&'static str],
+}
+
+pub const SHOP_ITEMS: [ShopItem; 1] = [ShopItem {
+    name: "Dart Tower",
+    color: BLUE,
+    stats: &[
+        "Fires a dart every 2 seconds",
+        "where it is aimed.",
+        "Fires faster with every level,",
+        "up to level 5.",
+    ],
+}];
+
+pub fn sidebar_rect() -> Rect {
+    Rect::new(WORLD_WIDTH, 0., SIDEBAR_WIDTH, WORLD_HEIGHT)
+}
+
+pub fn is_on_sidebar(point: Vec2) -> bool {
+    sidebar_rect().contains(point)
+}
+
+pub fn shop_button_rect(index: usize) -> Rect {
+    let sidebar = sidebar_rect();
+
+    Rect::new(
+        sidebar.x + BUTTON_MARGIN,
+        BUTTONS_TOP + (BUTTON_HEIGHT + BUTTON_MARGIN) * index as f32,
+        sidebar.w - BUTTON_MARGIN * 2.,
+        BUTTON_HEIGHT,
+    )
+}
+
+pub fn shop_item_at(point: Vec2) -> Option<usize> {
+    (0..SHOP_ITEMS.len()).find(|index| shop_button_rect(*index).contains(point))
+}
+
+// The item whose button was pressed this tick, if any.
+pub fn clicked_shop_item(keys: &Keys) -> Option<usize> {
+    if !keys.place_tower.pressed {
+        return None;
+    }
+
+    shop_item_at(keys.interface_pointer_position)
+}
+
+// The item to start placing this tick, whether its button was clicked or it
+// was stepped to.
+pub fn chosen_shop_item(keys: &Keys) -> Option<usize> {
+    keys.select_shop_item.or_else(|| clicked_shop_item(keys))
+}
+
+// Steps forwards or backwards through the items, wrapping around at either
+// end. Nothing selected yet steps onto the first or the last item.
+pub fn step_shop_item(selected: Option<usize>, is_forward: bool) -> usize {
+    match (selected, is_forward) {
+        (None, true) => 0,
+        (None, false) => SHOP_ITEMS.len() - 1,
+        (Some(index), true) => (index + 1) % SHOP_ITEMS.len(),
+        (Some(index), false) => (index + SHOP_ITEMS.len() - 1) % SHOP_ITEMS.len(),
+    }
+}
+
+fn draw_shop_button(index: usize, price: u32, is_affordable: bool, is_hovered: bool) {
+    let item = &SHOP_ITEMS[index];
+    let button = shop_button_rect(index);
+
+    let (background, foreground, icon) = match (is_affordable, is_hovered) {
+        (false, _) => (Color::new(0.2, 0.2, 0.2, 0.8), GRAY, GRAY),
+        (true, true) => (Color::new(0.35, 0.35, 0.35, 0.9), WHITE, item.color),
+        (true, false) => (Color::new(0.25, 0.25, 0.25, 0.9), WHITE, item.color),
+    };
+
+    draw_rectangle(button.x, button.y, button.w, button.h, background);
+    draw_rectangle_lines(button.x, button.y, button.w, button.h, 2., foreground);
+
+    let icon_center = Vec2::new(
+        button.x + BUTTON_MARGIN + ICON_SIZE,
+        button.y + button.h / 2.,
+    );
+
+    draw_circle(icon_center.x, icon_center.y, ICON_SIZE, icon);
+    draw_line(
+        icon_center.x,
+        icon_center.y,
+        icon_center.x + ICON_SIZE * 1.5,
+        icon_center.y,
+        2.,
+        icon,
+    );
+
+    let text_x = icon_center.x + ICON_SIZE + BUTTON_MARGIN;
+
+    draw_text(item.name, text_x, button.y + 25., 18., foreground);
+    draw_text(
+        format!("${}", price).as_str(),
+        text_x,
+        button.y + 45.,
+        20.,
+        foreground,
+    );
+}
+
+// Drawn to the left of the hovered button so it does not cover the others.
+fn draw_tooltip(index: usize, price: u32, is_affordable: bool) {
+    let item = &SHOP_ITEMS[index];
+    let button = shop_button_rect(index);
+
+    let height = TOOLTIP_LINE_HEIGHT * (item.stats.len() + 2) as f32 + BUTTON_MARGIN;
+    let x = button.x - BUTTON_MARGIN * 2. - TOOLTIP_WIDTH;
+    let y = button.y;
+
+    draw_rectangle(x, y, TOOLTIP_WIDTH, height, Color::new(0., 0., 0., 0.85));
+
+    let price_line = if is_affordable {
+        format!("Price: ${}", price)
+    } else {
+        format!("Price: ${} (not enough coins)", price)
+    };
+
+    [item.name.to_string(), price_line]
+        .into_iter()
+        .chain(item.stats.iter().map(|line| line.to_string()))
+        .enumerate()
+        .for_each(|(line_index, line)| {
+            draw_text(
+                line.as_str(),
+                x + BUTTON_MARGIN,
+                y + TOOLTIP_LINE_HEIGHT * (line_index + 1) as f32,
+                20.,
+                WHITE,
+            );
+        });
+}
+
+// `coins` is None when resources are infinite, so everything is affordable.
+pub fn draw_shop(prices: &[u32], coins: Option<u32>, pointer_position: Vec2) {
+    let sidebar = sidebar_rect();
+
+    draw_rectangle(
+        sidebar.x,
+        sidebar.y,
+        sidebar.w,
+        sidebar.h,
+        Color::new(0.1, 0.1, 0.1, 0.75),
+    );
+
+    draw_text("SHOP", sidebar.x + BUTTON_MARGIN, 35., 32., WHITE);
+
+    let hovered_item = shop_item_at(pointer_position);
+    let is_affordable = |price: u32| coins.is_none_or(|coins| coins >= price);
+
+    prices
+        .iter()
+        .take(SHOP_ITEMS.len())
+        .enumerate()
+        .for_each(|(index, price)| {
+            draw_shop_button(
+                index,
+                *price,
+                is_affordable(*price),
+                hovered_item == Some(index),
+            );
+        });
+
+    if let Some(index) = hovered_item {
+        if let Some(price) = prices.get(index) {
+            draw_tooltip(index, *price, is_affordable(*price));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// The front end drives either implementation through this trait. Stepping
+// takes the simulation by value so the functional pipeline can pass its state
+// along, while the object oriented scene just updates itself and returns.
+
+use std::io::Error;
+use std::io::ErrorKind;
+
+use crate::events::GameEvent;
+use crate::input::Keys;
+use crate::snapshot::GameSnapshot;
+use crate::view::View;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Implementation {
+    Functional,
+    ObjectOriented,
+    BevyEcs,
+}
+
+pub const IMPLEMENTATIONS: [Implementation; 3] = [
+    Implementation::Functional,
+    Implementation::ObjectOriented,
+    Implementation::BevyEcs,
+];
+
+// The names --implementation accepts.
+pub fn implementation_name(implementation: Implementation) -> &'static str {
+    match implementation {
+        Implementation::Functional => "functional",
+        Implementation::ObjectOriented => "object-oriented",
+        Implementation::BevyEcs => "bevy-ecs",
+    }
+}
+
+pub fn parse_implementation(name: &str) -> Option<Implementation> {
+    IMPLEMENTATIONS
+        .into_iter()
+        .find(|implementation| implementation_name(*implementation) == name)
+}
+
+pub trait GameSimulation: Sized {
+    type Sprites;
+
+    fn step(self, delta_time: f32, keys: Keys) -> Self;
+
+    // The view only applies to the world, the interface is drawn over it.
+    fn render(&self, sprites: &Self::Sprites, alpha: f32, view: &View);
+
+    fn snapshot(&self) -> GameSnapshot;
+
+    // The events of the last step, for subscribers outside the simulation.
+    fn events(&self) -> &[GameEvent];
+
+    fn save(&self, _path: &str) -> Result<(), Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "Saving is not supported",
+        ))
+    }
+
+    fn load(_path: &str) -> Result<Self, Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "Loading is not supported",
+        ))
+    }
+}
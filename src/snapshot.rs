@@ -0,0 +1,60 @@
+// A plain copy of the gameplay state that both implementations can produce,
+// so they can be compared without knowing each other's types.
+
+use macroquad::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TowerSnapshot {
+    pub position: Vec2,
+    pub angle: f32,
+    pub level: u32,
+    pub pop_count: u32,
+    pub spent: u32,
+    pub projectiles: Vec<Vec2>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PreviewTowerSnapshot {
+    pub position: Vec2,
+    pub angle: f32,
+}
+
+// The tower being placed, and how the pointer and the arrow keys move it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacementSnapshot {
+    pub is_placing_tower: bool,
+    pub preview_tower: Option<PreviewTowerSnapshot>,
+    pub drag_start: Option<Vec2>,
+    pub nudged_position: Option<Vec2>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScreenSnapshot {
+    MainMenu,
+    MapSelect,
+    DifficultySelect,
+    GameModeSelect,
+    BetweenRounds,
+    Playing,
+    Paused,
+    Victory,
+    Defeat,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSnapshot {
+    pub screen: ScreenSnapshot,
+    pub game_speed: u32,
+    // The options picked on the menus, by index, as each implementation has
+    // its own types for them.
+    pub map: usize,
+    pub difficulty: usize,
+    pub game_mode: usize,
+    pub coins: u32,
+    pub lives: i32,
+    pub round: u32,
+    pub balloons_to_spawn: u32,
+    pub balloons: Vec<Vec2>,
+    pub towers: Vec<TowerSnapshot>,
+    pub placement: PlacementSnapshot,
+}
@@ -0,0 +1,55 @@
+// Runs the simulation at a fixed tick rate however often frames are drawn, by
+// banking the time of each frame until it adds up to whole ticks.
+
+use crate::input::held_keys;
+use crate::input::merge_keys;
+use crate::input::Keys;
+
+pub const FIXED_DELTA_TIME: f32 = 1. / 60.;
+// Long stalls, such as dragging the window, are not caught up on in full.
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+#[derive(Clone)]
+pub struct Timestep {
+    // Frame time not yet spent on ticks.
+    pub accumulator: f32,
+    // Input from frames since the last tick ran.
+    pub pending_keys: Option<Keys>,
+}
+
+pub fn new_timestep() -> Timestep {
+    Timestep {
+        accumulator: 0.,
+        pending_keys: None,
+    }
+}
+
+// Adds a frame and its input, and returns the input for each tick now due. The
+// first tick gets everything pressed since the last one, the rest only what is
+// still held.
+pub fn advance_timestep(timestep: Timestep, frame_time: f32, keys: Keys) -> (Timestep, Vec<Keys>) {
+    let mut accumulator = timestep.accumulator + frame_time.min(MAX_FRAME_TIME);
+    let mut pending_keys = Some(match timestep.pending_keys {
+        Some(pending_keys) => merge_keys(pending_keys, keys.clone()),
+        None => keys.clone(),
+    });
+    let mut ticks = Vec::new();
+
+    while accumulator >= FIXED_DELTA_TIME {
+        ticks.push(pending_keys.take().unwrap_or_else(|| held_keys(&keys)));
+        accumulator -= FIXED_DELTA_TIME;
+    }
+
+    (
+        Timestep {
+            accumulator,
+            pending_keys,
+        },
+        ticks,
+    )
+}
+
+// How far the next tick is along, for drawing between the last two.
+pub fn timestep_alpha(timestep: &Timestep) -> f32 {
+    timestep.accumulator / FIXED_DELTA_TIME
+}
@@ -0,0 +1,216 @@
+// Maps the fixed logical world onto the window. The world and the sidebar
+// beside it form a frame that is scaled to fit and centred, with bars along
+// whichever sides of the window are too long for it, so resizing the window
+// never moves anything within the world. The window size is passed in so the
+// mapping can be used without a window.
+//
+// Inside that frame the world is seen through a view that can be zoomed and
+// panned, while the interface is always drawn over the whole frame unzoomed.
+
+use macroquad::prelude::*;
+
+use crate::shop::SIDEBAR_WIDTH;
+use crate::world::WORLD_HEIGHT;
+use crate::world::WORLD_WIDTH;
+
+// The interface spans the world and the sidebar to its right.
+pub const INTERFACE_WIDTH: f32 = WORLD_WIDTH + SIDEBAR_WIDTH;
+pub const INTERFACE_HEIGHT: f32 = WORLD_HEIGHT;
+
+const LETTERBOX_COLOR: Color = BLACK;
+
+pub const MIN_ZOOM: f32 = 1.;
+pub const MAX_ZOOM: f32 = 4.;
+// How much one notch of the mouse wheel zooms by.
+pub const ZOOM_STEP: f32 = 1.25;
+
+// How close to the edge of the frame, in window pixels, the pointer pans.
+pub const EDGE_PAN_MARGIN: f32 = 10.;
+// In world units per second at no zoom.
+pub const EDGE_PAN_SPEED: f32 = 400.;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct View {
+    // The world position shown in the middle of the frame.
+    pub center: Vec2,
+    pub zoom: f32,
+}
+
+pub const DEFAULT_VIEW: View = View {
+    center: Vec2::new(WORLD_WIDTH / 2., WORLD_HEIGHT / 2.),
+    zoom: 1.,
+};
+
+// The area the view is kept within.
+pub fn world_bounds() -> Rect {
+    Rect::new(0., 0., WORLD_WIDTH, WORLD_HEIGHT)
+}
+
+// The part of the window the interface is drawn into, in window pixels.
+pub fn interface_viewport(screen_size: Vec2) -> Rect {
+    let scale = (screen_size.x / INTERFACE_WIDTH).min(screen_size.y / INTERFACE_HEIGHT);
+    let size = Vec2::new(INTERFACE_WIDTH, INTERFACE_HEIGHT) * scale;
+    let origin = (screen_size - size) / 2.;
+
+    Rect::new(origin.x, origin.y, size.x, size.y)
+}
+
+// How many window pixels one world unit covers at no zoom.
+pub fn world_scale(screen_size: Vec2) -> f32 {
+    interface_viewport(screen_size).w / INTERFACE_WIDTH
+}
+
+// The part of the window the world is drawn into, the left of the frame.
+pub fn world_viewport(screen_size: Vec2) -> Rect {
+    let frame = interface_viewport(screen_size);
+    let scale = world_scale(screen_size);
+
+    Rect::new(frame.x, frame.y, WORLD_WIDTH * scale, WORLD_HEIGHT * scale)
+}
+
+pub fn screen_to_world(point: Vec2, screen_size: Vec2, view: &View) -> Vec2 {
+    let viewport = world_viewport(screen_size);
+    let frame_position = (point - viewport.point()) / world_scale(screen_size);
+
+    view.center + (frame_position - DEFAULT_VIEW.center) / view.zoom
+}
+
+// The interface is drawn unzoomed, so over the world it lines up with the
+// world at the default view.
+pub fn screen_to_interface(point: Vec2, screen_size: Vec2) -> Vec2 {
+    (point - interface_viewport(screen_size).point()) / world_scale(screen_size)
+}
+
+// Zooms by the given number of steps, out when negative, keeping the anchor
+// where it is on screen.
+pub fn zoom_view(view: View, steps: f32, anchor: Vec2) -> View {
+    let zoom = (view.zoom * ZOOM_STEP.powf(steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+
+    View {
+        center: anchor + (view.center - anchor) * view.zoom / zoom,
+        zoom,
+    }
+}
+
+pub fn pan_view(view: View, offset: Vec2) -> View {
+    View {
+        center: view.center + offset,
+        ..view
+    }
+}
+
+// Keeps everything shown inside the bounds, zooming in if the bounds are
+// smaller than the frame.
+pub fn clamp_view(view: View, bounds: Rect) -> View {
+    let zoom = view
+        .zoom
+        .max(WORLD_WIDTH / bounds.w)
+        .max(WORLD_HEIGHT / bounds.h)
+        .clamp(MIN_ZOOM, MAX_ZOOM);
+    let half_size = DEFAULT_VIEW.center / zoom;
+
+    View {
+        center: view.center.clamp(
+            bounds.point() + half_size,
+            bounds.point() + bounds.size() - half_size,
+        ),
+        zoom,
+    }
+}
+
+// Which way to pan while the pointer rests against the edges of the world,
+// inside them so that the sidebar next to it can be used without panning.
+pub fn edge_pan_direction(point: Vec2, screen_size: Vec2) -> Vec2 {
+    let viewport = world_viewport(screen_size);
+
+    if !viewport.contains(point) {
+        return Vec2::ZERO;
+    }
+
+    let axis = |position: f32, start: f32, end: f32| {
+        if position < start + EDGE_PAN_MARGIN {
+            -1.
+        } else if position > end - EDGE_PAN_MARGIN {
+            1.
+        } else {
+            0.
+        }
+    };
+
+    Vec2::new(
+        axis(point.x, viewport.left(), viewport.right()),
+        axis(point.y, viewport.top(), viewport.bottom()),
+    )
+}
+
+fn camera_viewport(viewport: Rect) -> Option<(i32, i32, i32, i32)> {
+    Some((
+        viewport.x.round() as i32,
+        viewport.y.round() as i32,
+        viewport.w.round() as i32,
+        viewport.h.round() as i32,
+    ))
+}
+
+// Draws the world as seen through the view, with y pointing down like the
+// default camera. The viewport is measured from the bottom of the window,
+// which is the same as from the top as the frame is centred and the world
+// fills its height.
+pub fn view_camera(screen_size: Vec2, view: &View) -> Camera2D {
+    Camera2D {
+        target: view.center,
+        zoom: Vec2::new(2. / WORLD_WIDTH, 2. / WORLD_HEIGHT) * view.zoom,
+        viewport: camera_viewport(world_viewport(screen_size)),
+        ..Default::default()
+    }
+}
+
+pub fn interface_camera(screen_size: Vec2) -> Camera2D {
+    Camera2D {
+        target: Vec2::new(INTERFACE_WIDTH, INTERFACE_HEIGHT) / 2.,
+        zoom: Vec2::new(2. / INTERFACE_WIDTH, 2. / INTERFACE_HEIGHT),
+        viewport: camera_viewport(interface_viewport(screen_size)),
+        ..Default::default()
+    }
+}
+
+fn screen_size() -> Vec2 {
+    Vec2::new(screen_width(), screen_height())
+}
+
+// For drawing the world, before switching back to the interface.
+pub fn set_view_camera(view: &View) {
+    set_camera(&view_camera(screen_size(), view));
+}
+
+pub fn set_interface_camera() {
+    set_camera(&interface_camera(screen_size()));
+}
+
+// Covers whatever the scene drew outside the frame. Drawn with the default
+// camera, after the scene.
+pub fn draw_letterbox(screen_size: Vec2) {
+    let viewport = interface_viewport(screen_size);
+
+    if viewport.x > 0. {
+        draw_rectangle(0., 0., viewport.x, screen_size.y, LETTERBOX_COLOR);
+        draw_rectangle(
+            viewport.right(),
+            0.,
+            screen_size.x - viewport.right(),
+            screen_size.y,
+            LETTERBOX_COLOR,
+        );
+    }
+
+    if viewport.y > 0. {
+        draw_rectangle(0., 0., screen_size.x, viewport.y, LETTERBOX_COLOR);
+        draw_rectangle(
+            0.,
+            viewport.bottom(),
+            screen_size.x,
+            screen_size.y - viewport.bottom(),
+            LETTERBOX_COLOR,
+        );
+    }
+}
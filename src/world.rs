@@ -0,0 +1,30 @@
+// The simulation runs in a fixed logical space rather than the window size,
+// so it behaves the same headless and at any window size. The default
+// matches macroquad's default window.
+
+use macroquad::prelude::*;
+
+pub const WORLD_WIDTH: f32 = 800.;
+pub const WORLD_HEIGHT: f32 = 600.;
+
+pub fn is_in_world(point: Vec2) -> bool {
+    (0. ..=WORLD_WIDTH).contains(&point.x) && (0. ..=WORLD_HEIGHT).contains(&point.y)
+}
+
+// How far something heading in the direction travels from the point before it
+// leaves the world.
+pub fn distance_to_world_edge(point: Vec2, direction: Vec2) -> f32 {
+    let distance_along = |position: f32, direction: f32, size: f32| {
+        if direction > 0. {
+            (size - position) / direction
+        } else if direction < 0. {
+            -position / direction
+        } else {
+            f32::INFINITY
+        }
+    };
+
+    distance_along(point.x, direction.x, WORLD_WIDTH)
+        .min(distance_along(point.y, direction.y, WORLD_HEIGHT))
+        .max(0.)
+}
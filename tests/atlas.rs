@@ -0,0 +1,130 @@
+// Checks that the atlas and sprite files are read into the frames and
+// animations they describe, that mistakes in them are reported, and that
+// animations pick the right frame as they play.
+
+use std::fs;
+
+use macroquad::prelude::*;
+
+use bloonstd7::atlas::animation_frame;
+use bloonstd7::atlas::animation_length;
+use bloonstd7::atlas::decode_atlas;
+use bloonstd7::atlas::decode_sprite_mapping;
+use bloonstd7::atlas::sprite_animation;
+use bloonstd7::atlas::Playback;
+use bloonstd7::atlas::SpriteAction;
+use bloonstd7::atlas::SpriteKind;
+use bloonstd7::atlas::SPRITE_KINDS;
+
+const ATLAS: &str = "
+# Two frames of a tower.
+frame tower_0 = 0, 0, 64, 64
+frame tower_1 = 64, 0, 64, 64
+
+animation idle = 0.5, loop, tower_0, tower_1
+animation attack = 0.1, once, tower_1, tower_0
+";
+
+const SPRITES: &str = "
+tower.idle = idle
+tower.attack = attack
+";
+
+#[test]
+fn atlases_are_read_into_frames_and_animations() {
+    let atlas = decode_atlas(ATLAS).unwrap();
+
+    assert_eq!(atlas.frames.len(), 2);
+    assert_eq!(atlas.frames[1].rect, Rect::new(64., 0., 64., 64.));
+
+    let attack = &atlas.animations[1];
+
+    assert_eq!(attack.name, "attack");
+    assert_eq!(attack.frame_duration, 0.1);
+    assert_eq!(attack.playback, Playback::Once);
+    assert_eq!(
+        attack.frames,
+        vec![Rect::new(64., 0., 64., 64.), Rect::new(0., 0., 64., 64.)]
+    );
+}
+
+#[test]
+fn broken_atlases_are_rejected() {
+    // Frames are used before they are declared.
+    assert!(decode_atlas("animation idle = 1, loop, tower_0\nframe tower_0 = 0, 0, 1, 1").is_err());
+    assert!(decode_atlas("frame tower_0 = 0, 0, 64").is_err());
+    assert!(decode_atlas("frame tower_0 = 0, 0, 1, 1\nanimation idle = 0, loop, tower_0").is_err());
+    assert!(
+        decode_atlas("frame tower_0 = 0, 0, 1, 1\nanimation idle = 1, forever, tower_0").is_err()
+    );
+    assert!(decode_atlas("animation idle = 1, loop").is_err());
+    assert!(decode_atlas("sound pop = pop.wav").is_err());
+}
+
+#[test]
+fn sprites_are_mapped_to_animations() {
+    let atlas = decode_atlas(ATLAS).unwrap();
+    let mapping = decode_sprite_mapping(SPRITES, &atlas).unwrap();
+
+    assert_eq!(
+        sprite_animation(&atlas, &mapping, SpriteKind::Tower, SpriteAction::Attack)
+            .map(|animation| animation.name.as_str()),
+        Some("attack")
+    );
+    assert_eq!(
+        sprite_animation(&atlas, &mapping, SpriteKind::Balloon, SpriteAction::Idle),
+        None
+    );
+}
+
+#[test]
+fn broken_sprite_mappings_are_rejected() {
+    let atlas = decode_atlas(ATLAS).unwrap();
+
+    assert!(decode_sprite_mapping("tower.idle = walk", &atlas).is_err());
+    assert!(decode_sprite_mapping("tank.idle = idle", &atlas).is_err());
+    assert!(decode_sprite_mapping("tower.dance = idle", &atlas).is_err());
+    assert!(decode_sprite_mapping("tower = idle", &atlas).is_err());
+}
+
+#[test]
+fn looping_animations_start_over() {
+    let atlas = decode_atlas(ATLAS).unwrap();
+    let idle = &atlas.animations[0];
+
+    assert_eq!(animation_length(idle), 1.);
+    assert_eq!(animation_frame(idle, 0.25), idle.frames[0]);
+    assert_eq!(animation_frame(idle, 0.75), idle.frames[1]);
+    assert_eq!(animation_frame(idle, 1.25), idle.frames[0]);
+}
+
+#[test]
+fn one_off_animations_stop_on_their_last_frame() {
+    let atlas = decode_atlas(ATLAS).unwrap();
+    let attack = &atlas.animations[1];
+
+    assert_eq!(animation_frame(attack, 0.05), attack.frames[0]);
+    assert_eq!(animation_frame(attack, 0.15), attack.frames[1]);
+    assert_eq!(animation_frame(attack, 10.), attack.frames[1]);
+}
+
+#[test]
+fn the_shipped_sprites_cover_every_sprite() {
+    let atlas = decode_atlas(&fs::read_to_string("resources/sprites/atlas.cfg").unwrap()).unwrap();
+    let mapping = decode_sprite_mapping(
+        &fs::read_to_string("resources/sprites/sprites.cfg").unwrap(),
+        &atlas,
+    )
+    .unwrap();
+
+    SPRITE_KINDS.iter().for_each(|kind| {
+        assert!(
+            sprite_animation(&atlas, &mapping, *kind, SpriteAction::Idle).is_some(),
+            "{:?} has no idle animation",
+            kind
+        );
+    });
+
+    assert!(sprite_animation(&atlas, &mapping, SpriteKind::Tower, SpriteAction::Attack).is_some());
+    assert!(sprite_animation(&atlas, &mapping, SpriteKind::Balloon, SpriteAction::Pop).is_some());
+}
@@ -0,0 +1,171 @@
+// Checks that bindings survive being written to and read back from the config
+// file format, that broken files are reported instead of guessed at, and that
+// rebinding swaps inputs per device and flags inputs shared between actions.
+// Also checks the default bindings, where no input is shared and the shoulder
+// buttons step through the shop.
+
+use macroquad::prelude::*;
+
+use bloonstd7::bindings::action_inputs;
+use bloonstd7::bindings::bind;
+use bloonstd7::bindings::conflicting_actions;
+use bloonstd7::bindings::decode_bindings;
+use bloonstd7::bindings::default_bindings;
+use bloonstd7::bindings::encode_bindings;
+use bloonstd7::bindings::rebind;
+use bloonstd7::bindings::Action;
+use bloonstd7::bindings::GamepadButton;
+use bloonstd7::bindings::Input;
+use bloonstd7::bindings::ACTIONS;
+use bloonstd7::bindings::OPTION_ACTIONS;
+
+#[test]
+fn bindings_round_trip_through_the_config_format() {
+    let bindings = bind(
+        default_bindings(),
+        Action::PlaceTower,
+        vec![
+            Input::Key(KeyCode::Enter),
+            Input::Gamepad(GamepadButton::South),
+        ],
+    );
+
+    let decoded = decode_bindings(&encode_bindings(&bindings)).unwrap();
+
+    ACTIONS.iter().for_each(|action| {
+        assert_eq!(
+            action_inputs(&decoded, *action),
+            action_inputs(&bindings, *action)
+        );
+    });
+}
+
+#[test]
+fn missing_actions_keep_their_defaults() {
+    let bindings = decode_bindings("# comment\n\nsell_tower = Key.X, Mouse.Right\n").unwrap();
+
+    assert_eq!(
+        action_inputs(&bindings, Action::SellTower),
+        &[Input::Key(KeyCode::X), Input::Mouse(MouseButton::Right)]
+    );
+    assert_eq!(
+        action_inputs(&bindings, Action::SpawnBalloon),
+        &[Input::Key(KeyCode::B)]
+    );
+}
+
+#[test]
+fn unknown_actions_and_inputs_are_rejected() {
+    assert!(decode_bindings("fly = Key.T\n").is_err());
+    assert!(decode_bindings("pause = Key.Nope\n").is_err());
+    assert!(decode_bindings("pause Key.P\n").is_err());
+}
+
+#[test]
+fn rebinding_replaces_the_input_from_the_same_device() {
+    let bindings = rebind(
+        default_bindings(),
+        Action::PlaceTower,
+        Input::Gamepad(GamepadButton::RightTrigger),
+    );
+
+    assert_eq!(
+        action_inputs(&bindings, Action::PlaceTower),
+        &[
+            Input::Mouse(MouseButton::Left),
+            Input::Gamepad(GamepadButton::RightTrigger),
+        ]
+    );
+
+    // The keyboard and mouse count as one device.
+    let bindings = rebind(bindings, Action::PlaceTower, Input::Key(KeyCode::Enter));
+
+    assert_eq!(
+        action_inputs(&bindings, Action::PlaceTower),
+        &[
+            Input::Key(KeyCode::Enter),
+            Input::Gamepad(GamepadButton::RightTrigger),
+        ]
+    );
+
+    let bindings = rebind(
+        bindings,
+        Action::PlaceTower,
+        Input::Mouse(MouseButton::Right),
+    );
+
+    assert_eq!(
+        action_inputs(&bindings, Action::PlaceTower),
+        &[
+            Input::Mouse(MouseButton::Right),
+            Input::Gamepad(GamepadButton::RightTrigger),
+        ]
+    );
+}
+
+#[test]
+fn no_default_input_is_shared_between_actions() {
+    let bindings = default_bindings();
+
+    ACTIONS.iter().for_each(|action| {
+        assert_eq!(
+            conflicting_actions(&bindings, *action),
+            vec![],
+            "{:?}",
+            action
+        );
+    });
+}
+
+#[test]
+fn menu_options_can_be_rebound() {
+    assert_eq!(
+        action_inputs(&default_bindings(), OPTION_ACTIONS[0]),
+        &[Input::Key(KeyCode::Key1)]
+    );
+
+    let bindings = decode_bindings("select_option_2 = Key.Kp2, Gamepad.DPadDown\n").unwrap();
+
+    assert_eq!(
+        action_inputs(&bindings, Action::SelectOption2),
+        &[
+            Input::Key(KeyCode::Kp2),
+            Input::Gamepad(GamepadButton::DPadDown),
+        ]
+    );
+}
+
+#[test]
+fn inputs_bound_to_other_actions_are_flagged() {
+    assert!(conflicting_actions(&default_bindings(), Action::SpawnBalloon).is_empty());
+
+    let bindings = rebind(
+        default_bindings(),
+        Action::SpawnBalloon,
+        Input::Key(KeyCode::N),
+    );
+
+    assert_eq!(
+        conflicting_actions(&bindings, Action::SpawnBalloon),
+        vec![Action::StepFrame]
+    );
+    assert_eq!(
+        conflicting_actions(&bindings, Action::StepFrame),
+        vec![Action::SpawnBalloon]
+    );
+}
+
+#[test]
+fn shoulder_buttons_step_through_the_shop() {
+    let bindings = default_bindings();
+
+    assert_eq!(
+        action_inputs(&bindings, Action::PreviousShopItem),
+        &[Input::Gamepad(GamepadButton::LeftShoulder)]
+    );
+    assert_eq!(
+        action_inputs(&bindings, Action::NextShopItem),
+        &[Input::Gamepad(GamepadButton::RightShoulder)]
+    );
+    assert!(conflicting_actions(&bindings, Action::NextShopItem).is_empty());
+}
@@ -0,0 +1,189 @@
+// Checks that the difficulty picked on the menu sets the starting cash and
+// lives, scales what towers and upgrades cost and how fast balloons fly, in
+// every implementation.
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::balloon::BALLOON_SPEED;
+use bloonstd7::functional::difficulty::balloon_speed_multiplier;
+use bloonstd7::functional::difficulty::scale_price;
+use bloonstd7::functional::difficulty::starting_cash;
+use bloonstd7::functional::difficulty::starting_lives;
+use bloonstd7::functional::difficulty::Difficulty;
+use bloonstd7::functional::difficulty::DIFFICULTIES;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::TOWER_COST;
+use bloonstd7::functional::scene::UPGRADE_COST;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const EASY: usize = 0;
+const IMPOPPABLE: usize = 3;
+const STANDARD: usize = 0;
+
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+const FLIGHT_TICKS: usize = 30;
+
+fn start_game<S: GameSimulation>(simulation: S, difficulty: usize) -> S {
+    [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            select_difficulty: Some(difficulty),
+            ..NO_KEYS
+        },
+        Keys {
+            select_game_mode: Some(STANDARD),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(simulation, |simulation, keys| {
+        simulation.step(DELTA_TIME, keys)
+    })
+}
+
+fn at_tower(keys: Keys) -> Keys {
+    Keys {
+        pointer_position: TOWER_POSITION,
+        interface_pointer_position: TOWER_POSITION,
+        ..keys
+    }
+}
+
+fn place_tower<S: GameSimulation>(simulation: S) -> S {
+    simulation
+        .step(
+            DELTA_TIME,
+            at_tower(Keys {
+                tower_placement: PRESSED,
+                ..NO_KEYS
+            }),
+        )
+        .step(
+            DELTA_TIME,
+            at_tower(Keys {
+                place_tower: CLICKED,
+                ..NO_KEYS
+            }),
+        )
+}
+
+fn assert_starting_resources<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    DIFFICULTIES
+        .iter()
+        .enumerate()
+        .for_each(|(index, difficulty)| {
+            let snapshot = start_game(new_simulation(0), index).snapshot();
+
+            assert_eq!(snapshot.difficulty, index);
+            assert_eq!(snapshot.coins, starting_cash(*difficulty));
+            assert_eq!(snapshot.lives, starting_lives(*difficulty));
+        });
+}
+
+fn assert_scaled_prices<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    DIFFICULTIES
+        .iter()
+        .enumerate()
+        .for_each(|(index, difficulty)| {
+            let snapshot = place_tower(start_game(new_simulation(0), index)).snapshot();
+            let tower_cost = scale_price(*difficulty, TOWER_COST);
+
+            assert_eq!(snapshot.towers[0].spent, tower_cost);
+            assert_eq!(snapshot.coins, starting_cash(*difficulty) - tower_cost);
+        });
+
+    let snapshot = place_tower(start_game(new_simulation(0), EASY))
+        .step(
+            DELTA_TIME,
+            at_tower(Keys {
+                upgrade_tower: PRESSED,
+                ..NO_KEYS
+            }),
+        )
+        .snapshot();
+
+    assert_eq!(snapshot.towers[0].level, 2);
+    assert_eq!(
+        snapshot.towers[0].spent,
+        scale_price(Difficulty::Easy, TOWER_COST) + scale_price(Difficulty::Easy, UPGRADE_COST)
+    );
+}
+
+// How far the first balloon flies over a few ticks once it has spawned.
+fn balloon_flight<S: GameSimulation>(new_simulation: fn(u64) -> S, difficulty: usize) -> f32 {
+    let mut simulation = start_game(new_simulation(0), difficulty).step(
+        DELTA_TIME,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    while simulation.snapshot().balloons.is_empty() {
+        simulation = simulation.step(DELTA_TIME, NO_KEYS);
+    }
+
+    let start = simulation.snapshot().balloons[0];
+    let simulation = (0..FLIGHT_TICKS).fold(simulation, |simulation, _| {
+        simulation.step(DELTA_TIME, NO_KEYS)
+    });
+
+    simulation.snapshot().balloons[0].x - start.x
+}
+
+fn assert_balloon_speeds<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    [EASY, IMPOPPABLE].into_iter().for_each(|index| {
+        let expected = BALLOON_SPEED
+            * balloon_speed_multiplier(DIFFICULTIES[index])
+            * FLIGHT_TICKS as f32
+            * DELTA_TIME;
+
+        assert!((balloon_flight(new_simulation, index) - expected).abs() < 0.01);
+    });
+}
+
+#[test]
+fn prices_scale_with_the_difficulty() {
+    let prices: Vec<u32> = DIFFICULTIES
+        .iter()
+        .map(|difficulty| scale_price(*difficulty, TOWER_COST))
+        .collect();
+
+    assert_eq!(prices, vec![13, 15, 16, 18]);
+}
+
+#[test]
+fn difficulties_set_the_starting_cash_and_lives() {
+    assert_starting_resources(new_scene);
+    assert_starting_resources(Scene::new);
+    assert_starting_resources(EcsScene::new);
+}
+
+#[test]
+fn towers_and_upgrades_cost_the_scaled_price() {
+    assert_scaled_prices(new_scene);
+    assert_scaled_prices(Scene::new);
+    assert_scaled_prices(EcsScene::new);
+}
+
+#[test]
+fn harder_difficulties_send_faster_balloons() {
+    assert_balloon_speeds(new_scene);
+    assert_balloon_speeds(Scene::new);
+    assert_balloon_speeds(EcsScene::new);
+}
@@ -0,0 +1,311 @@
+// Checks that the scenes report what happened in a step as events and that
+// subscribers receive them.
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::events::publish;
+use bloonstd7::events::GameEvent;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::GameState;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+// Side by side, as close as the footprints allow, so a balloon between them
+// is in both of their lanes.
+const LEFT_TOWER_POSITION: Vec2 = Vec2::new(250., 200.);
+const RIGHT_TOWER_POSITION: Vec2 = Vec2::new(350., 200.);
+
+fn new_game() -> GameState {
+    start_game(new_scene(0))
+}
+
+fn start_game<S: GameSimulation>(simulation: S) -> S {
+    [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Medium
+            select_difficulty: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Standard
+            select_game_mode: Some(0),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(simulation, |simulation, keys| {
+        simulation.step(DELTA_TIME, keys)
+    })
+}
+
+// Places a tower aimed straight down across the balloon path.
+fn place_tower_aimed_down<S: GameSimulation>(simulation: S, position: Vec2) -> S {
+    let aim = (0..6).map(|_| Keys {
+        rotate_tower_clockwise: PRESSED,
+        ..NO_KEYS
+    });
+
+    [Keys {
+        tower_placement: PRESSED,
+        ..NO_KEYS
+    }]
+    .into_iter()
+    .chain(aim)
+    .chain([Keys {
+        place_tower: CLICKED,
+        ..NO_KEYS
+    }])
+    .fold(simulation, |simulation, keys| {
+        simulation.step(
+            DELTA_TIME,
+            Keys {
+                pointer_position: position,
+                interface_pointer_position: position,
+                ..keys
+            },
+        )
+    })
+}
+
+fn popped_balloons(events: &[GameEvent]) -> usize {
+    events
+        .iter()
+        .filter(|event| matches!(event, GameEvent::BalloonPopped { .. }))
+        .count()
+}
+
+// Towers only fire during a round, so both start firing together and their
+// darts fly level. The first balloon is between their lanes when the second
+// pair of darts comes down, so both reach it on the same tick. Only one of
+// them pops it.
+fn assert_two_darts_pop_a_balloon_once<S: GameSimulation>(simulation: S) {
+    let simulation = place_tower_aimed_down(start_game(simulation), LEFT_TOWER_POSITION);
+    let mut simulation = place_tower_aimed_down(simulation, RIGHT_TOWER_POSITION).step(
+        DELTA_TIME,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    while popped_balloons(simulation.events()) == 0 {
+        simulation = simulation.step(DELTA_TIME, NO_KEYS);
+    }
+
+    let snapshot = simulation.snapshot();
+    let darts = snapshot
+        .towers
+        .iter()
+        .map(|tower| tower.projectiles.len())
+        .sum::<usize>();
+
+    assert_eq!(popped_balloons(simulation.events()), 1);
+    assert_eq!(
+        snapshot
+            .towers
+            .iter()
+            .map(|tower| tower.pop_count)
+            .sum::<u32>(),
+        1
+    );
+    // The dart that missed flies on.
+    assert_eq!(darts, 1);
+    assert!(!simulation
+        .events()
+        .iter()
+        .any(|event| matches!(event, GameEvent::BalloonEscaped { .. })));
+}
+
+fn place_tower(state: GameState) -> GameState {
+    state
+        .step(
+            DELTA_TIME,
+            Keys {
+                tower_placement: PRESSED,
+                pointer_position: TOWER_POSITION,
+                ..NO_KEYS
+            },
+        )
+        .step(
+            DELTA_TIME,
+            Keys {
+                place_tower: CLICKED,
+                pointer_position: TOWER_POSITION,
+                ..NO_KEYS
+            },
+        )
+}
+
+#[test]
+fn placing_a_tower_is_reported_for_the_step_only() {
+    let state = place_tower(new_game());
+
+    assert_eq!(
+        state.events(),
+        &[GameEvent::TowerPlaced {
+            position: TOWER_POSITION,
+            cost: 15,
+        }]
+    );
+
+    assert!(state.step(DELTA_TIME, NO_KEYS).events().is_empty());
+}
+
+#[test]
+fn selling_a_tower_refunds_it_through_the_economy() {
+    let state = place_tower(new_game());
+    let coins = state.snapshot().coins;
+
+    let state = state.step(
+        DELTA_TIME,
+        Keys {
+            sell_tower: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(
+        state.events(),
+        &[GameEvent::TowerSold {
+            position: TOWER_POSITION,
+            refund: 10,
+        }]
+    );
+    assert_eq!(state.snapshot().coins, coins + 10);
+}
+
+#[test]
+fn undoing_a_placement_refunds_it_in_full() {
+    let state = new_game();
+    let coins = state.snapshot().coins;
+
+    let state = place_tower(state).step(
+        DELTA_TIME,
+        Keys {
+            undo: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(
+        state.events(),
+        &[GameEvent::TowerPlacementUndone {
+            position: TOWER_POSITION,
+            refund: 15,
+        }]
+    );
+    assert_eq!(state.snapshot().coins, coins);
+    assert!(state.snapshot().towers.is_empty());
+}
+
+#[test]
+fn towers_placed_during_a_round_cannot_be_undone() {
+    let undo = Keys {
+        undo: PRESSED,
+        ..NO_KEYS
+    };
+    // A dart pops one balloon, so it takes two towers to pop the round.
+    let state = place_tower_aimed_down(new_game(), RIGHT_TOWER_POSITION + Vec2::X * 100.)
+        .step(
+            DELTA_TIME,
+            Keys {
+                start_round: PRESSED,
+                ..NO_KEYS
+            },
+        )
+        .step(
+            DELTA_TIME,
+            Keys {
+                tower_placement: PRESSED,
+                pointer_position: TOWER_POSITION,
+                ..NO_KEYS
+            },
+        );
+
+    // Aimed straight down across the balloon path.
+    let state = (0..6).fold(state, |state, _| {
+        state.step(
+            DELTA_TIME,
+            Keys {
+                rotate_tower_clockwise: PRESSED,
+                pointer_position: TOWER_POSITION,
+                ..NO_KEYS
+            },
+        )
+    });
+    let state = state.step(
+        DELTA_TIME,
+        Keys {
+            place_tower: CLICKED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+    );
+
+    let state = state.step(DELTA_TIME, undo.clone());
+
+    assert!(!state
+        .events()
+        .iter()
+        .any(|event| matches!(event, GameEvent::TowerPlacementUndone { .. })));
+    assert_eq!(state.snapshot().towers.len(), 2);
+
+    // Nor once the round is over.
+    let state = (0..3000).fold(state, |state, _| state.step(DELTA_TIME, NO_KEYS));
+    let snapshot = state.snapshot();
+
+    assert!(snapshot.lives > 0);
+    assert!(snapshot.balloons.is_empty());
+    assert!(state.step(DELTA_TIME, undo).events().is_empty());
+    assert_eq!(snapshot.towers.len(), 2);
+}
+
+#[test]
+fn subscribers_receive_every_event() {
+    let state = new_game().step(
+        DELTA_TIME,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    let mut received = Vec::new();
+    let mut subscriber = |event: &GameEvent| received.push(*event);
+
+    publish(state.events(), &mut [&mut subscriber]);
+
+    assert_eq!(received, vec![GameEvent::RoundStarted { round: 1 }]);
+}
+
+#[test]
+fn functional_darts_pop_a_balloon_once() {
+    assert_two_darts_pop_a_balloon_once(new_scene(0));
+}
+
+#[test]
+fn object_oriented_darts_pop_a_balloon_once() {
+    assert_two_darts_pop_a_balloon_once(Scene::new(0));
+}
+
+#[test]
+fn ecs_darts_pop_a_balloon_once() {
+    assert_two_darts_pop_a_balloon_once(EcsScene::new(0));
+}
@@ -0,0 +1,326 @@
+// Checks the rules each game mode changes: the starting cash, what pops pay,
+// whether towers and escapes cost anything, whether rounds wait for the
+// player, and whether towers can be sold or lost games continued, in every
+// implementation.
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::difficulty::starting_cash;
+use bloonstd7::functional::difficulty::starting_lives;
+use bloonstd7::functional::difficulty::DIFFICULTIES;
+use bloonstd7::functional::game_mode::starting_cash_for_mode;
+use bloonstd7::functional::game_mode::GameMode;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::snapshot::ScreenSnapshot;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const EASY: usize = 0;
+const IMPOPPABLE: usize = 3;
+
+const STANDARD: usize = 0;
+const SANDBOX: usize = 1;
+const DEFLATION: usize = 2;
+const HALF_CASH: usize = 3;
+const APOPALYPSE: usize = 4;
+const CHIMPS: usize = 5;
+
+// Aims straight down across the balloon path from above it.
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+const SECOND_TOWER_POSITION: Vec2 = Vec2::new(500., 200.);
+// A quarter turn in 15 degree steps.
+const ROTATION_PRESSES: usize = 6;
+// Long enough for a whole early round to spawn and cross the map.
+const ROUND_TICKS: usize = 1200;
+
+fn start_game<S: GameSimulation>(simulation: S, difficulty: usize, game_mode: usize) -> S {
+    [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            select_difficulty: Some(difficulty),
+            ..NO_KEYS
+        },
+        Keys {
+            select_game_mode: Some(game_mode),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(simulation, |simulation, keys| {
+        simulation.step(DELTA_TIME, keys)
+    })
+}
+
+fn press<S: GameSimulation>(simulation: S, keys: Keys) -> S {
+    simulation.step(
+        DELTA_TIME,
+        Keys {
+            interface_pointer_position: keys.pointer_position,
+            ..keys
+        },
+    )
+}
+
+fn wait<S: GameSimulation>(simulation: S, ticks: usize) -> S {
+    (0..ticks).fold(simulation, |simulation, _| {
+        simulation.step(DELTA_TIME, NO_KEYS)
+    })
+}
+
+fn place_tower<S: GameSimulation>(simulation: S, position: Vec2) -> S {
+    let simulation = press(
+        simulation,
+        Keys {
+            tower_placement: PRESSED,
+            pointer_position: position,
+            ..NO_KEYS
+        },
+    );
+    let simulation = (0..ROTATION_PRESSES).fold(simulation, |simulation, _| {
+        press(
+            simulation,
+            Keys {
+                rotate_tower_clockwise: PRESSED,
+                pointer_position: position,
+                ..NO_KEYS
+            },
+        )
+    });
+
+    press(
+        simulation,
+        Keys {
+            place_tower: CLICKED,
+            pointer_position: position,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn start_round<S: GameSimulation>(simulation: S) -> S {
+    press(
+        simulation,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn assert_starting_cash<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    DIFFICULTIES
+        .iter()
+        .enumerate()
+        .for_each(|(index, difficulty)| {
+            let deflation = start_game(new_simulation(0), index, DEFLATION).snapshot();
+            let half_cash = start_game(new_simulation(0), index, HALF_CASH).snapshot();
+
+            assert_eq!(
+                deflation.coins,
+                starting_cash_for_mode(GameMode::Deflation, *difficulty)
+            );
+            assert_eq!(deflation.coins, 100);
+            assert_eq!(half_cash.coins, starting_cash(*difficulty) / 2);
+        });
+}
+
+// The coins earned from pops over a round with one tower, and how many
+// balloons that tower popped.
+fn round_income<S: GameSimulation>(new_simulation: fn(u64) -> S, game_mode: usize) -> (u32, u32) {
+    let simulation = place_tower(
+        start_game(new_simulation(0), EASY, game_mode),
+        TOWER_POSITION,
+    );
+    let coins = simulation.snapshot().coins;
+    let snapshot = wait(start_round(simulation), ROUND_TICKS).snapshot();
+
+    (snapshot.coins - coins, snapshot.towers[0].pop_count)
+}
+
+fn assert_income<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let (standard_income, standard_pops) = round_income(new_simulation, STANDARD);
+    let (half_cash_income, half_cash_pops) = round_income(new_simulation, HALF_CASH);
+    let (deflation_income, deflation_pops) = round_income(new_simulation, DEFLATION);
+
+    assert!(standard_pops > 1);
+    assert_eq!(standard_income, standard_pops);
+    assert_eq!(half_cash_income, half_cash_pops / 2);
+    assert!(deflation_pops > 0);
+    assert_eq!(deflation_income, 0);
+}
+
+fn assert_sandbox_is_free<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = start_game(new_simulation(0), EASY, SANDBOX);
+    let starting = simulation.snapshot();
+    let simulation = start_round(place_tower(simulation, TOWER_POSITION));
+    let simulation = press(
+        simulation,
+        Keys {
+            spawn_balloon: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(simulation.snapshot().balloons.len(), 1);
+
+    let snapshot = wait(simulation, ROUND_TICKS).snapshot();
+
+    assert_eq!(snapshot.towers.len(), 1);
+    assert_eq!(
+        snapshot.coins,
+        starting.coins + snapshot.towers[0].pop_count
+    );
+    assert_eq!(snapshot.lives, starting.lives);
+    assert_eq!(snapshot.screen, ScreenSnapshot::BetweenRounds);
+    assert_eq!(snapshot.round, 1);
+}
+
+fn assert_no_spawning_on_demand<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = start_round(start_game(new_simulation(0), EASY, STANDARD));
+    let simulation = press(
+        simulation,
+        Keys {
+            spawn_balloon: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    assert!(simulation.snapshot().balloons.is_empty());
+}
+
+fn assert_apopalypse_rounds<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = start_game(new_simulation(0), EASY, APOPALYPSE);
+    let simulation = place_tower(simulation, TOWER_POSITION);
+    let mut simulation = place_tower(simulation, SECOND_TOWER_POSITION);
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Playing);
+    assert_eq!(simulation.snapshot().round, 1);
+
+    for _ in 0..ROUND_TICKS {
+        simulation = simulation.step(DELTA_TIME, NO_KEYS);
+
+        assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Playing);
+
+        if simulation.snapshot().round == 2 {
+            return;
+        }
+    }
+
+    panic!("the second round never started");
+}
+
+fn assert_chimps_rules<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = place_tower(
+        start_game(new_simulation(0), IMPOPPABLE, CHIMPS),
+        TOWER_POSITION,
+    );
+    let simulation = press(
+        simulation,
+        Keys {
+            sell_tower: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(simulation.snapshot().towers.len(), 1);
+
+    let simulation = wait(start_round(simulation), ROUND_TICKS);
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Defeat);
+
+    let simulation = press(
+        simulation,
+        Keys {
+            continue_game: PRESSED,
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Defeat);
+}
+
+fn assert_standard_continues<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = wait(
+        start_round(start_game(new_simulation(0), IMPOPPABLE, STANDARD)),
+        ROUND_TICKS,
+    );
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Defeat);
+
+    let snapshot = press(
+        simulation,
+        Keys {
+            continue_game: PRESSED,
+            ..NO_KEYS
+        },
+    )
+    .snapshot();
+
+    assert_eq!(snapshot.screen, ScreenSnapshot::BetweenRounds);
+    assert_eq!(snapshot.lives, starting_lives(DIFFICULTIES[IMPOPPABLE]));
+    assert_eq!(snapshot.round, 1);
+}
+
+#[test]
+fn deflation_and_half_cash_change_the_starting_cash() {
+    assert_starting_cash(new_scene);
+    assert_starting_cash(Scene::new);
+    assert_starting_cash(EcsScene::new);
+}
+
+#[test]
+fn deflation_and_half_cash_change_what_pops_pay() {
+    assert_income(new_scene);
+    assert_income(Scene::new);
+    assert_income(EcsScene::new);
+}
+
+#[test]
+fn sandbox_costs_no_coins_or_lives() {
+    assert_sandbox_is_free(new_scene);
+    assert_sandbox_is_free(Scene::new);
+    assert_sandbox_is_free(EcsScene::new);
+}
+
+#[test]
+fn only_sandbox_spawns_balloons_on_demand() {
+    assert_no_spawning_on_demand(new_scene);
+    assert_no_spawning_on_demand(Scene::new);
+    assert_no_spawning_on_demand(EcsScene::new);
+}
+
+#[test]
+fn apopalypse_rounds_start_without_the_player() {
+    assert_apopalypse_rounds(new_scene);
+    assert_apopalypse_rounds(Scene::new);
+    assert_apopalypse_rounds(EcsScene::new);
+}
+
+#[test]
+fn chimps_forbids_selling_and_continues() {
+    assert_chimps_rules(new_scene);
+    assert_chimps_rules(Scene::new);
+    assert_chimps_rules(EcsScene::new);
+}
+
+#[test]
+fn standard_games_can_be_continued() {
+    assert_standard_continues(new_scene);
+    assert_standard_continues(Scene::new);
+    assert_standard_continues(EcsScene::new);
+}
@@ -0,0 +1,213 @@
+// Checks that faster game speeds run more of the same ticks rather than
+// longer ones, and that a paused game only moves when stepped a frame at a
+// time, in every implementation.
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::balloon::BALLOON_SPEED;
+use bloonstd7::functional::difficulty::balloon_speed_multiplier;
+use bloonstd7::functional::difficulty::DIFFICULTIES;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::MAX_GAME_SPEED;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::snapshot::GameSnapshot;
+use bloonstd7::snapshot::ScreenSnapshot;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const EASY: usize = 0;
+const STANDARD: usize = 0;
+
+// Aims straight down across the balloon path from above it.
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+// A quarter turn in 15 degree steps.
+const ROTATION_PRESSES: usize = 6;
+const FAST_TICKS: usize = 200;
+
+fn press<S: GameSimulation>(simulation: S, keys: Keys) -> S {
+    simulation.step(
+        DELTA_TIME,
+        Keys {
+            interface_pointer_position: keys.pointer_position,
+            ..keys
+        },
+    )
+}
+
+fn wait<S: GameSimulation>(simulation: S, ticks: usize) -> S {
+    (0..ticks).fold(simulation, |simulation, _| {
+        simulation.step(DELTA_TIME, NO_KEYS)
+    })
+}
+
+fn change_game_speed<S: GameSimulation>(simulation: S) -> S {
+    press(
+        simulation,
+        Keys {
+            change_game_speed: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn pause<S: GameSimulation>(simulation: S) -> S {
+    press(
+        simulation,
+        Keys {
+            pause: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn step_frame<S: GameSimulation>(simulation: S) -> S {
+    press(
+        simulation,
+        Keys {
+            step_frame: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+// An Easy game with one tower aimed at the path, with the first round just
+// started at the given speed.
+fn start_playing<S: GameSimulation>(simulation: S, game_speed: u32) -> S {
+    let simulation = [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            select_difficulty: Some(EASY),
+            ..NO_KEYS
+        },
+        Keys {
+            select_game_mode: Some(STANDARD),
+            ..NO_KEYS
+        },
+        Keys {
+            tower_placement: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(simulation, press);
+    let simulation = (0..ROTATION_PRESSES).fold(simulation, |simulation, _| {
+        press(
+            simulation,
+            Keys {
+                rotate_tower_clockwise: PRESSED,
+                pointer_position: TOWER_POSITION,
+                ..NO_KEYS
+            },
+        )
+    });
+    let simulation = press(
+        simulation,
+        Keys {
+            place_tower: CLICKED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+    );
+
+    let simulation =
+        (1..game_speed).fold(simulation, |simulation, _| change_game_speed(simulation));
+
+    press(
+        simulation,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn ignoring_game_speed(snapshot: GameSnapshot) -> GameSnapshot {
+    GameSnapshot {
+        game_speed: 1,
+        ..snapshot
+    }
+}
+
+fn assert_speed_cycles<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let mut simulation = start_playing(new_simulation(0), 1);
+
+    assert_eq!(simulation.snapshot().game_speed, 1);
+
+    for game_speed in (2..=MAX_GAME_SPEED).chain([1]) {
+        simulation = change_game_speed(simulation);
+
+        assert_eq!(simulation.snapshot().game_speed, game_speed);
+    }
+}
+
+fn assert_fast_ticks_match_normal_ones<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let fast = wait(start_playing(new_simulation(1), MAX_GAME_SPEED), FAST_TICKS);
+    let normal = wait(
+        start_playing(new_simulation(1), 1),
+        FAST_TICKS * MAX_GAME_SPEED as usize,
+    );
+
+    assert_eq!(fast.snapshot().game_speed, MAX_GAME_SPEED);
+    assert!(fast.snapshot().towers[0].pop_count > 0);
+    assert_eq!(
+        ignoring_game_speed(fast.snapshot()),
+        ignoring_game_speed(normal.snapshot())
+    );
+}
+
+fn assert_frame_stepping<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let mut simulation = pause(start_playing(new_simulation(0), 1));
+
+    while simulation.snapshot().balloons.is_empty() {
+        simulation = step_frame(simulation);
+    }
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Paused);
+
+    let paused = simulation.snapshot();
+    let simulation = wait(simulation, 10);
+
+    assert_eq!(simulation.snapshot(), paused);
+
+    // A stepped frame is a single tick whatever the game speed.
+    let stepped = step_frame(change_game_speed(simulation)).snapshot();
+    let expected = BALLOON_SPEED * balloon_speed_multiplier(DIFFICULTIES[EASY]) * DELTA_TIME;
+
+    assert_eq!(stepped.screen, ScreenSnapshot::Paused);
+    assert!((stepped.balloons[0].x - paused.balloons[0].x - expected).abs() < 1e-3);
+}
+
+#[test]
+fn game_speed_cycles_back_to_normal() {
+    assert_speed_cycles(new_scene);
+    assert_speed_cycles(Scene::new);
+    assert_speed_cycles(EcsScene::new);
+}
+
+#[test]
+fn faster_speeds_play_the_same_ticks() {
+    assert_fast_ticks_match_normal_ones(new_scene);
+    assert_fast_ticks_match_normal_ones(Scene::new);
+    assert_fast_ticks_match_normal_ones(EcsScene::new);
+}
+
+#[test]
+fn paused_games_only_move_a_frame_at_a_time() {
+    assert_frame_stepping(new_scene);
+    assert_frame_stepping(Scene::new);
+    assert_frame_stepping(EcsScene::new);
+}
@@ -0,0 +1,129 @@
+// Checks how the virtual cursor follows the stick and hands control back to
+// the mouse.
+
+use macroquad::prelude::*;
+
+use bloonstd7::gamepad::new_virtual_cursor;
+use bloonstd7::gamepad::update_virtual_cursor;
+
+const DELTA_TIME: f32 = 1. / 60.;
+const BOUNDS: Vec2 = Vec2::new(800., 600.);
+const MOUSE_POSITION: Vec2 = Vec2::new(100., 100.);
+
+#[test]
+fn a_resting_stick_leaves_the_mouse_in_control() {
+    let cursor = update_virtual_cursor(
+        new_virtual_cursor(MOUSE_POSITION),
+        Vec2::new(0.1, -0.1),
+        MOUSE_POSITION,
+        false,
+        BOUNDS,
+        DELTA_TIME,
+    );
+
+    assert!(!cursor.is_active);
+    assert_eq!(cursor.position, MOUSE_POSITION);
+}
+
+#[test]
+fn the_stick_moves_the_cursor_from_the_mouse_position() {
+    let cursor = update_virtual_cursor(
+        new_virtual_cursor(MOUSE_POSITION),
+        Vec2::new(1., 0.),
+        MOUSE_POSITION,
+        false,
+        BOUNDS,
+        DELTA_TIME,
+    );
+
+    assert!(cursor.is_active);
+    assert_eq!(cursor.position.y, MOUSE_POSITION.y);
+    assert!(cursor.position.x > MOUSE_POSITION.x);
+
+    // The cursor stays where the stick left it until the mouse moves.
+    let resting = update_virtual_cursor(
+        cursor,
+        Vec2::ZERO,
+        MOUSE_POSITION,
+        false,
+        BOUNDS,
+        DELTA_TIME,
+    );
+
+    assert_eq!(resting.position, cursor.position);
+
+    let moved = update_virtual_cursor(
+        resting,
+        Vec2::ZERO,
+        MOUSE_POSITION,
+        true,
+        BOUNDS,
+        DELTA_TIME,
+    );
+
+    assert!(!moved.is_active);
+    assert_eq!(moved.position, MOUSE_POSITION);
+}
+
+#[test]
+fn the_cursor_stays_inside_the_bounds() {
+    let cursor = (0..600).fold(new_virtual_cursor(MOUSE_POSITION), |cursor, _| {
+        update_virtual_cursor(
+            cursor,
+            Vec2::new(-1., 1.),
+            MOUSE_POSITION,
+            false,
+            BOUNDS,
+            DELTA_TIME,
+        )
+    });
+
+    assert_eq!(cursor.position, Vec2::new(0., BOUNDS.y));
+}
+
+#[test]
+fn the_cursor_starts_slowly_past_the_dead_zone() {
+    let step = |stick| {
+        update_virtual_cursor(
+            new_virtual_cursor(MOUSE_POSITION),
+            stick,
+            MOUSE_POSITION,
+            false,
+            BOUNDS,
+            DELTA_TIME,
+        )
+        .position
+            - MOUSE_POSITION
+    };
+
+    let slow = step(Vec2::new(0.25, 0.));
+    let fast = step(Vec2::new(1., 0.));
+
+    assert!(slow.x > 0.);
+    assert!(slow.x < fast.x / 10.);
+}
+
+#[test]
+fn the_stick_wins_over_the_mouse_while_it_is_pushed() {
+    let cursor = update_virtual_cursor(
+        new_virtual_cursor(MOUSE_POSITION),
+        Vec2::new(0., 1.),
+        MOUSE_POSITION,
+        false,
+        BOUNDS,
+        DELTA_TIME,
+    );
+
+    let pushed = update_virtual_cursor(
+        cursor,
+        Vec2::new(0., 1.),
+        Vec2::new(400., 400.),
+        true,
+        BOUNDS,
+        DELTA_TIME,
+    );
+
+    assert!(pushed.is_active);
+    assert_eq!(pushed.position.x, MOUSE_POSITION.x);
+    assert!(pushed.position.y > cursor.position.y);
+}
@@ -0,0 +1,607 @@
+// Drives the functional, object oriented and ECS scenes with the same
+// scripted input and checks that they agree on the gameplay state and the
+// events they report after every tick.
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::input::Keys;
+use bloonstd7::input::HELD;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::input::RELEASED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::shop::is_on_sidebar;
+use bloonstd7::shop::shop_item_at;
+use bloonstd7::simulation::GameSimulation;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const EASY: usize = 0;
+const MEDIUM: usize = 1;
+const IMPOPPABLE: usize = 3;
+
+const STANDARD: usize = 0;
+const SANDBOX: usize = 1;
+const HALF_CASH: usize = 3;
+const APOPALYPSE: usize = 4;
+const CHIMPS: usize = 5;
+
+// Aims straight down across the balloon path from above it.
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+const SECOND_TOWER_POSITION: Vec2 = Vec2::new(500., 200.);
+const THIRD_TOWER_POSITION: Vec2 = Vec2::new(400., 200.);
+// On the first shop button, and on the sidebar below the buttons.
+const SHOP_BUTTON_POSITION: Vec2 = Vec2::new(875., 80.);
+const SIDEBAR_POSITION: Vec2 = Vec2::new(875., 400.);
+// A quarter turn in 15 degree steps.
+const ROTATION_PRESSES: usize = 6;
+const GRID_SIZE: f32 = 25.;
+const DRAG_TICKS: usize = 10;
+
+struct Scenario {
+    seed: u64,
+    grid_size: Option<f32>,
+    ticks: Vec<Keys>,
+}
+
+impl Scenario {
+    // Every scene starts on the main menu, so each scenario goes through the
+    // map, difficulty and game mode menus first.
+    fn new(seed: u64, difficulty: usize, game_mode: usize) -> Self {
+        Self {
+            seed,
+            grid_size: None,
+            ticks: vec![
+                Keys {
+                    confirm: PRESSED,
+                    ..NO_KEYS
+                },
+                Keys {
+                    select_map: Some(0),
+                    ..NO_KEYS
+                },
+                Keys {
+                    select_difficulty: Some(difficulty),
+                    ..NO_KEYS
+                },
+                Keys {
+                    select_game_mode: Some(game_mode),
+                    ..NO_KEYS
+                },
+            ],
+        }
+    }
+
+    // Every tick from here on snaps to the grid, as when the player turns
+    // snapping on.
+    fn snap_to_grid(self, grid_size: Option<f32>) -> Self {
+        Self { grid_size, ..self }
+    }
+
+    // The scenarios play unzoomed, where the interface lines up with the
+    // world.
+    fn press(mut self, keys: Keys) -> Self {
+        self.ticks.push(Keys {
+            grid_size: self.grid_size,
+            interface_pointer_position: keys.pointer_position,
+            ..keys
+        });
+        self
+    }
+
+    fn hold(self, keys: Keys, ticks: usize) -> Self {
+        (0..ticks).fold(self, |scenario, _| scenario.press(keys.clone()))
+    }
+
+    fn wait(self, ticks: usize) -> Self {
+        self.hold(NO_KEYS, ticks)
+    }
+
+    fn place_tower(self, position: Vec2) -> Self {
+        self.press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: position,
+            ..NO_KEYS
+        })
+        .hold(
+            Keys {
+                rotate_tower_clockwise: PRESSED,
+                pointer_position: position,
+                ..NO_KEYS
+            },
+            ROTATION_PRESSES,
+        )
+        .click(position)
+    }
+
+    fn click(self, position: Vec2) -> Self {
+        self.press(Keys {
+            place_tower: PRESSED,
+            pointer_position: position,
+            ..NO_KEYS
+        })
+        .press(Keys {
+            place_tower: RELEASED,
+            pointer_position: position,
+            ..NO_KEYS
+        })
+    }
+
+    // Presses at the start, moves to the end over a few ticks and releases
+    // there.
+    fn drag(self, start: Vec2, end: Vec2) -> Self {
+        let scenario = self.press(Keys {
+            place_tower: PRESSED,
+            pointer_position: start,
+            ..NO_KEYS
+        });
+
+        (1..=DRAG_TICKS)
+            .fold(scenario, |scenario, tick| {
+                scenario.press(Keys {
+                    place_tower: HELD,
+                    pointer_position: start.lerp(end, tick as f32 / DRAG_TICKS as f32),
+                    ..NO_KEYS
+                })
+            })
+            .press(Keys {
+                place_tower: RELEASED,
+                pointer_position: end,
+                ..NO_KEYS
+            })
+    }
+
+    fn start_round(self) -> Self {
+        self.press(Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        })
+    }
+
+    fn run(self) {
+        let mut functional = new_scene(self.seed);
+        let mut object_oriented = Scene::new(self.seed);
+        let mut ecs = EcsScene::new(self.seed);
+
+        for (tick, keys) in self.ticks.into_iter().enumerate() {
+            object_oriented = object_oriented.step(DELTA_TIME, keys.clone());
+            ecs = ecs.step(DELTA_TIME, keys.clone());
+            functional = functional.step(DELTA_TIME, keys);
+
+            let snapshot = functional.snapshot();
+
+            assert_eq!(
+                snapshot,
+                object_oriented.snapshot(),
+                "object oriented implementation diverged on tick {}",
+                tick
+            );
+            assert_eq!(
+                snapshot,
+                ecs.snapshot(),
+                "ECS implementation diverged on tick {}",
+                tick
+            );
+            assert_eq!(
+                functional.events(),
+                object_oriented.events(),
+                "object oriented implementation reported other events on tick {}",
+                tick
+            );
+            assert_eq!(
+                functional.events(),
+                ecs.events(),
+                "ECS implementation reported other events on tick {}",
+                tick
+            );
+        }
+    }
+}
+
+#[test]
+fn standard_rounds_with_towers() {
+    Scenario::new(1, EASY, STANDARD)
+        .place_tower(TOWER_POSITION)
+        .place_tower(THIRD_TOWER_POSITION)
+        .start_round()
+        .wait(900)
+        .place_tower(SECOND_TOWER_POSITION)
+        .start_round()
+        .wait(1200)
+        .start_round()
+        .wait(1500)
+        .run();
+}
+
+#[test]
+fn dragging_aims_the_placed_tower() {
+    Scenario::new(11, MEDIUM, STANDARD)
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        })
+        .drag(TOWER_POSITION, TOWER_POSITION + Vec2::new(-60., 40.))
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: SECOND_TOWER_POSITION,
+            ..NO_KEYS
+        })
+        .drag(
+            SECOND_TOWER_POSITION,
+            SECOND_TOWER_POSITION + Vec2::new(5., 5.),
+        )
+        .start_round()
+        .wait(900)
+        .run();
+}
+
+#[test]
+fn buying_towers_from_the_shop() {
+    // Medium starts with enough coins for two towers, so the third click on
+    // the shop finds the button disabled.
+    Scenario::new(12, MEDIUM, STANDARD)
+        .click(SHOP_BUTTON_POSITION)
+        .click(TOWER_POSITION)
+        .drag(SHOP_BUTTON_POSITION, SECOND_TOWER_POSITION)
+        .click(SHOP_BUTTON_POSITION)
+        .click(THIRD_TOWER_POSITION)
+        .start_round()
+        .wait(600)
+        .run();
+}
+
+#[test]
+fn towers_are_not_placed_on_the_sidebar() {
+    Scenario::new(13, MEDIUM, SANDBOX)
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        })
+        .click(SIDEBAR_POSITION)
+        .click(TOWER_POSITION)
+        .click(SHOP_BUTTON_POSITION)
+        .drag(SECOND_TOWER_POSITION, SIDEBAR_POSITION)
+        .click(SIDEBAR_POSITION)
+        .start_round()
+        .wait(300)
+        .run();
+}
+
+#[test]
+fn towers_are_not_placed_on_top_of_each_other() {
+    Scenario::new(15, MEDIUM, SANDBOX)
+        .place_tower(TOWER_POSITION)
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: TOWER_POSITION + Vec2::new(40., 0.),
+            ..NO_KEYS
+        })
+        .click(TOWER_POSITION + Vec2::new(40., 0.))
+        .drag(TOWER_POSITION + Vec2::new(0., 30.), SECOND_TOWER_POSITION)
+        .click(SECOND_TOWER_POSITION)
+        .start_round()
+        .wait(300)
+        .run();
+}
+
+#[test]
+fn snapping_and_nudging_placed_towers() {
+    // Clear of the snapped towers, which would block them.
+    let nudged_position = Vec2::new(300., 400.);
+    let moved_position = Vec2::new(450., 420.);
+    let nudge_right = Keys {
+        nudge_right: PRESSED,
+        pointer_position: nudged_position,
+        ..NO_KEYS
+    };
+
+    Scenario::new(14, MEDIUM, SANDBOX)
+        .snap_to_grid(Some(GRID_SIZE))
+        .place_tower(TOWER_POSITION + Vec2::new(7., -9.))
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: SECOND_TOWER_POSITION + Vec2::new(-11., 4.),
+            ..NO_KEYS
+        })
+        .drag(
+            SECOND_TOWER_POSITION + Vec2::new(-11., 4.),
+            SECOND_TOWER_POSITION + Vec2::new(60., 60.),
+        )
+        .snap_to_grid(None)
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: nudged_position,
+            ..NO_KEYS
+        })
+        .hold(nudge_right.clone(), 3)
+        .press(Keys {
+            nudge_down: PRESSED,
+            rotate_tower_counter_clockwise: PRESSED,
+            pointer_position: nudged_position,
+            ..NO_KEYS
+        })
+        .click(nudged_position)
+        // Moving the pointer drops the nudges, so this one lands under it.
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: moved_position,
+            ..NO_KEYS
+        })
+        .hold(
+            Keys {
+                pointer_position: moved_position,
+                ..nudge_right
+            },
+            2,
+        )
+        .press(Keys {
+            pointer_position: moved_position + Vec2::new(3., 3.),
+            pointer_delta: Vec2::new(3., 3.),
+            ..NO_KEYS
+        })
+        .click(moved_position + Vec2::new(3., 3.))
+        .start_round()
+        .wait(600)
+        .run();
+}
+
+#[test]
+fn cancelled_placement_discards_the_preview() {
+    Scenario::new(2, MEDIUM, STANDARD)
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        })
+        .hold(
+            Keys {
+                rotate_tower_counter_clockwise: PRESSED,
+                pointer_position: TOWER_POSITION,
+                ..NO_KEYS
+            },
+            3,
+        )
+        .press(Keys {
+            cancel_tower_placement: PRESSED,
+            ..NO_KEYS
+        })
+        .click(TOWER_POSITION)
+        .press(Keys {
+            tower_placement: PRESSED,
+            pointer_position: SECOND_TOWER_POSITION,
+            ..NO_KEYS
+        })
+        .click(SECOND_TOWER_POSITION)
+        .run();
+}
+
+#[test]
+fn upgrading_and_selling_towers() {
+    let upgrade = Keys {
+        upgrade_tower: PRESSED,
+        pointer_position: TOWER_POSITION,
+        ..NO_KEYS
+    };
+    let sell = Keys {
+        sell_tower: PRESSED,
+        pointer_position: TOWER_POSITION,
+        ..NO_KEYS
+    };
+
+    Scenario::new(3, EASY, STANDARD)
+        .place_tower(TOWER_POSITION)
+        .press(upgrade.clone())
+        .press(upgrade.clone())
+        .start_round()
+        .wait(600)
+        .press(upgrade)
+        .wait(600)
+        .press(sell.clone())
+        .wait(10)
+        .press(sell)
+        .run();
+}
+
+#[test]
+fn undoing_placements_and_upgrades() {
+    let upgrade = Keys {
+        upgrade_tower: PRESSED,
+        pointer_position: TOWER_POSITION,
+        ..NO_KEYS
+    };
+    let sell = Keys {
+        sell_tower: PRESSED,
+        pointer_position: TOWER_POSITION,
+        ..NO_KEYS
+    };
+    let undo = Keys {
+        undo: PRESSED,
+        ..NO_KEYS
+    };
+
+    Scenario::new(15, EASY, STANDARD)
+        .place_tower(TOWER_POSITION)
+        .place_tower(SECOND_TOWER_POSITION)
+        .press(upgrade.clone())
+        .press(upgrade.clone())
+        .hold(undo.clone(), 5)
+        .place_tower(TOWER_POSITION)
+        .press(upgrade)
+        .start_round()
+        // Starting the round leaves nothing to undo.
+        .press(undo.clone())
+        .wait(300)
+        // Nor can a tower placed during the round be undone, even once it is
+        // over.
+        .place_tower(THIRD_TOWER_POSITION)
+        .wait(150)
+        .press(undo.clone())
+        .wait(1500)
+        .press(undo.clone())
+        .place_tower(SECOND_TOWER_POSITION)
+        .press(sell)
+        // Selling leaves nothing to undo either.
+        .press(undo)
+        .wait(600)
+        .run();
+}
+
+#[test]
+fn sandbox_spawning_on_demand() {
+    let spawn = Keys {
+        spawn_balloon: PRESSED,
+        ..NO_KEYS
+    };
+
+    Scenario::new(4, MEDIUM, SANDBOX)
+        .place_tower(TOWER_POSITION)
+        .press(spawn.clone())
+        .start_round()
+        .press(spawn.clone())
+        .wait(20)
+        .press(spawn.clone())
+        .wait(20)
+        .press(spawn)
+        .wait(1000)
+        .run();
+}
+
+#[test]
+fn defeat_and_continue() {
+    Scenario::new(5, IMPOPPABLE, STANDARD)
+        .start_round()
+        .wait(600)
+        .press(Keys {
+            continue_game: PRESSED,
+            ..NO_KEYS
+        })
+        .place_tower(TOWER_POSITION)
+        .start_round()
+        .wait(600)
+        .press(Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        })
+        .start_round()
+        .wait(600)
+        .run();
+}
+
+#[test]
+fn apopalypse_rounds_run_back_to_back() {
+    Scenario::new(6, EASY, APOPALYPSE)
+        .place_tower(TOWER_POSITION)
+        .place_tower(SECOND_TOWER_POSITION)
+        .wait(3000)
+        .run();
+}
+
+#[test]
+fn half_cash_income() {
+    Scenario::new(7, EASY, HALF_CASH)
+        .place_tower(TOWER_POSITION)
+        .start_round()
+        .wait(1200)
+        .run();
+}
+
+#[test]
+fn chimps_forbids_selling_and_continues() {
+    Scenario::new(8, MEDIUM, CHIMPS)
+        .place_tower(TOWER_POSITION)
+        .press(Keys {
+            sell_tower: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        })
+        .start_round()
+        .wait(1500)
+        .press(Keys {
+            continue_game: PRESSED,
+            ..NO_KEYS
+        })
+        .wait(10)
+        .run();
+}
+
+#[test]
+fn game_speed_pause_and_frame_stepping() {
+    let pause = Keys {
+        pause: PRESSED,
+        ..NO_KEYS
+    };
+    let step_frame = Keys {
+        step_frame: PRESSED,
+        ..NO_KEYS
+    };
+    let change_game_speed = Keys {
+        change_game_speed: PRESSED,
+        ..NO_KEYS
+    };
+
+    Scenario::new(9, MEDIUM, STANDARD)
+        .place_tower(TOWER_POSITION)
+        .press(change_game_speed.clone())
+        .start_round()
+        .wait(200)
+        .press(pause.clone())
+        .hold(step_frame, 30)
+        .press(change_game_speed)
+        .press(pause)
+        .wait(600)
+        .run();
+}
+
+#[test]
+fn victory_returns_to_the_menu() {
+    let scenario = Scenario::new(10, EASY, SANDBOX)
+        .place_tower(TOWER_POSITION)
+        .press(Keys {
+            change_game_speed: PRESSED,
+            ..NO_KEYS
+        })
+        .press(Keys {
+            change_game_speed: PRESSED,
+            ..NO_KEYS
+        });
+
+    (0..20)
+        .fold(scenario, |scenario, _| scenario.start_round().wait(1200))
+        .press(Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        })
+        .wait(10)
+        // And from the main menu, on to another game on the other map.
+        .press(Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        })
+        .press(Keys {
+            select_map: Some(1),
+            ..NO_KEYS
+        })
+        .press(Keys {
+            select_difficulty: Some(MEDIUM),
+            ..NO_KEYS
+        })
+        .press(Keys {
+            select_game_mode: Some(STANDARD),
+            ..NO_KEYS
+        })
+        .place_tower(TOWER_POSITION)
+        .start_round()
+        .wait(300)
+        .run();
+}
+
+#[test]
+fn the_scenario_buttons_are_on_the_shop() {
+    assert_eq!(shop_item_at(SHOP_BUTTON_POSITION), Some(0));
+    assert!(is_on_sidebar(SIDEBAR_POSITION));
+    assert_eq!(shop_item_at(SIDEBAR_POSITION), None);
+}
@@ -0,0 +1,181 @@
+// Checks grid snapping, nudging and stepped rotation, and that the functional
+// scene places towers where they were nudged to.
+
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::GameState;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::placement::clamp_to_world;
+use bloonstd7::placement::nudge_offset;
+use bloonstd7::placement::rotation_offset;
+use bloonstd7::placement::snap_to_grid;
+use bloonstd7::placement::NUDGE_DISTANCE;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::world::WORLD_HEIGHT;
+use bloonstd7::world::WORLD_WIDTH;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const GRID_SIZE: f32 = 25.;
+const TOWER_POSITION: Vec2 = Vec2::new(307., 212.);
+
+fn new_game() -> GameState {
+    [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Medium
+            select_difficulty: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Standard
+            select_game_mode: Some(0),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(new_scene(0), |state, keys| state.step(DELTA_TIME, keys))
+}
+
+// Starts placing at the tower position, applies the given ticks and clicks
+// without moving the pointer.
+fn place_tower(ticks: Vec<Keys>, grid_size: Option<f32>) -> (Vec2, f32) {
+    let keys = Keys {
+        pointer_position: TOWER_POSITION,
+        grid_size,
+        ..NO_KEYS
+    };
+
+    let state = std::iter::once(Keys {
+        tower_placement: PRESSED,
+        ..keys.clone()
+    })
+    .chain(ticks.into_iter().map(|tick| Keys {
+        pointer_position: TOWER_POSITION,
+        grid_size,
+        ..tick
+    }))
+    .chain(std::iter::once(Keys {
+        place_tower: CLICKED,
+        ..keys
+    }))
+    .fold(new_game(), |state, keys| state.step(DELTA_TIME, keys));
+
+    let tower = &state.snapshot().towers[0];
+
+    (tower.position, tower.angle)
+}
+
+#[test]
+fn snapping_rounds_to_the_nearest_grid_point() {
+    assert_eq!(
+        snap_to_grid(TOWER_POSITION, Some(GRID_SIZE)),
+        Vec2::new(300., 200.)
+    );
+    assert_eq!(
+        snap_to_grid(Vec2::new(313., 238.), Some(GRID_SIZE)),
+        Vec2::new(325., 250.)
+    );
+    assert_eq!(snap_to_grid(TOWER_POSITION, None), TOWER_POSITION);
+}
+
+#[test]
+fn nudges_move_by_a_pixel_or_a_grid_cell() {
+    let keys = Keys {
+        nudge_left: PRESSED,
+        nudge_down: PRESSED,
+        ..NO_KEYS
+    };
+
+    assert_eq!(
+        nudge_offset(&keys),
+        Vec2::new(-NUDGE_DISTANCE, NUDGE_DISTANCE)
+    );
+    assert_eq!(
+        nudge_offset(&Keys {
+            grid_size: Some(GRID_SIZE),
+            ..keys
+        }),
+        Vec2::new(-GRID_SIZE, GRID_SIZE)
+    );
+}
+
+#[test]
+fn opposite_rotations_cancel_out() {
+    let clockwise = Keys {
+        rotate_tower_clockwise: PRESSED,
+        ..NO_KEYS
+    };
+
+    assert_eq!(rotation_offset(&clockwise), PI / 12.);
+    assert_eq!(
+        rotation_offset(&Keys {
+            rotate_tower_counter_clockwise: PRESSED,
+            ..clockwise
+        }),
+        0.
+    );
+}
+
+#[test]
+fn towers_are_placed_where_they_were_nudged() {
+    let nudge_right = Keys {
+        nudge_right: PRESSED,
+        ..NO_KEYS
+    };
+
+    let (position, _) = place_tower(vec![nudge_right.clone(), nudge_right], None);
+
+    assert_eq!(position, TOWER_POSITION + Vec2::new(2., 0.));
+
+    let (position, angle) = place_tower(
+        vec![Keys {
+            nudge_up: PRESSED,
+            rotate_tower_clockwise: PRESSED,
+            ..NO_KEYS
+        }],
+        Some(GRID_SIZE),
+    );
+
+    assert_eq!(position, Vec2::new(300., 175.));
+    assert_eq!(angle, PI / 12.);
+}
+
+#[test]
+fn positions_past_the_edge_are_kept_on_the_map() {
+    assert_eq!(clamp_to_world(TOWER_POSITION), TOWER_POSITION);
+    assert_eq!(
+        clamp_to_world(Vec2::new(-20., WORLD_HEIGHT + 5.)),
+        Vec2::new(0., WORLD_HEIGHT)
+    );
+    // Snapping to a grid that does not divide the map rounds past its edge.
+    assert_eq!(
+        clamp_to_world(snap_to_grid(Vec2::new(WORLD_WIDTH - 1., 0.), Some(30.))),
+        Vec2::new(WORLD_WIDTH, 0.)
+    );
+}
+
+#[test]
+fn nudging_stops_at_the_edge_of_the_map() {
+    let nudge_up = Keys {
+        nudge_up: PRESSED,
+        ..NO_KEYS
+    };
+
+    let (position, _) = place_tower(vec![nudge_up; 10], Some(GRID_SIZE));
+
+    assert_eq!(position, Vec2::new(300., 0.));
+}
@@ -0,0 +1,124 @@
+// Checks that the gameplay randomness is fully determined by its seed, so
+// that a seeded game always plays the same and replays and saves can rely on
+// it, in every implementation.
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::random::new_rng;
+use bloonstd7::functional::random::next_random;
+use bloonstd7::functional::random::random_range;
+use bloonstd7::functional::random::rng_state;
+use bloonstd7::functional::random::Rng;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::input::Keys;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::snapshot::GameSnapshot;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const EASY: usize = 0;
+const SANDBOX: usize = 1;
+
+// Long enough for the first round to finish spawning.
+const ROUND_TICKS: usize = 600;
+const DRAWS: usize = 1000;
+
+fn draws(rng: Rng, count: usize) -> (Rng, Vec<u64>) {
+    (0..count).fold((rng, Vec::new()), |(rng, mut values), _| {
+        let (rng, value) = next_random(rng);
+
+        values.push(value);
+        (rng, values)
+    })
+}
+
+// The snapshot after every tick of the first round of a sandbox game, where
+// balloons escape without ending it.
+fn play_round<S: GameSimulation>(simulation: S) -> Vec<GameSnapshot> {
+    let keys = [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            select_difficulty: Some(EASY),
+            ..NO_KEYS
+        },
+        Keys {
+            select_game_mode: Some(SANDBOX),
+            ..NO_KEYS
+        },
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .chain((0..ROUND_TICKS).map(|_| NO_KEYS));
+
+    let (_, snapshots) = keys.fold(
+        (simulation, Vec::new()),
+        |(simulation, mut snapshots), keys| {
+            let simulation = simulation.step(DELTA_TIME, keys);
+
+            snapshots.push(simulation.snapshot());
+            (simulation, snapshots)
+        },
+    );
+
+    snapshots
+}
+
+fn assert_seeded_play<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    assert_eq!(play_round(new_simulation(1)), play_round(new_simulation(1)));
+    assert_ne!(play_round(new_simulation(1)), play_round(new_simulation(2)));
+}
+
+#[test]
+fn the_same_seed_draws_the_same_numbers() {
+    let (_, first) = draws(new_rng(7), DRAWS);
+    let (_, second) = draws(new_rng(7), DRAWS);
+    let (_, other) = draws(new_rng(8), DRAWS);
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn the_generator_is_splitmix64() {
+    let (_, values) = draws(new_rng(0), 2);
+
+    assert_eq!(values, vec![0xE220_A839_7B1D_CDAF, 0x6E78_9E6A_A1B9_65F4]);
+}
+
+#[test]
+fn a_saved_state_continues_the_same_numbers() {
+    let (rng, _) = draws(new_rng(3), 10);
+    let (_, expected) = draws(rng, 10);
+    let (_, restored) = draws(new_rng(rng_state(rng)), 10);
+
+    assert_eq!(restored, expected);
+}
+
+#[test]
+fn ranges_stay_within_their_bounds() {
+    (0..DRAWS).fold(new_rng(5), |rng, _| {
+        let (rng, value) = random_range(rng, -2., 3.);
+
+        assert!((-2. ..3.).contains(&value));
+        rng
+    });
+}
+
+#[test]
+fn seeded_games_play_the_same() {
+    assert_seeded_play(new_scene);
+    assert_seeded_play(Scene::new);
+    assert_seeded_play(EcsScene::new);
+}
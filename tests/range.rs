@@ -0,0 +1,85 @@
+// Checks that darts fly until they leave the world, which is as far as the
+// range shown for the tower that fired them.
+
+use macroquad::prelude::*;
+
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::world::distance_to_world_edge;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+// Aiming right, its darts cross most of the world.
+const TOWER_POSITION: Vec2 = Vec2::new(350., 300.);
+
+#[test]
+fn darts_fly_to_the_edge_of_the_world() {
+    let state = [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Medium
+            select_difficulty: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Sandbox, so no balloons get in the way.
+            select_game_mode: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            tower_placement: PRESSED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+        Keys {
+            place_tower: CLICKED,
+            pointer_position: TOWER_POSITION,
+            ..NO_KEYS
+        },
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(new_scene(0), |state, keys| state.step(DELTA_TIME, keys));
+
+    let farthest_distance = (0..600)
+        .scan(state, |state, _| {
+            *state = state.clone().step(DELTA_TIME, NO_KEYS);
+
+            Some(state.snapshot())
+        })
+        .flat_map(|snapshot| snapshot.towers)
+        .flat_map(|tower| tower.projectiles)
+        .map(|projectile| projectile.distance(TOWER_POSITION))
+        .fold(0., f32::max);
+
+    let range = distance_to_world_edge(TOWER_POSITION, Vec2::X);
+
+    // A dart may travel one more tick before it is cleared.
+    assert_eq!(range, 450.);
+    assert!(farthest_distance > range - 10.);
+    assert!(farthest_distance <= range + 500. * DELTA_TIME * 2.);
+}
+
+#[test]
+fn the_range_ends_at_the_first_edge_in_the_way() {
+    assert_eq!(distance_to_world_edge(TOWER_POSITION, -Vec2::X), 350.);
+    assert_eq!(distance_to_world_edge(TOWER_POSITION, Vec2::Y), 300.);
+    assert_eq!(
+        distance_to_world_edge(Vec2::new(700., 300.), Vec2::new(0.6, -0.8)),
+        100. / 0.6
+    );
+}
@@ -0,0 +1,176 @@
+// Records a session with each implementation, plays the recording back from
+// the same seed and checks that it ends in the same state.
+
+use std::env::temp_dir;
+use std::fs::remove_file;
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::replay::load_replay;
+use bloonstd7::functional::replay::ReplayWriter;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::implementation_name;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::simulation::Implementation;
+
+const DELTA_TIME: f32 = 1. / 60.;
+const SEED: u64 = 42;
+
+// Aims straight down across the balloon path from above it.
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+// A quarter turn in 15 degree steps.
+const ROTATION_PRESSES: usize = 6;
+const GRID_SIZE: f32 = 25.;
+// Long enough for balloons to spawn at random intervals and be popped.
+const ROUND_TICKS: usize = 300;
+
+fn replay_path(name: &str) -> String {
+    temp_dir()
+        .join(format!("bloonstd7-{}-{}.btdr", name, std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn snapped(keys: Keys) -> Keys {
+    Keys {
+        pointer_position: TOWER_POSITION,
+        interface_pointer_position: TOWER_POSITION,
+        grid_size: Some(GRID_SIZE),
+        ..keys
+    }
+}
+
+// Goes through the menus, places a tower on the grid and plays a round.
+fn session() -> Vec<Keys> {
+    let menus = [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Medium
+            select_difficulty: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Standard
+            select_game_mode: Some(0),
+            ..NO_KEYS
+        },
+    ];
+
+    let placement = [Keys {
+        tower_placement: PRESSED,
+        ..NO_KEYS
+    }]
+    .into_iter()
+    .chain((0..ROTATION_PRESSES).map(|_| Keys {
+        rotate_tower_clockwise: PRESSED,
+        ..NO_KEYS
+    }))
+    .chain([Keys {
+        place_tower: CLICKED,
+        ..NO_KEYS
+    }])
+    .map(snapped);
+
+    let round = [Keys {
+        start_round: PRESSED,
+        ..NO_KEYS
+    }]
+    .into_iter()
+    .chain((0..ROUND_TICKS).map(|_| NO_KEYS));
+
+    menus.into_iter().chain(placement).chain(round).collect()
+}
+
+fn record<S: GameSimulation>(simulation: S, implementation: Implementation) -> S {
+    let mut recorder = ReplayWriter::create(
+        &replay_path(implementation_name(implementation)),
+        implementation,
+        SEED,
+    )
+    .expect("Failed to create the replay");
+
+    let simulation = session().into_iter().fold(simulation, |simulation, keys| {
+        recorder.record(&keys).expect("Failed to record the replay");
+        simulation.step(DELTA_TIME, keys)
+    });
+
+    recorder.flush().expect("Failed to record the replay");
+
+    simulation
+}
+
+fn play_back<S: GameSimulation>(new_simulation: fn(u64) -> S, implementation: Implementation) -> S {
+    let path = replay_path(implementation_name(implementation));
+    let replay = load_replay(&path, implementation);
+
+    remove_file(&path).expect("Failed to remove the replay");
+
+    let replay = replay.expect("Failed to load the replay");
+
+    replay
+        .ticks
+        .into_iter()
+        .fold(new_simulation(replay.seed), |simulation, keys| {
+            simulation.step(DELTA_TIME, keys)
+        })
+}
+
+fn assert_replays_match<S: GameSimulation>(
+    new_simulation: fn(u64) -> S,
+    implementation: Implementation,
+) {
+    let recorded = record(new_simulation(SEED), implementation).snapshot();
+
+    assert!(!recorded.towers.is_empty());
+    assert!(recorded.towers[0].pop_count > 0);
+
+    assert_eq!(
+        play_back(new_simulation, implementation).snapshot(),
+        recorded
+    );
+}
+
+#[test]
+fn functional_replays_reproduce_the_session() {
+    assert_replays_match(new_scene, Implementation::Functional);
+}
+
+#[test]
+fn object_oriented_replays_reproduce_the_session() {
+    assert_replays_match(Scene::new, Implementation::ObjectOriented);
+}
+
+#[test]
+fn ecs_replays_reproduce_the_session() {
+    assert_replays_match(EcsScene::new, Implementation::BevyEcs);
+}
+
+#[test]
+fn replays_are_rejected_by_other_implementations() {
+    let path = replay_path("mismatch");
+
+    ReplayWriter::create(&path, Implementation::BevyEcs, SEED)
+        .and_then(|mut recorder| recorder.flush())
+        .expect("Failed to create the replay");
+
+    let other = load_replay(&path, Implementation::Functional);
+    let same = load_replay(&path, Implementation::BevyEcs);
+
+    remove_file(&path).expect("Failed to remove the replay");
+
+    assert!(other.is_err());
+    assert!(same.is_ok());
+}
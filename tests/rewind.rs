@@ -0,0 +1,76 @@
+// Checks how the rewind history keeps, scrubs through and drops states, using
+// tick numbers as the states.
+
+use bloonstd7::functional::rewind::current_state;
+use bloonstd7::functional::rewind::new_history;
+use bloonstd7::functional::rewind::record_state;
+use bloonstd7::functional::rewind::step_back;
+use bloonstd7::functional::rewind::step_forward;
+use bloonstd7::functional::rewind::History;
+use bloonstd7::functional::rewind::REWIND_CAPACITY;
+
+fn recorded(ticks: usize) -> History<usize> {
+    (0..ticks).fold(new_history(), record_state)
+}
+
+fn stepped_back(history: History<usize>, steps: usize) -> History<usize> {
+    (0..steps).fold(history, |history, _| step_back(history))
+}
+
+#[test]
+fn empty_histories_have_no_state() {
+    let history = step_forward(step_back(new_history::<usize>()));
+
+    assert_eq!(current_state(&history), None);
+}
+
+#[test]
+fn the_latest_state_is_current() {
+    assert_eq!(current_state(&recorded(5)), Some(&4));
+}
+
+#[test]
+fn stepping_moves_through_the_kept_states() {
+    let history = stepped_back(recorded(5), 2);
+
+    assert_eq!(current_state(&history), Some(&2));
+
+    let history = step_forward(history);
+
+    assert_eq!(current_state(&history), Some(&3));
+
+    let history = step_forward(step_forward(history));
+
+    assert_eq!(current_state(&history), Some(&4));
+
+    let history = stepped_back(history, 10);
+
+    assert_eq!(current_state(&history), Some(&0));
+}
+
+#[test]
+fn only_the_latest_states_are_kept() {
+    let ticks = REWIND_CAPACITY + 100;
+    let history = recorded(ticks);
+
+    assert_eq!(current_state(&history), Some(&(ticks - 1)));
+
+    let history = stepped_back(history, REWIND_CAPACITY);
+
+    assert_eq!(current_state(&history), Some(&(ticks - REWIND_CAPACITY)));
+}
+
+#[test]
+fn resuming_drops_the_states_after_the_current_one() {
+    let history = record_state(stepped_back(recorded(5), 2), 10);
+
+    assert_eq!(current_state(&history), Some(&10));
+
+    let history = step_forward(history);
+
+    assert_eq!(current_state(&history), Some(&10));
+
+    let history = step_back(history);
+
+    assert_eq!(current_state(&history), Some(&2));
+}
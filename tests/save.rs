@@ -0,0 +1,173 @@
+// Saves a game in progress with each implementation, loads it back and checks
+// that nothing was lost, and that a save from one implementation loads in the
+// others.
+
+use std::env::temp_dir;
+use std::fs::remove_file;
+
+use macroquad::prelude::*;
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::GameState;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+// Fires right, above the balloon path, so its darts stay in the air until
+// they leave the world.
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+// Long enough for balloons to be on the way, and just after the tower's
+// second dart was fired.
+const ROUND_TICKS: usize = 130;
+
+fn save_path(name: &str) -> String {
+    temp_dir()
+        .join(format!("bloonstd7-{}-{}.btd", name, std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn pointer_at(keys: Keys) -> Keys {
+    Keys {
+        pointer_position: TOWER_POSITION,
+        interface_pointer_position: TOWER_POSITION,
+        ..keys
+    }
+}
+
+// Places a tower, starts the first round and pauses it partway through.
+fn game_in_progress() -> Vec<Keys> {
+    let menus = [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Easy
+            select_difficulty: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Half Cash
+            select_game_mode: Some(3),
+            ..NO_KEYS
+        },
+    ];
+
+    let placement = [
+        Keys {
+            tower_placement: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            place_tower: CLICKED,
+            ..NO_KEYS
+        },
+    ]
+    .map(pointer_at);
+
+    let round = [Keys {
+        start_round: PRESSED,
+        ..NO_KEYS
+    }]
+    .into_iter()
+    .chain((0..ROUND_TICKS).map(|_| NO_KEYS))
+    .chain([Keys {
+        pause: PRESSED,
+        ..NO_KEYS
+    }]);
+
+    menus.into_iter().chain(placement).chain(round).collect()
+}
+
+fn play<S: GameSimulation>(simulation: S, ticks: Vec<Keys>) -> S {
+    ticks.into_iter().fold(simulation, |simulation, keys| {
+        simulation.step(DELTA_TIME, keys)
+    })
+}
+
+fn resume<S: GameSimulation>(simulation: S) -> S {
+    let ticks = [Keys {
+        pause: PRESSED,
+        ..NO_KEYS
+    }]
+    .into_iter()
+    .chain((0..ROUND_TICKS).map(|_| NO_KEYS))
+    .collect();
+
+    play(simulation, ticks)
+}
+
+fn save_and_load<S: GameSimulation, L: GameSimulation>(simulation: &S, name: &str) -> L {
+    let path = save_path(name);
+
+    simulation.save(&path).expect("Failed to save the game");
+
+    let loaded = L::load(&path);
+
+    remove_file(&path).expect("Failed to remove the save file");
+
+    loaded.expect("Failed to load the game")
+}
+
+fn assert_round_trip<S: GameSimulation>(simulation: S, name: &str) {
+    let simulation = play(simulation, game_in_progress());
+
+    assert!(!simulation.snapshot().balloons.is_empty());
+    assert!(!simulation.snapshot().towers[0].projectiles.is_empty());
+
+    let loaded: S = save_and_load(&simulation, name);
+
+    assert_eq!(loaded.snapshot(), simulation.snapshot());
+
+    // The random numbers and cooldowns carry over too, so both play on the
+    // same way.
+    assert_eq!(resume(loaded).snapshot(), resume(simulation).snapshot());
+}
+
+#[test]
+fn functional_games_survive_saving_and_loading() {
+    assert_round_trip(new_scene(0), "functional");
+}
+
+#[test]
+fn object_oriented_games_survive_saving_and_loading() {
+    assert_round_trip(Scene::new(0), "object-oriented");
+}
+
+#[test]
+fn ecs_games_survive_saving_and_loading() {
+    assert_round_trip(EcsScene::new(0), "ecs");
+}
+
+#[test]
+fn saves_load_in_every_implementation() {
+    let state = play(new_scene(0), game_in_progress());
+
+    let object_oriented: Scene = save_and_load(&state, "to-object-oriented");
+    let ecs: EcsScene = save_and_load(&state, "to-ecs");
+    let functional: GameState = save_and_load(&ecs, "from-ecs");
+
+    assert_eq!(object_oriented.snapshot(), state.snapshot());
+    assert_eq!(ecs.snapshot(), state.snapshot());
+    assert_eq!(functional.snapshot(), state.snapshot());
+}
+
+#[test]
+fn games_are_only_saved_while_playing() {
+    let path = save_path("main-menu");
+
+    assert!(new_scene(0).save(&path).is_err());
+    assert!(Scene::new(0).save(&path).is_err());
+    assert!(EcsScene::new(0).save(&path).is_err());
+}
@@ -0,0 +1,147 @@
+// Checks how stages are ordered and skipped, using a log of the stages that
+// ran as the state.
+
+use bloonstd7::functional::schedule::add_stage;
+use bloonstd7::functional::schedule::after;
+use bloonstd7::functional::schedule::before;
+use bloonstd7::functional::schedule::new_schedule;
+use bloonstd7::functional::schedule::run_if;
+use bloonstd7::functional::schedule::run_schedule;
+use bloonstd7::functional::schedule::stage;
+use bloonstd7::functional::schedule::stage_names;
+use bloonstd7::functional::schedule::Schedule;
+use bloonstd7::functional::schedule::ScheduleError;
+use bloonstd7::functional::schedule::Stage;
+
+type Log = Vec<&'static str>;
+
+fn spawn(mut log: Log) -> Log {
+    log.push("spawn");
+    log
+}
+
+fn move_balloons(mut log: Log) -> Log {
+    log.push("move");
+    log
+}
+
+fn pop(mut log: Log) -> Log {
+    log.push("pop");
+    log
+}
+
+fn slow_down(mut log: Log) -> Log {
+    log.push("slow down");
+    log
+}
+
+fn has_spawned(log: &Log) -> bool {
+    log.contains(&"spawn")
+}
+
+fn build(stages: Vec<Stage<Log>>) -> Result<Schedule<Log>, ScheduleError> {
+    stages
+        .into_iter()
+        .try_fold(new_schedule(), add_stage)
+        .map_err(|(_, error)| error)
+}
+
+#[test]
+fn stages_run_in_the_order_they_were_added() {
+    let schedule = build(vec![
+        stage("spawn", spawn),
+        stage("move", move_balloons),
+        stage("pop", pop),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        run_schedule(&schedule, Vec::new()),
+        vec!["spawn", "move", "pop"]
+    );
+}
+
+#[test]
+fn stages_can_be_inserted_before_and_after_others() {
+    let schedule = build(vec![
+        stage("spawn", spawn),
+        stage("move", move_balloons),
+        after(stage("pop", pop), "slow down"),
+        before(stage("slow down", slow_down), "move"),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        stage_names(&schedule),
+        vec!["spawn", "slow down", "move", "pop"]
+    );
+}
+
+#[test]
+fn missing_stages_are_ignored_by_constraints() {
+    let schedule = build(vec![
+        before(stage("spawn", spawn), "missing"),
+        after(stage("move", move_balloons), "missing"),
+    ])
+    .unwrap();
+
+    assert_eq!(stage_names(&schedule), vec!["spawn", "move"]);
+}
+
+#[test]
+fn stages_are_skipped_when_their_run_conditions_fail() {
+    let schedule = build(vec![
+        run_if(stage("pop", pop), has_spawned),
+        stage("spawn", spawn),
+        run_if(stage("move", move_balloons), has_spawned),
+    ])
+    .unwrap();
+
+    assert_eq!(run_schedule(&schedule, Vec::new()), vec!["spawn", "move"]);
+}
+
+#[test]
+fn duplicate_stages_are_rejected() {
+    let result = build(vec![stage("spawn", spawn), stage("spawn", spawn)]);
+
+    assert_eq!(result.err(), Some(ScheduleError::DuplicateStage("spawn")));
+}
+
+#[test]
+fn cycles_are_rejected() {
+    let result = build(vec![
+        stage("spawn", spawn),
+        before(stage("move", move_balloons), "pop"),
+        before(stage("pop", pop), "move"),
+    ]);
+
+    assert_eq!(
+        result.err(),
+        Some(ScheduleError::Cycle(vec!["move", "pop"]))
+    );
+}
+
+#[test]
+fn rejected_stages_leave_the_schedule_intact() {
+    let schedule = build(vec![
+        stage("spawn", spawn),
+        before(stage("move", move_balloons), "pop"),
+    ])
+    .unwrap();
+
+    let (schedule, error) = add_stage(schedule, stage("spawn", spawn)).err().unwrap();
+
+    assert_eq!(error, ScheduleError::DuplicateStage("spawn"));
+
+    let (schedule, error) = add_stage(schedule, before(stage("pop", pop), "move"))
+        .err()
+        .unwrap();
+
+    assert_eq!(error, ScheduleError::Cycle(vec!["move", "pop"]));
+    assert_eq!(stage_names(&schedule), vec!["spawn", "move"]);
+    assert_eq!(run_schedule(&schedule, Vec::new()), vec!["spawn", "move"]);
+
+    let schedule = add_stage(schedule, stage("pop", pop)).ok().unwrap();
+
+    assert_eq!(stage_names(&schedule), vec!["spawn", "move", "pop"]);
+}
@@ -0,0 +1,252 @@
+// Checks how each implementation moves between its screens: through the
+// menus, into and out of rounds and pauses, and back again after a game is
+// won or lost.
+
+use bloonstd7::bevy_ecs::scene::Scene as EcsScene;
+use bloonstd7::functional::difficulty::final_round;
+use bloonstd7::functional::difficulty::starting_cash;
+use bloonstd7::functional::difficulty::starting_lives;
+use bloonstd7::functional::difficulty::DIFFICULTIES;
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::input::Keys;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::object_oriented::scene::Scene;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::snapshot::ScreenSnapshot;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const EASY: usize = 0;
+const HARD: usize = 2;
+const IMPOPPABLE: usize = 3;
+
+const STANDARD: usize = 0;
+const SANDBOX: usize = 1;
+
+// Past the last option on every menu.
+const INVALID_OPTION: usize = 99;
+// Long enough for any round to spawn and cross the map at triple speed.
+const ROUND_TICKS: usize = 1200;
+
+fn press<S: GameSimulation>(simulation: S, keys: Keys) -> S {
+    simulation.step(DELTA_TIME, keys)
+}
+
+fn wait<S: GameSimulation>(simulation: S, ticks: usize) -> S {
+    (0..ticks).fold(simulation, |simulation, _| {
+        simulation.step(DELTA_TIME, NO_KEYS)
+    })
+}
+
+fn confirm<S: GameSimulation>(simulation: S) -> S {
+    press(
+        simulation,
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn start_round<S: GameSimulation>(simulation: S) -> S {
+    press(
+        simulation,
+        Keys {
+            start_round: PRESSED,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn start_game<S: GameSimulation>(simulation: S, difficulty: usize, game_mode: usize) -> S {
+    [
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            select_difficulty: Some(difficulty),
+            ..NO_KEYS
+        },
+        Keys {
+            select_game_mode: Some(game_mode),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(confirm(simulation), press)
+}
+
+fn assert_menu_flow<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = new_simulation(0);
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::MainMenu);
+
+    let simulation = confirm(simulation);
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::MapSelect);
+
+    let simulation = press(
+        simulation,
+        Keys {
+            select_map: Some(INVALID_OPTION),
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::MapSelect);
+
+    let simulation = press(
+        simulation,
+        Keys {
+            select_map: Some(1),
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(
+        simulation.snapshot().screen,
+        ScreenSnapshot::DifficultySelect
+    );
+    assert_eq!(simulation.snapshot().map, 1);
+
+    let simulation = press(
+        simulation,
+        Keys {
+            select_difficulty: Some(INVALID_OPTION),
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(
+        simulation.snapshot().screen,
+        ScreenSnapshot::DifficultySelect
+    );
+
+    let simulation = press(
+        simulation,
+        Keys {
+            select_difficulty: Some(HARD),
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::GameModeSelect);
+
+    let simulation = press(
+        simulation,
+        Keys {
+            select_game_mode: Some(INVALID_OPTION),
+            ..NO_KEYS
+        },
+    );
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::GameModeSelect);
+
+    let snapshot = press(
+        simulation,
+        Keys {
+            select_game_mode: Some(STANDARD),
+            ..NO_KEYS
+        },
+    )
+    .snapshot();
+
+    assert_eq!(snapshot.screen, ScreenSnapshot::BetweenRounds);
+    assert_eq!(snapshot.map, 1);
+    assert_eq!(snapshot.difficulty, HARD);
+    assert_eq!(snapshot.game_mode, STANDARD);
+    assert_eq!(snapshot.round, 0);
+}
+
+fn assert_rounds_and_pauses<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = start_round(start_game(new_simulation(0), EASY, STANDARD));
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Playing);
+    assert_eq!(simulation.snapshot().round, 1);
+
+    let pause = Keys {
+        pause: PRESSED,
+        ..NO_KEYS
+    };
+    let simulation = press(simulation, pause.clone());
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Paused);
+
+    let simulation = press(simulation, pause);
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Playing);
+}
+
+fn assert_defeat_resets<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let simulation = wait(
+        start_round(start_game(new_simulation(0), IMPOPPABLE, STANDARD)),
+        ROUND_TICKS,
+    );
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Defeat);
+
+    let snapshot = confirm(simulation).snapshot();
+    let difficulty = DIFFICULTIES[IMPOPPABLE];
+
+    assert_eq!(snapshot.screen, ScreenSnapshot::BetweenRounds);
+    assert_eq!(snapshot.round, 0);
+    assert_eq!(snapshot.coins, starting_cash(difficulty));
+    assert_eq!(snapshot.lives, starting_lives(difficulty));
+    assert!(snapshot.balloons.is_empty());
+}
+
+// Sandbox balloons escape for free, so every round can be waited out at
+// triple speed without towers.
+fn assert_victory_returns_to_the_menu<S: GameSimulation>(new_simulation: fn(u64) -> S) {
+    let change_game_speed = Keys {
+        change_game_speed: PRESSED,
+        ..NO_KEYS
+    };
+    let simulation = start_game(new_simulation(0), EASY, SANDBOX);
+    let simulation = press(
+        press(simulation, change_game_speed.clone()),
+        change_game_speed,
+    );
+
+    let simulation = (0..final_round(DIFFICULTIES[EASY])).fold(simulation, |simulation, _| {
+        assert_eq!(simulation.snapshot().screen, ScreenSnapshot::BetweenRounds);
+
+        wait(start_round(simulation), ROUND_TICKS)
+    });
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::Victory);
+
+    let simulation = confirm(simulation);
+
+    assert_eq!(simulation.snapshot().screen, ScreenSnapshot::MainMenu);
+}
+
+#[test]
+fn menus_lead_to_a_new_game_and_ignore_invalid_options() {
+    assert_menu_flow(new_scene);
+    assert_menu_flow(Scene::new);
+    assert_menu_flow(EcsScene::new);
+}
+
+#[test]
+fn rounds_start_and_pause() {
+    assert_rounds_and_pauses(new_scene);
+    assert_rounds_and_pauses(Scene::new);
+    assert_rounds_and_pauses(EcsScene::new);
+}
+
+#[test]
+fn confirming_a_defeat_starts_over() {
+    assert_defeat_resets(new_scene);
+    assert_defeat_resets(Scene::new);
+    assert_defeat_resets(EcsScene::new);
+}
+
+#[test]
+fn confirming_a_victory_returns_to_the_menu() {
+    assert_victory_returns_to_the_menu(new_scene);
+    assert_victory_returns_to_the_menu(Scene::new);
+    assert_victory_returns_to_the_menu(EcsScene::new);
+}
@@ -0,0 +1,207 @@
+// Checks the shop layout and that buying from it places towers in the
+// functional scene.
+
+use macroquad::prelude::*;
+
+use bloonstd7::functional::scene::new_scene;
+use bloonstd7::functional::scene::GameState;
+use bloonstd7::input::Keys;
+use bloonstd7::input::CLICKED;
+use bloonstd7::input::HELD;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::shop::chosen_shop_item;
+use bloonstd7::shop::clicked_shop_item;
+use bloonstd7::shop::is_on_sidebar;
+use bloonstd7::shop::shop_button_rect;
+use bloonstd7::shop::shop_item_at;
+use bloonstd7::shop::sidebar_rect;
+use bloonstd7::shop::step_shop_item;
+use bloonstd7::shop::SHOP_ITEMS;
+use bloonstd7::simulation::GameSimulation;
+use bloonstd7::world::is_in_world;
+use bloonstd7::world::WORLD_WIDTH;
+
+const DELTA_TIME: f32 = 1. / 60.;
+
+const TOWER_POSITION: Vec2 = Vec2::new(300., 200.);
+
+fn new_game() -> GameState {
+    [
+        Keys {
+            confirm: PRESSED,
+            ..NO_KEYS
+        },
+        Keys {
+            select_map: Some(0),
+            ..NO_KEYS
+        },
+        Keys {
+            // Medium
+            select_difficulty: Some(1),
+            ..NO_KEYS
+        },
+        Keys {
+            // Standard
+            select_game_mode: Some(0),
+            ..NO_KEYS
+        },
+    ]
+    .into_iter()
+    .fold(new_scene(0), |state, keys| state.step(DELTA_TIME, keys))
+}
+
+// Unzoomed, the world and the interface line up.
+fn click(state: GameState, position: Vec2) -> GameState {
+    click_zoomed(state, position, position)
+}
+
+fn click_zoomed(state: GameState, position: Vec2, interface_position: Vec2) -> GameState {
+    state.step(
+        DELTA_TIME,
+        Keys {
+            place_tower: CLICKED,
+            pointer_position: position,
+            interface_pointer_position: interface_position,
+            ..NO_KEYS
+        },
+    )
+}
+
+fn button_center(index: usize) -> Vec2 {
+    shop_button_rect(index).center()
+}
+
+#[test]
+fn every_button_is_on_the_sidebar() {
+    (0..SHOP_ITEMS.len()).for_each(|index| {
+        let button = shop_button_rect(index);
+
+        assert!(sidebar_rect().contains(button.point()));
+        assert!(sidebar_rect().contains(button.point() + button.size()));
+        assert_eq!(shop_item_at(button_center(index)), Some(index));
+    });
+}
+
+#[test]
+fn only_presses_on_a_button_click_it() {
+    let on_button = Keys {
+        place_tower: PRESSED,
+        interface_pointer_position: button_center(0),
+        ..NO_KEYS
+    };
+
+    assert_eq!(clicked_shop_item(&on_button), Some(0));
+    assert_eq!(
+        clicked_shop_item(&Keys {
+            place_tower: HELD,
+            ..on_button.clone()
+        }),
+        None
+    );
+    assert_eq!(
+        clicked_shop_item(&Keys {
+            interface_pointer_position: TOWER_POSITION,
+            ..on_button
+        }),
+        None
+    );
+    assert!(!is_on_sidebar(TOWER_POSITION));
+}
+
+#[test]
+fn stepping_wraps_around_the_items() {
+    let last = SHOP_ITEMS.len() - 1;
+
+    assert_eq!(step_shop_item(None, true), 0);
+    assert_eq!(step_shop_item(None, false), last);
+    assert_eq!(step_shop_item(Some(last), true), 0);
+    assert_eq!(step_shop_item(Some(0), false), last);
+}
+
+#[test]
+fn stepped_items_are_chosen_without_a_click() {
+    let stepped = Keys {
+        select_shop_item: Some(0),
+        ..NO_KEYS
+    };
+
+    assert_eq!(clicked_shop_item(&stepped), None);
+    assert_eq!(chosen_shop_item(&stepped), Some(0));
+}
+
+#[test]
+fn stepping_to_an_item_starts_placing_a_tower() {
+    let state = new_game().step(
+        DELTA_TIME,
+        Keys {
+            select_shop_item: Some(0),
+            ..NO_KEYS
+        },
+    );
+    let state = click(state, TOWER_POSITION);
+
+    assert_eq!(state.snapshot().towers.len(), 1);
+}
+
+#[test]
+fn clicking_a_button_starts_placing_a_tower() {
+    let state = click(click(new_game(), button_center(0)), TOWER_POSITION);
+
+    let towers = state.snapshot().towers;
+
+    assert_eq!(towers.len(), 1);
+    assert_eq!(towers[0].position, TOWER_POSITION);
+}
+
+#[test]
+fn disabled_buttons_do_nothing() {
+    // Medium starts with enough coins for two towers.
+    let state = (0..3).fold(new_game(), |state, index| {
+        click(
+            click(state, button_center(0)),
+            TOWER_POSITION + Vec2::new(index as f32 * 100., 0.),
+        )
+    });
+
+    assert_eq!(state.snapshot().towers.len(), 2);
+}
+
+#[test]
+fn towers_are_not_placed_on_top_of_each_other() {
+    let state = click(click(new_game(), button_center(0)), TOWER_POSITION);
+    let state = click(
+        click(state, button_center(0)),
+        TOWER_POSITION + Vec2::new(40., 0.),
+    );
+
+    assert_eq!(state.snapshot().towers.len(), 1);
+}
+
+#[test]
+fn the_sidebar_does_not_cover_the_map() {
+    let near_edge = Vec2::new(WORLD_WIDTH - 30., 300.);
+
+    assert!(!is_on_sidebar(near_edge));
+    assert!(!is_in_world(sidebar_rect().center()));
+
+    let state = click(click(new_game(), button_center(0)), near_edge);
+
+    assert_eq!(state.snapshot().towers[0].position, near_edge);
+}
+
+#[test]
+fn the_map_is_picked_zoomed_and_the_sidebar_unzoomed() {
+    let near_edge = Vec2::new(WORLD_WIDTH - 75., 300.);
+    // Zoomed in twice on the right half of the map, that spot is left of the
+    // middle of the frame.
+    let interface_position = Vec2::new(250., 300.);
+
+    let state = click(new_game(), button_center(0));
+    let state = click_zoomed(state, near_edge, interface_position);
+
+    let towers = state.snapshot().towers;
+
+    assert_eq!(towers.len(), 1);
+    assert_eq!(towers[0].position, near_edge);
+}
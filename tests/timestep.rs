@@ -0,0 +1,143 @@
+// Checks how frames of input are turned into fixed ticks, so that no press is
+// lost when frames are shorter than a tick, and none is repeated when they
+// are longer.
+
+use macroquad::prelude::*;
+
+use bloonstd7::input::held_keys;
+use bloonstd7::input::merge_keys;
+use bloonstd7::input::Keys;
+use bloonstd7::input::HELD;
+use bloonstd7::input::IDLE;
+use bloonstd7::input::NO_KEYS;
+use bloonstd7::input::PRESSED;
+use bloonstd7::input::RELEASED;
+use bloonstd7::timestep::advance_timestep;
+use bloonstd7::timestep::new_timestep;
+use bloonstd7::timestep::timestep_alpha;
+use bloonstd7::timestep::Timestep;
+use bloonstd7::timestep::FIXED_DELTA_TIME;
+use bloonstd7::timestep::MAX_FRAME_TIME;
+
+// A frame drawn at four times the tick rate.
+const SHORT_FRAME: f32 = FIXED_DELTA_TIME / 4.;
+
+fn frames(frames: Vec<(f32, Keys)>) -> (Timestep, Vec<Keys>) {
+    frames.into_iter().fold(
+        (new_timestep(), Vec::new()),
+        |(timestep, mut ticks), (frame_time, keys)| {
+            let (timestep, frame_ticks) = advance_timestep(timestep, frame_time, keys);
+
+            ticks.extend(frame_ticks);
+            (timestep, ticks)
+        },
+    )
+}
+
+#[test]
+fn presses_shorter_than_a_tick_reach_the_next_tick() {
+    let (_, ticks) = frames(vec![
+        (
+            SHORT_FRAME,
+            Keys {
+                place_tower: PRESSED,
+                ..NO_KEYS
+            },
+        ),
+        (
+            SHORT_FRAME,
+            Keys {
+                place_tower: RELEASED,
+                ..NO_KEYS
+            },
+        ),
+        (SHORT_FRAME, NO_KEYS),
+        (SHORT_FRAME, NO_KEYS),
+    ]);
+
+    assert_eq!(ticks.len(), 1);
+    assert!(ticks[0].place_tower.pressed);
+    assert!(ticks[0].place_tower.released);
+    assert!(!ticks[0].place_tower.held);
+}
+
+#[test]
+fn presses_are_applied_once_on_slow_frames() {
+    let (_, ticks) = frames(vec![(
+        FIXED_DELTA_TIME * 3.5,
+        Keys {
+            start_round: PRESSED,
+            place_tower: PRESSED,
+            ..NO_KEYS
+        },
+    )]);
+
+    assert_eq!(ticks.len(), 3);
+    assert_eq!(ticks[0].start_round, PRESSED);
+    assert_eq!(ticks[1].start_round, HELD);
+    assert_eq!(ticks[2].place_tower, HELD);
+}
+
+#[test]
+fn leftover_time_carries_over_to_the_next_frame() {
+    let (timestep, ticks) = frames(vec![(FIXED_DELTA_TIME * 1.5, NO_KEYS)]);
+
+    assert_eq!(ticks.len(), 1);
+    assert!((timestep_alpha(&timestep) - 0.5).abs() < 1e-4);
+
+    let (_, ticks) = advance_timestep(timestep, FIXED_DELTA_TIME * 0.5, NO_KEYS);
+
+    assert_eq!(ticks.len(), 1);
+}
+
+#[test]
+fn long_stalls_are_not_caught_up_in_full() {
+    let (_, ticks) = frames(vec![(10., NO_KEYS)]);
+    let (_, longest_ticks) = frames(vec![(MAX_FRAME_TIME, NO_KEYS)]);
+
+    assert_eq!(ticks.len(), longest_ticks.len());
+}
+
+#[test]
+fn merged_keys_keep_presses_and_take_the_latest_pointer() {
+    let keys = merge_keys(
+        Keys {
+            undo: PRESSED,
+            select_map: Some(2),
+            pointer_position: Vec2::new(10., 10.),
+            pointer_delta: Vec2::new(1., 0.),
+            ..NO_KEYS
+        },
+        Keys {
+            undo: RELEASED,
+            pointer_position: Vec2::new(20., 10.),
+            pointer_delta: Vec2::new(2., 1.),
+            ..NO_KEYS
+        },
+    );
+
+    assert!(keys.undo.pressed && keys.undo.released && !keys.undo.held);
+    assert_eq!(keys.select_map, Some(2));
+    assert_eq!(keys.pointer_position, Vec2::new(20., 10.));
+    assert_eq!(keys.pointer_delta, Vec2::new(3., 1.));
+}
+
+#[test]
+fn held_keys_drop_presses_releases_and_selections() {
+    let keys = held_keys(&Keys {
+        nudge_left: PRESSED,
+        confirm: RELEASED,
+        select_difficulty: Some(1),
+        pointer_position: Vec2::new(5., 5.),
+        pointer_delta: Vec2::new(4., 0.),
+        grid_size: Some(25.),
+        ..NO_KEYS
+    });
+
+    assert_eq!(keys.nudge_left, HELD);
+    assert_eq!(keys.confirm, IDLE);
+    assert_eq!(keys.select_difficulty, None);
+    assert_eq!(keys.pointer_position, Vec2::new(5., 5.));
+    assert_eq!(keys.pointer_delta, Vec2::ZERO);
+    assert_eq!(keys.grid_size, Some(25.));
+}
@@ -0,0 +1,204 @@
+// Checks that the world and the sidebar are fitted into windows of any shape,
+// that the view zooms and pans within the map, and that window positions map
+// back to the same place in the world.
+
+use macroquad::prelude::*;
+
+use bloonstd7::view::clamp_view;
+use bloonstd7::view::edge_pan_direction;
+use bloonstd7::view::interface_viewport;
+use bloonstd7::view::pan_view;
+use bloonstd7::view::screen_to_interface;
+use bloonstd7::view::screen_to_world;
+use bloonstd7::view::world_bounds;
+use bloonstd7::view::world_scale;
+use bloonstd7::view::world_viewport;
+use bloonstd7::view::zoom_view;
+use bloonstd7::view::View;
+use bloonstd7::view::DEFAULT_VIEW;
+use bloonstd7::view::INTERFACE_HEIGHT;
+use bloonstd7::view::INTERFACE_WIDTH;
+use bloonstd7::view::MAX_ZOOM;
+use bloonstd7::view::MIN_ZOOM;
+use bloonstd7::world::WORLD_HEIGHT;
+use bloonstd7::world::WORLD_WIDTH;
+
+const WORLD_SIZE: Vec2 = Vec2::new(WORLD_WIDTH, WORLD_HEIGHT);
+const FRAME_SIZE: Vec2 = Vec2::new(INTERFACE_WIDTH, INTERFACE_HEIGHT);
+const WIDE_SCREEN: Vec2 = Vec2::new(1850., 600.);
+const TALL_SCREEN: Vec2 = Vec2::new(475., 900.);
+
+const ZOOMED_VIEW: View = View {
+    center: Vec2::new(200., 150.),
+    zoom: 2.,
+};
+
+#[test]
+fn a_window_the_size_of_the_frame_is_filled() {
+    assert_eq!(
+        interface_viewport(FRAME_SIZE),
+        Rect::new(0., 0., INTERFACE_WIDTH, INTERFACE_HEIGHT)
+    );
+    assert_eq!(
+        world_viewport(FRAME_SIZE),
+        Rect::new(0., 0., WORLD_WIDTH, WORLD_HEIGHT)
+    );
+    assert_eq!(
+        screen_to_world(Vec2::new(123., 456.), FRAME_SIZE, &DEFAULT_VIEW),
+        Vec2::new(123., 456.)
+    );
+}
+
+#[test]
+fn the_sidebar_is_beside_the_world() {
+    let sidebar = Vec2::new(875., 80.);
+
+    assert_eq!(screen_to_interface(sidebar, FRAME_SIZE), sidebar);
+    assert!(!world_viewport(FRAME_SIZE).contains(sidebar));
+    assert!(screen_to_world(sidebar, FRAME_SIZE, &DEFAULT_VIEW).x > WORLD_WIDTH);
+}
+
+#[test]
+fn wide_windows_have_bars_at_the_sides() {
+    assert_eq!(
+        world_viewport(WIDE_SCREEN),
+        Rect::new(450., 0., WORLD_WIDTH, WORLD_HEIGHT)
+    );
+    assert_eq!(world_scale(WIDE_SCREEN), 1.);
+    assert_eq!(
+        screen_to_world(Vec2::new(450., 0.), WIDE_SCREEN, &DEFAULT_VIEW),
+        Vec2::ZERO
+    );
+}
+
+#[test]
+fn tall_windows_have_bars_above_and_below() {
+    assert_eq!(world_viewport(TALL_SCREEN), Rect::new(0., 300., 400., 300.));
+    assert_eq!(world_scale(TALL_SCREEN), 0.5);
+    assert_eq!(
+        screen_to_world(Vec2::new(400., 600.), TALL_SCREEN, &DEFAULT_VIEW),
+        WORLD_SIZE
+    );
+}
+
+#[test]
+fn resizing_keeps_the_pointer_at_the_same_world_position() {
+    let center = screen_to_world(
+        world_viewport(WIDE_SCREEN).center(),
+        WIDE_SCREEN,
+        &ZOOMED_VIEW,
+    );
+
+    assert_eq!(center, ZOOMED_VIEW.center);
+    assert_eq!(
+        screen_to_world(
+            world_viewport(TALL_SCREEN).center(),
+            TALL_SCREEN,
+            &ZOOMED_VIEW
+        ),
+        center
+    );
+}
+
+#[test]
+fn zoomed_views_show_a_smaller_part_of_the_world() {
+    assert_eq!(
+        screen_to_world(Vec2::ZERO, FRAME_SIZE, &ZOOMED_VIEW),
+        Vec2::ZERO
+    );
+    assert_eq!(
+        screen_to_world(WORLD_SIZE, FRAME_SIZE, &ZOOMED_VIEW),
+        WORLD_SIZE / 2.
+    );
+}
+
+#[test]
+fn zooming_keeps_the_anchor_in_place() {
+    let pointer = Vec2::new(100., 500.);
+    let anchor = screen_to_world(pointer, FRAME_SIZE, &DEFAULT_VIEW);
+
+    let view = zoom_view(DEFAULT_VIEW, 2., anchor);
+
+    assert_eq!(view.zoom, 1.5625);
+    assert!(
+        screen_to_world(pointer, FRAME_SIZE, &view).distance(anchor) < 0.001,
+        "the anchor moved"
+    );
+}
+
+#[test]
+fn zooming_stops_at_the_limits() {
+    assert_eq!(
+        zoom_view(DEFAULT_VIEW, -1., DEFAULT_VIEW.center).zoom,
+        MIN_ZOOM
+    );
+    assert_eq!(
+        zoom_view(DEFAULT_VIEW, 100., DEFAULT_VIEW.center).zoom,
+        MAX_ZOOM
+    );
+}
+
+#[test]
+fn views_are_kept_on_the_map() {
+    assert_eq!(
+        clamp_view(pan_view(DEFAULT_VIEW, Vec2::new(50., -20.)), world_bounds()),
+        DEFAULT_VIEW
+    );
+    assert_eq!(
+        clamp_view(
+            pan_view(ZOOMED_VIEW, Vec2::new(-500., 1000.)),
+            world_bounds()
+        ),
+        View {
+            center: Vec2::new(200., 450.),
+            zoom: 2.,
+        }
+    );
+}
+
+#[test]
+fn maps_smaller_than_the_frame_are_zoomed_into() {
+    let bounds = Rect::new(0., 0., WORLD_WIDTH / 2., WORLD_HEIGHT);
+
+    assert_eq!(
+        clamp_view(DEFAULT_VIEW, bounds),
+        View {
+            center: Vec2::new(200., 300.),
+            zoom: 2.,
+        }
+    );
+}
+
+#[test]
+fn resting_against_the_edges_pans() {
+    assert_eq!(
+        edge_pan_direction(Vec2::new(5., 300.), FRAME_SIZE),
+        Vec2::new(-1., 0.)
+    );
+    assert_eq!(
+        edge_pan_direction(Vec2::new(795., 595.), FRAME_SIZE),
+        Vec2::new(1., 1.)
+    );
+    assert_eq!(edge_pan_direction(WORLD_SIZE / 2., FRAME_SIZE), Vec2::ZERO);
+    // The sidebar can be used without panning.
+    assert_eq!(
+        edge_pan_direction(Vec2::new(875., 595.), FRAME_SIZE),
+        Vec2::ZERO
+    );
+    // The edges are those of the world, not of the window.
+    assert_eq!(
+        edge_pan_direction(Vec2::new(455., 300.), WIDE_SCREEN),
+        Vec2::new(-1., 0.)
+    );
+}
+
+#[test]
+fn the_interface_is_not_zoomed() {
+    let point = Vec2::new(725., 80.);
+
+    assert_eq!(screen_to_interface(point, FRAME_SIZE), point);
+    assert_eq!(
+        screen_to_world(point, FRAME_SIZE, &ZOOMED_VIEW),
+        Vec2::new(362.5, 40.)
+    );
+}